@@ -1,15 +1,228 @@
 use anyhow::Result;
-use std::collections::HashSet;
+use log::{debug, info};
+use nalgebra::ComplexField;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::time::{Duration, Instant};
 
+use crate::geometry::{geometry_operations, Real};
 use crate::mesh3d::{manifold_mesh3d, ManifoldMesh3D};
 
 use super::sub_algorithms::DelaunayInterface;
 
-fn extract_physical_edges(
-    mesh: &ManifoldMesh3D,
-    ang_max: Option<f32>,
+#[derive(Clone, Debug)]
+/// Summary of a conversion to Delaunay mesh
+pub struct DelaunayReport {
+    /// Number of vertices before conversion
+    pub initial_vertices: usize,
+    /// Number of edges before conversion
+    pub initial_edges: usize,
+    /// Number of faces before conversion
+    pub initial_faces: usize,
+    /// Number of vertices after conversion
+    pub final_vertices: usize,
+    /// Number of edges after conversion
+    pub final_edges: usize,
+    /// Number of faces after conversion
+    pub final_faces: usize,
+    /// Number of edge flips
+    pub flips: usize,
+    /// Number of edge splits
+    pub edge_splits: usize,
+    /// Number of face splits
+    pub face_splits: usize,
+    /// Number of splits due to refinement bounds
+    pub refinement_splits: usize,
+    /// Indices of inserted vertices
+    pub inserted_vertices: Vec<usize>,
+    /// Tetrahedralization of the Delaunay mesh, as vertex indices (empty if a limit was reached)
+    pub tetrahedra: Vec<[usize; 4]>,
+    /// Conversion duration
+    pub elapsed: Duration,
+}
+
+impl DelaunayReport {
+    /// Report as a JSON object (tetrahedra excepted)
+    pub fn to_json(&self) -> String {
+        let inserted: Vec<String> = self
+            .inserted_vertices
+            .iter()
+            .map(|ind| ind.to_string())
+            .collect();
+        let fields = [
+            ("initial_vertices", self.initial_vertices.to_string()),
+            ("initial_edges", self.initial_edges.to_string()),
+            ("initial_faces", self.initial_faces.to_string()),
+            ("final_vertices", self.final_vertices.to_string()),
+            ("final_edges", self.final_edges.to_string()),
+            ("final_faces", self.final_faces.to_string()),
+            ("flips", self.flips.to_string()),
+            ("edge_splits", self.edge_splits.to_string()),
+            ("face_splits", self.face_splits.to_string()),
+            ("refinement_splits", self.refinement_splits.to_string()),
+            ("inserted_vertices", format!("[{}]", inserted.join(", "))),
+            ("elapsed_secs", self.elapsed.as_secs_f64().to_string()),
+        ];
+        let lines: Vec<String> = fields
+            .iter()
+            .map(|(key, val)| format!("  \"{}\": {}", key, val))
+            .collect();
+        format!("{{\n{}\n}}", lines.join(",\n"))
+    }
+
+    /// Report with initial counts of a mesh
+    fn start<T: Real>(mesh: &ManifoldMesh3D<T>) -> DelaunayReport {
+        DelaunayReport {
+            initial_vertices: mesh.get_nb_vertices(),
+            initial_edges: mesh.get_nb_halfedges() >> 1,
+            initial_faces: mesh.get_nb_faces(),
+            final_vertices: mesh.get_nb_vertices(),
+            final_edges: mesh.get_nb_halfedges() >> 1,
+            final_faces: mesh.get_nb_faces(),
+            flips: 0,
+            edge_splits: 0,
+            face_splits: 0,
+            refinement_splits: 0,
+            inserted_vertices: Vec::new(),
+            tetrahedra: Vec::new(),
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Sets final counts of a mesh
+    fn finish<T: Real>(&mut self, mesh: &ManifoldMesh3D<T>, elapsed: Duration) {
+        self.final_vertices = mesh.get_nb_vertices();
+        self.final_edges = mesh.get_nb_halfedges() >> 1;
+        self.final_faces = mesh.get_nb_faces();
+        self.inserted_vertices = (self.initial_vertices..self.final_vertices).collect();
+        self.elapsed = elapsed;
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+/// Limits of a conversion to Delaunay mesh (no limit if not given)
+///
+/// The conversion stops before an iteration that would exceed the iteration limit, or before a
+/// vertex insertion that would exceed the inserted vertices limit, so that a conversion ending
+/// within the limits succeeds
+pub struct DelaunayLimits {
+    /// Maximum number of inserted vertices
+    pub max_inserted_vertices: Option<usize>,
+    /// Maximum number of iterations (flip or split of a non Delaunay element, refinement split)
+    pub max_iterations: Option<usize>,
+    /// Time budget
+    pub time_budget: Option<Duration>,
+}
+
+impl DelaunayLimits {
+    /// First limit reached before an iteration, if any
+    ///
+    /// The inserted vertices limit is only checked if the iteration inserts a vertex
+    fn reached(
+        &self,
+        iterations: usize,
+        inserting: bool,
+        inserted: usize,
+        elapsed: Duration,
+    ) -> Option<DelaunayLimit> {
+        if inserting
+            && self
+                .max_inserted_vertices
+                .is_some_and(|max| inserted >= max)
+        {
+            Some(DelaunayLimit::InsertedVertices(inserted))
+        } else if self.max_iterations.is_some_and(|max| iterations >= max) {
+            Some(DelaunayLimit::Iterations(iterations))
+        } else if self.time_budget.is_some_and(|budget| elapsed >= budget) {
+            Some(DelaunayLimit::Time(elapsed))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Limit reached by a conversion to Delaunay mesh
+pub enum DelaunayLimit {
+    /// Too many inserted vertices (number inserted)
+    InsertedVertices(usize),
+    /// Too many iterations (number done)
+    Iterations(usize),
+    /// Time budget exceeded (elapsed time)
+    Time(Duration),
+}
+
+impl fmt::Display for DelaunayLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DelaunayLimit::InsertedVertices(nb) => write!(f, "{} inserted vertices", nb),
+            DelaunayLimit::Iterations(nb) => write!(f, "{} iterations", nb),
+            DelaunayLimit::Time(elapsed) => write!(f, "{}s elapsed", elapsed.as_secs_f64()),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Error of a conversion to Delaunay mesh stopped by a limit
+///
+/// The partially converted mesh is the one given to the conversion
+pub struct DelaunayLimitError {
+    /// Limit reached
+    pub limit: DelaunayLimit,
+    /// Remaining non Delaunay edges (vertex indices)
+    pub non_del_edges: Vec<[usize; 2]>,
+    /// Remaining non Delaunay faces (vertex indices)
+    pub non_del_faces: Vec<[usize; 3]>,
+    /// Report of the conversion up to the limit
+    pub report: DelaunayReport,
+}
+
+impl DelaunayLimitError {
+    fn new<T: Real>(
+        limit: DelaunayLimit,
+        deltet: &DelaunayInterface<T>,
+        report: DelaunayReport,
+    ) -> Result<DelaunayLimitError> {
+        let mut non_del_edges = Vec::new();
+        for ind_he in deltet.get_all_non_del_halfedge()? {
+            let mut edge = deltet.get_mesh().get_halfedge(ind_he)?.halfedge();
+            edge.sort();
+            non_del_edges.push(edge);
+        }
+        non_del_edges.sort();
+        non_del_edges.dedup();
+        let mut non_del_faces = Vec::new();
+        for ind_face in deltet.get_all_non_del_face()? {
+            non_del_faces.push(deltet.get_mesh().get_face(ind_face)?.vertices_inds());
+        }
+        Ok(DelaunayLimitError {
+            limit,
+            non_del_edges,
+            non_del_faces,
+            report,
+        })
+    }
+}
+
+impl fmt::Display for DelaunayLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Delaunay conversion stopped after {}: {} non delaunay edge(s), {} non delaunay face(s) remaining",
+            self.limit,
+            self.non_del_edges.len(),
+            self.non_del_faces.len()
+        )
+    }
+}
+
+impl std::error::Error for DelaunayLimitError {}
+
+fn extract_physical_edges<T: Real>(
+    mesh: &ManifoldMesh3D<T>,
+    ang_max: Option<T>,
 ) -> Result<HashSet<manifold_mesh3d::HalfEdge>> {
-    let ang_max = ang_max.unwrap_or(std::f32::consts::PI);
+    let ang_max = ang_max.unwrap_or(T::pi());
     let cos_min = ang_max.cos();
 
     let mut physical: HashSet<manifold_mesh3d::HalfEdge> = HashSet::new();
@@ -20,7 +233,7 @@ fn extract_physical_edges(
             continue;
         }
 
-        if ang_max == std::f32::consts::PI {
+        if ang_max == T::pi() {
             physical.insert(he.halfedge());
         }
 
@@ -58,7 +271,7 @@ fn extract_physical_edges(
         let nor_2 = vec_u_2.cross(&vec_v_2);
 
         // cosinus between normals
-        let cos_cur = nor_1.dot(&nor_2).abs();
+        let cos_cur = ComplexField::abs(nor_1.dot(&nor_2));
 
         if cos_cur > cos_min {
             physical.insert(he.halfedge());
@@ -67,10 +280,40 @@ fn extract_physical_edges(
     Ok(physical)
 }
 
-fn compute_halfedge_split_vertex(
-    deltet: &DelaunayInterface,
+/// Adds given feature edges to physical edges
+fn add_feature_edges<T: Real>(
+    mesh: &ManifoldMesh3D<T>,
+    feature_edges: &[[usize; 2]],
+    physical: &mut HashSet<manifold_mesh3d::HalfEdge>,
+) -> Result<()> {
+    for &[ind_v1, ind_v2] in feature_edges.iter() {
+        if mesh.is_edge_in(ind_v1, ind_v2).is_none() && mesh.is_edge_in(ind_v2, ind_v1).is_none() {
+            return Err(anyhow::Error::msg(format!(
+                "add_feature_edges(): Feature edge ({}, {}) is not a mesh edge",
+                ind_v1, ind_v2
+            )));
+        }
+        physical.insert([ind_v1.min(ind_v2), ind_v1.max(ind_v2)]);
+    }
+    Ok(())
+}
+
+/// Replaces a split physical edge with its two halves
+fn split_physical_edge(
+    physical: &mut HashSet<manifold_mesh3d::HalfEdge>,
+    he_inds: manifold_mesh3d::HalfEdge,
+    ind_new: usize,
+) {
+    if physical.remove(&he_inds) {
+        physical.insert([he_inds[0], ind_new]);
+        physical.insert([he_inds[1], ind_new]);
+    }
+}
+
+fn compute_halfedge_split_vertex<T: Real>(
+    deltet: &DelaunayInterface<T>,
     vertex_inds: [usize; 2],
-) -> Result<manifold_mesh3d::Vertex> {
+) -> Result<manifold_mesh3d::Vertex<T>> {
     let (vert1, vert2) = if deltet.is_original_vertex(vertex_inds[0]) {
         (
             deltet.get_mesh().get_vertex(vertex_inds[0])?.vertex(),
@@ -83,16 +326,18 @@ fn compute_halfedge_split_vertex(
         )
     };
 
-    let vert_mid = (vert1 + vert2) * 0.5;
-    let dist = (0.5 * (vert1 - vert2)).norm();
+    let half: T = nalgebra::convert(0.5);
+    let two: T = nalgebra::convert(2.0);
+    let vert_mid = (vert1 + vert2) * half;
+    let dist = ((vert1 - vert2) * half).norm();
     let k_f = dist.log2();
     let k_1 = k_f.floor();
-    let k_2 = k_1 + 1.0;
+    let k_2 = k_1 + T::one();
 
     let vec = (vert2 - vert1).normalize();
 
-    let vs1 = vert1 + vec * 2.0_f32.powf(k_1);
-    let vs2 = vert1 + vec * 2.0_f32.powf(k_2);
+    let vs1 = vert1 + vec * two.powf(k_1);
+    let vs2 = vert1 + vec * two.powf(k_2);
 
     let d1 = (vert_mid - vs1).norm();
     let d2 = (vert_mid - vs2).norm();
@@ -104,94 +349,454 @@ fn compute_halfedge_split_vertex(
     }
 }
 
-fn compute_face_split_vertex(face: manifold_mesh3d::IterFace) -> Result<manifold_mesh3d::Vertex> {
+fn compute_face_split_vertex<T: Real>(
+    face: manifold_mesh3d::IterFace<T>,
+) -> Result<manifold_mesh3d::Vertex<T>> {
     let [vert1, vert2, vert3] = face.vertices();
 
-    Ok((vert1.vertex() + vert2.vertex() + vert3.vertex()) / 3.0)
+    Ok((vert1.vertex() + vert2.vertex() + vert3.vertex()) / nalgebra::convert::<f64, T>(3.0))
+}
+
+fn shortest_edge_length<T: Real>(mesh: &ManifoldMesh3D<T>) -> Result<T> {
+    let mut length_min: Option<T> = None;
+    for &ind_he in mesh.halfedges().keys() {
+        let he = mesh.get_halfedge(ind_he)?;
+        let length = (he.last_vertex().vertex() - he.first_vertex().vertex()).norm();
+        length_min = match length_min {
+            Some(length_cur) if length_cur <= length => Some(length_cur),
+            _ => Some(length),
+        };
+    }
+    Ok(length_min.unwrap_or(T::zero()))
+}
+
+/// Refinement of a face violating the size or quality bounds
+enum Refinement<T> {
+    /// Split of a halfedge at its middle
+    Halfedge(usize),
+    /// Split of the face at a given vertex
+    Face(manifold_mesh3d::Vertex<T>),
+}
+
+/// Computes how to refine a face, if it violates the bounds
+///
+/// Too long faces get their longest edge split. Too sharp faces get their circumcenter inserted,
+/// or their longest edge split when the circumcenter is outside the face, as long as the new
+/// vertex stays at least length_min away from the face vertices (which stops the refinement
+/// near small angles of the input mesh)
+fn face_refinement<T: Real>(
+    face: manifold_mesh3d::IterFace<T>,
+    opt_max_edge_length: Option<T>,
+    opt_min_angle: Option<T>,
+    length_min: T,
+) -> Option<Refinement<T>> {
+    let half: T = nalgebra::convert(0.5);
+    let hes = face.halfedges();
+    let pts = hes.map(|he| he.first_vertex().vertex());
+    let vecs = [0, 1, 2].map(|i| pts[(i + 1) % 3] - pts[i]);
+    let lengths = vecs.map(|vec| vec.norm());
+    let ind_longest = (0..3).fold(0, |ind_max, i| {
+        if lengths[i] > lengths[ind_max] {
+            i
+        } else {
+            ind_max
+        }
+    });
+    let length_longest = lengths[ind_longest];
+
+    if opt_max_edge_length.is_some_and(|length_max| length_longest > length_max) {
+        return Some(Refinement::Halfedge(hes[ind_longest].ind()));
+    }
+
+    let too_sharp = opt_min_angle
+        .is_some_and(|angle_min| (0..3).any(|i| (-vecs[(i + 2) % 3]).angle(&vecs[i]) < angle_min));
+    if !too_sharp {
+        return None;
+    }
+
+    let pts_f64 = pts.map(|pt| pt.map(|x| x.to_f64()));
+    if let Some(center) = geometry_operations::circle_center_f64(pts_f64) {
+        let center = center.map(nalgebra::convert::<f64, T>);
+        let nor = vecs[0].cross(&vecs[1]);
+        let inside = (0..3).all(|i| vecs[i].cross(&(center - pts[i])).dot(&nor) > T::zero());
+        if inside && (center - pts[0]).norm() >= length_min {
+            return Some(Refinement::Face(center));
+        }
+    }
+    if length_longest * half >= length_min {
+        Some(Refinement::Halfedge(hes[ind_longest].ind()))
+    } else {
+        None
+    }
+}
+
+/// Fails with a DelaunayLimitError if one of the limits is reached before an iteration (inserting
+/// a vertex or not)
+fn check_limits<T: Real>(
+    limits: &DelaunayLimits,
+    deltet: &DelaunayInterface<T>,
+    report: &mut DelaunayReport,
+    iterations: usize,
+    inserting: bool,
+    start: Instant,
+) -> Result<()> {
+    let inserted = deltet.get_mesh().get_nb_vertices() - report.initial_vertices;
+    if let Some(limit) = limits.reached(iterations, inserting, inserted, start.elapsed()) {
+        report.finish(deltet.get_mesh(), start.elapsed());
+        return Err(DelaunayLimitError::new(limit, deltet, report.clone())?.into());
+    }
+    Ok(())
+}
+
+/// Flips the edges opposite to a vertex which are not locally Delaunay on the surface
+///
+/// Only non physical edges are flipped, and only if the flipped edge is in the tetrahedralization
+fn flip_around_vertex<T: Real>(
+    deltet: &mut DelaunayInterface<T>,
+    physical: &HashSet<manifold_mesh3d::HalfEdge>,
+    ind_vertex: usize,
+) -> Result<usize> {
+    let mut num_flip = 0;
+    let mut to_check: Vec<usize> = deltet
+        .get_mesh()
+        .get_vertex(ind_vertex)?
+        .halfedges()
+        .iter()
+        .filter_map(|he| he.next_halfedge())
+        .map(|he| he.ind())
+        .collect();
+
+    while let Some(ind_he) = to_check.pop() {
+        let he = if let Ok(he) = deltet.get_mesh().get_halfedge(ind_he) {
+            he
+        } else {
+            continue;
+        };
+        let (he_next, he_opp_next) = if let (Some(he_next), Some(he_opp_next)) = (
+            he.next_halfedge(),
+            he.opposite_halfedge()
+                .and_then(|he_opp| he_opp.next_halfedge()),
+        ) {
+            (he_next, he_opp_next)
+        } else {
+            continue;
+        };
+        if he_next.last_vertex().ind() != ind_vertex {
+            continue;
+        }
+        let [ind_1, ind_2] = he.halfedge();
+        let ind_opp = he_opp_next.last_vertex().ind();
+        if physical.contains(&[ind_1.min(ind_2), ind_1.max(ind_2)])
+            || !deltet.is_edge_in(&[ind_vertex, ind_opp])
+        {
+            continue;
+        }
+
+        let pt_1 = he.first_vertex().vertex();
+        let pt_2 = he.last_vertex().vertex();
+        let pt_vertex = he_next.last_vertex().vertex();
+        let pt_opp = he_opp_next.last_vertex().vertex();
+        let ang_vertex = (pt_1 - pt_vertex).angle(&(pt_2 - pt_vertex));
+        let ang_opp = (pt_1 - pt_opp).angle(&(pt_2 - pt_opp));
+        if ang_vertex + ang_opp <= T::pi() {
+            continue;
+        }
+
+        if deltet.flip_halfedge(ind_he)? {
+            num_flip += 1;
+            for ind_end in [ind_1, ind_2] {
+                if let Some(he_link) = deltet.get_mesh().is_edge_in(ind_end, ind_opp) {
+                    to_check.push(he_link.ind());
+                    to_check.extend(he_link.opposite_halfedge().map(|he| he.ind()));
+                }
+            }
+        }
+    }
+    Ok(num_flip)
+}
+
+/// Faces around given vertices
+fn faces_around<T: Real>(mesh: &ManifoldMesh3D<T>, vertices: &[usize]) -> Result<Vec<usize>> {
+    let mut faces = Vec::new();
+    for &ind_vertex in vertices {
+        for he in mesh.get_vertex(ind_vertex)?.halfedges() {
+            faces.extend(he.face().map(|face| face.ind()));
+        }
+    }
+    Ok(faces)
 }
 
 /// Modifies a manifold mesh to convert it into Delaunay mesh
-pub fn to_delaunay(mesh: &mut ManifoldMesh3D, ang_max: Option<f32>) -> Result<()> {
+///
+/// Giving a maximum edge length or a minimum triangle angle (radians) refines the surface
+/// further: once the mesh is Delaunay, faces violating these bounds are split (see
+/// face_refinement), the edges around the new vertex are flipped towards a surface Delaunay
+/// triangulation, and the mesh is brought back to Delaunay, until no face violates the bounds.
+/// The refinement does not insert vertices closer than half the shortest input edge. The minimum
+/// angle should not exceed 30 degrees, above which the refinement is not guaranteed to end
+///
+/// Physical edges (never flipped, only split) are given by the dihedral angle bound ang_max, and
+/// by the optional feature edges (pairs of vertex indices)
+///
+/// Returns a report of the conversion (counts, inserted vertices, duration). If one of the
+/// optional limits is reached, fails with a DelaunayLimitError and leaves the mesh partially
+/// converted
+pub fn to_delaunay<T: Real>(
+    mesh: &mut ManifoldMesh3D<T>,
+    ang_max: Option<T>,
+    opt_feature_edges: Option<&[[usize; 2]]>,
+    opt_max_edge_length: Option<T>,
+    opt_min_angle: Option<T>,
+    opt_limits: Option<DelaunayLimits>,
+) -> Result<DelaunayReport> {
+    let now = Instant::now();
+    let limits = opt_limits.unwrap_or_default();
+    if let Some(angle_min) = opt_min_angle {
+        if angle_min <= T::zero() || angle_min > T::frac_pi_6() {
+            return Err(anyhow::Error::msg(
+                "to_delaunay(): Minimum angle should be in ]0, 30] degrees",
+            ));
+        }
+    }
+    let mut report = DelaunayReport::start(mesh);
+
     let mut deltet = DelaunayInterface::from_mesh(mesh)?;
 
-    let physical = extract_physical_edges(deltet.get_mesh(), ang_max)?;
+    let mut physical = extract_physical_edges(deltet.get_mesh(), ang_max)?;
+    if let Some(feature_edges) = opt_feature_edges {
+        add_feature_edges(deltet.get_mesh(), feature_edges, &mut physical)?;
+    }
+
+    let refine = opt_max_edge_length.is_some() || opt_min_angle.is_some();
+    let length_min = shortest_edge_length(deltet.get_mesh())? * nalgebra::convert(0.5);
+    let mut faces_to_check: BTreeSet<usize> = if refine {
+        deltet.get_mesh().faces().keys().copied().collect()
+    } else {
+        BTreeSet::new()
+    };
 
     let mut nb_non_del_hedges = deltet.count_non_del_halfedges()?;
     let mut nb_non_del_faces = deltet.count_non_del_faces()?;
-    println!("Vertices: {}", deltet.get_mesh().get_nb_vertices());
-    println!(
+    info!("Vertices: {}", deltet.get_mesh().get_nb_vertices());
+    info!(
         "Non delaunay edges: {}/{}",
         nb_non_del_hedges >> 1,
         deltet.get_mesh().get_nb_halfedges() >> 1
     );
-    println!(
+    info!(
         "Non delaunay faces: {}/{}",
         nb_non_del_faces,
         deltet.get_mesh().get_nb_faces()
     );
 
-    let mut num_split_edge = 0;
-    let mut num_split_face = 0;
-    let mut num_flip = 0;
-    let mut shift_edge = 0;
-    let mut shift_face = 0;
+    let mut shift_edge = None;
+    let mut shift_face = None;
     let mut cpt_force_split = 0;
-
-    print!(
-        "\r{} flip(s), {} edge split(s), {} face split(s)",
-        num_flip, num_split_edge, num_split_face
-    );
+    let mut iterations = 0;
 
     loop {
-        if let Some(he) = deltet.get_non_del_halfedge(Some(shift_edge))? {
-            shift_edge = (shift_edge + 1) % deltet.get_mesh().get_nb_halfedges();
+        // limits are only checked when an element remains to be processed
+        if nb_non_del_hedges > 0 || nb_non_del_faces > 0 {
+            check_limits(&limits, &deltet, &mut report, iterations, false, now)?;
+        }
+
+        if let Some(he) = deltet.get_non_del_halfedge(shift_edge)? {
+            shift_edge = Some(he.ind());
             let mut he_inds = he.halfedge();
             he_inds.sort();
             let is_physical = physical.contains(&he_inds);
             let index_he = he.ind();
+            let opposite_vertices = [Some(he), he.opposite_halfedge()]
+                .iter()
+                .filter_map(|opt_he| opt_he.and_then(|he| he.next_halfedge()))
+                .map(|he| he.last_vertex().ind())
+                .collect::<Vec<usize>>();
             let flipped = if !is_physical && cpt_force_split < nb_non_del_hedges {
                 deltet.flip_halfedge(index_he)?
             } else {
                 false
             };
             if flipped {
-                num_flip = num_flip + 1;
+                report.flips += 1;
                 cpt_force_split = cpt_force_split + 1;
+                if refine {
+                    faces_to_check.extend(faces_around(deltet.get_mesh(), &opposite_vertices)?);
+                }
             } else {
+                check_limits(&limits, &deltet, &mut report, iterations, true, now)?;
                 let vert_split = compute_halfedge_split_vertex(&deltet, he_inds)?;
                 deltet.split_halfedge(&vert_split, index_he)?;
-                num_split_edge = num_split_edge + 1;
+                report.edge_splits += 1;
                 cpt_force_split = 0;
+                let ind_new = deltet.get_mesh().get_nb_vertices() - 1;
+                split_physical_edge(&mut physical, he_inds, ind_new);
+                if refine {
+                    faces_to_check.extend(faces_around(deltet.get_mesh(), &[ind_new])?);
+                }
             }
-        } else if let Some(face) = deltet.get_non_del_face(Some(shift_face))? {
-            shift_face = shift_face + 1;
+        } else if let Some(face) = deltet.get_non_del_face(shift_face)? {
+            shift_face = Some(face.ind());
+            check_limits(&limits, &deltet, &mut report, iterations, true, now)?;
             let vert_split = compute_face_split_vertex(face)?;
             deltet.split_face(&vert_split, face.ind())?;
-            num_split_face = num_split_face + 1;
+            report.face_splits += 1;
+            if refine {
+                let ind_new = deltet.get_mesh().get_nb_vertices() - 1;
+                faces_to_check.extend(faces_around(deltet.get_mesh(), &[ind_new])?);
+            }
+        } else if let Some(ind_face) = faces_to_check.pop_first() {
+            let opt_refinement = match deltet.get_mesh().get_face(ind_face) {
+                Ok(face) => face_refinement(face, opt_max_edge_length, opt_min_angle, length_min),
+                Err(_) => None,
+            };
+            if opt_refinement.is_some() {
+                check_limits(&limits, &deltet, &mut report, iterations, true, now)?;
+            }
+            match opt_refinement {
+                Some(Refinement::Halfedge(ind_he)) => {
+                    let he = deltet.get_mesh().get_halfedge(ind_he)?;
+                    let mut he_inds = he.halfedge();
+                    he_inds.sort();
+                    let half: T = nalgebra::convert(0.5);
+                    let vert_split =
+                        (he.first_vertex().vertex() + he.last_vertex().vertex()) * half;
+                    deltet.split_halfedge(&vert_split, ind_he)?;
+                    let ind_new = deltet.get_mesh().get_nb_vertices() - 1;
+                    split_physical_edge(&mut physical, he_inds, ind_new);
+                }
+                Some(Refinement::Face(vert_split)) => deltet.split_face(&vert_split, ind_face)?,
+                None => continue,
+            }
+            report.refinement_splits += 1;
+            let ind_new = deltet.get_mesh().get_nb_vertices() - 1;
+            report.flips += flip_around_vertex(&mut deltet, &physical, ind_new)?;
+            faces_to_check.extend(faces_around(deltet.get_mesh(), &[ind_new])?);
         } else {
             break;
         }
+        iterations += 1;
         nb_non_del_hedges = deltet.count_non_del_halfedges()?;
         nb_non_del_faces = deltet.count_non_del_faces()?;
-        print!("\r{} non del edges, {} non del faces, {} flip(s), {} edge split(s), {} face split(s)    ", 
-               nb_non_del_hedges >> 1, nb_non_del_faces, num_flip, num_split_edge, num_split_face);
+        debug!(
+            "{} non del edges, {} non del faces, {} flip(s), {} edge split(s), {} face split(s)",
+            nb_non_del_hedges >> 1,
+            nb_non_del_faces,
+            report.flips,
+            report.edge_splits,
+            report.face_splits
+        );
+    }
+    info!(
+        "{} flip(s), {} edge split(s), {} face split(s)",
+        report.flips, report.edge_splits, report.face_splits
+    );
+    if refine {
+        info!("{} refinement split(s)", report.refinement_splits);
     }
-    print!("\r{} flip(s), {} edge split(s), {} face split(s)                                                                          ", num_flip, num_split_edge, num_split_face);
-    println!("");
 
     nb_non_del_hedges = deltet.count_non_del_halfedges()?;
     nb_non_del_faces = deltet.count_non_del_faces()?;
-    println!("Vertices: {}", deltet.get_mesh().get_nb_vertices());
-    println!(
+    info!("Vertices: {}", deltet.get_mesh().get_nb_vertices());
+    info!(
         "Non delaunay edges: {}/{}",
         nb_non_del_hedges >> 1,
         deltet.get_mesh().get_nb_halfedges() >> 1
     );
-    println!(
+    info!(
         "Non delaunay faces: {}/{}",
         nb_non_del_faces,
         deltet.get_mesh().get_nb_faces()
     );
 
-    Ok(())
+    report.finish(deltet.get_mesh(), now.elapsed());
+    // the mesh may be Delaunay for several tetrahedralizations (cospherical vertices), keep this one
+    report.tetrahedra = deltet.get_tetrahedra().iter().copied().collect();
+    report.tetrahedra.sort();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh3d::mesh_operations;
+    use crate::mesh3d::test_meshes::jittered_bipyramid;
+
+    fn iteration_limit(max_iterations: usize) -> Option<DelaunayLimits> {
+        Some(DelaunayLimits {
+            max_iterations: Some(max_iterations),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn no_limit_reached_on_delaunay_mesh() {
+        // convex meshes are Delaunay
+        let mut mesh = jittered_bipyramid();
+        let report = to_delaunay(&mut mesh, None, None, None, None, iteration_limit(0)).unwrap();
+        assert_eq!(report.flips + report.edge_splits + report.face_splits, 0);
+    }
+
+    /// Bipyramid with a few edges from the top apex flipped into non Delaunay edges
+    fn dented_bipyramid() -> ManifoldMesh3D<f64> {
+        let mut mesh = jittered_bipyramid();
+        for ind_vertex in [2, 5, 8] {
+            let ind_he = mesh.is_edge_in(0, ind_vertex).unwrap().ind();
+            assert!(mesh_operations::flip_halfedge(&mut mesh, ind_he).unwrap());
+        }
+        mesh
+    }
+
+    #[test]
+    fn iteration_limit_is_inclusive() {
+        let mut mesh = dented_bipyramid();
+        let report = to_delaunay(&mut mesh, None, None, None, None, None).unwrap();
+        let iterations = report.flips + report.edge_splits + report.face_splits;
+        assert!(iterations > 0);
+
+        let mut mesh = dented_bipyramid();
+        let limits = iteration_limit(iterations);
+        assert!(to_delaunay(&mut mesh, None, None, None, None, limits).is_ok());
+
+        let mut mesh = dented_bipyramid();
+        let limits = iteration_limit(iterations - 1);
+        let err = to_delaunay(&mut mesh, None, None, None, None, limits).unwrap_err();
+        let limit_err = err.downcast_ref::<DelaunayLimitError>().unwrap();
+        assert_eq!(limit_err.limit, DelaunayLimit::Iterations(iterations - 1));
+    }
+
+    #[test]
+    fn inserted_vertices_limit_is_inclusive() {
+        let mut mesh = dented_bipyramid();
+        let max_edge_length = Some(0.8);
+        let report = to_delaunay(&mut mesh, None, None, max_edge_length, None, None).unwrap();
+        let inserted = report.inserted_vertices.len();
+        assert!(inserted > 0);
+
+        let limits = Some(DelaunayLimits {
+            max_inserted_vertices: Some(inserted),
+            ..Default::default()
+        });
+        let mut mesh = dented_bipyramid();
+        assert!(to_delaunay(&mut mesh, None, None, max_edge_length, None, limits).is_ok());
+
+        let limits = Some(DelaunayLimits {
+            max_inserted_vertices: Some(inserted - 1),
+            ..Default::default()
+        });
+        let mut mesh = dented_bipyramid();
+        let err = to_delaunay(&mut mesh, None, None, max_edge_length, None, limits).unwrap_err();
+        let limit_err = err.downcast_ref::<DelaunayLimitError>().unwrap();
+        assert_eq!(
+            limit_err.limit,
+            DelaunayLimit::InsertedVertices(inserted - 1)
+        );
+        assert_eq!(mesh.get_nb_vertices(), 11 + inserted - 1);
+    }
+
+    #[test]
+    fn minimum_angle_is_bounded() {
+        let mut mesh = jittered_bipyramid();
+        let min_angle = Some(35.0_f64.to_radians());
+        assert!(to_delaunay(&mut mesh, None, None, None, min_angle, None).is_err());
+    }
 }
@@ -1,5 +1,7 @@
 /// Delaunay mesh algorihm
 pub mod delaunay_alg;
+/// Progress observation of algorithms
+pub mod observer;
 /// Skeleton algorithm
 pub mod skeleton_alg;
 /// Sub operations used in algorithms
@@ -0,0 +1,79 @@
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// Progress event of a skeletonization
+pub enum SkeletonEvent {
+    /// Full skeleton propagation
+    Propagation {
+        /// Number of alveolae waiting to be handled
+        alveolae_remaining: usize,
+    },
+    /// New sheet skeletonization loop
+    LoopStarted {
+        /// Loop number (starting from 1)
+        loop_number: usize,
+    },
+    /// Sheet propagation
+    SheetPropagation {
+        /// Loop number (starting from 1)
+        loop_number: usize,
+        /// Number of sheets found so far
+        nb_sheets: usize,
+        /// Number of partial edges waiting to be handled
+        pedges_remaining: usize,
+    },
+    /// Boundary edges correction
+    BoundaryCorrection {
+        /// Loop number (starting from 1)
+        loop_number: usize,
+        /// Number of boundary partial edges waiting to be handled
+        pedges_remaining: usize,
+    },
+    /// Problematic edges correction
+    ProblematicCorrection {
+        /// Number of problematic partial edges waiting to be handled
+        pedges_remaining: usize,
+    },
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// Observer answer to a progress event
+pub enum ObserverAction {
+    /// Goes on with the computation
+    Continue,
+    /// Stops the computation, keeping the partial result
+    Stop,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+/// How an observed skeletonization ended
+pub enum SkeletonStatus {
+    /// Ran until the end
+    Completed,
+    /// Stopped by the observer, the result is partial
+    Stopped,
+    /// Stopped by an error (logged), the result is partial
+    Failed,
+}
+
+/// Receives skeletonization progress events
+pub trait SkeletonObserver {
+    /// Handles a progress event, and tells whether to go on
+    fn notify(&mut self, event: SkeletonEvent) -> ObserverAction;
+}
+
+impl<F: FnMut(SkeletonEvent) -> ObserverAction> SkeletonObserver for F {
+    fn notify(&mut self, event: SkeletonEvent) -> ObserverAction {
+        self(event)
+    }
+}
+
+/// Sends an event to an optional observer, returns true if the computation should stop
+pub(super) fn notify_stop(
+    opt_observer: &mut Option<&mut dyn SkeletonObserver>,
+    event: SkeletonEvent,
+) -> bool {
+    if let Some(observer) = opt_observer {
+        observer.notify(event) == ObserverAction::Stop
+    } else {
+        false
+    }
+}
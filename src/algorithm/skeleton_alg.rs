@@ -1,25 +1,97 @@
 use anyhow::Result;
+use log::{debug, info, warn};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::collections::HashMap;
 
 use crate::algorithm::sub_algorithms::SkeletonSeparation;
+use crate::geometry::Real;
 use crate::mesh3d::GenericMesh3D;
 use crate::mesh3d::ManifoldMesh3D;
 use crate::skeleton3d::Skeleton3D;
 
+use super::observer::{self, SkeletonEvent, SkeletonObserver, SkeletonStatus};
 use super::sub_algorithms::skeleton_operations;
 use super::sub_algorithms::SkeletonInterface3D;
 
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+/// Mesh connected components seeding the skeletonization
+pub enum ComponentSelection {
+    /// Single seed on a random face, only its component is skeletonized
+    #[default]
+    Single,
+    /// One seed per component
+    All,
+    /// One seed per component having at least the given number of faces
+    MinFaces(usize),
+}
+
+/// Random generator, reproducible if a seed is given
+fn seeded_rng(opt_seed: Option<u64>) -> StdRng {
+    match opt_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// First alveolae of the selected mesh components
+///
+/// Components that cannot be seeded are skipped, fails if none can be
+fn first_alveolae<T: Real>(
+    skeleton_interface: &mut SkeletonInterface3D<T>,
+    components: ComponentSelection,
+    rng: &mut StdRng,
+) -> Result<Vec<usize>> {
+    let min_faces = match components {
+        ComponentSelection::Single => {
+            return Ok(vec![skeleton_operations::first_alveola_in(
+                skeleton_interface,
+                rng,
+            )?]);
+        }
+        ComponentSelection::All => 0,
+        ComponentSelection::MinFaces(min_faces) => min_faces,
+    };
+
+    let mesh_components = skeleton_interface.get_mesh().connected_components();
+    debug!("{} mesh components", mesh_components.len());
+    let mut vec_alveola = Vec::new();
+    for component in mesh_components
+        .iter()
+        .filter(|component| component.len() >= min_faces)
+    {
+        match skeleton_operations::first_alveola_in_faces(skeleton_interface, component, rng) {
+            Ok(ind_alveola) => vec_alveola.push(ind_alveola),
+            Err(err) => warn!("Component of {} faces skipped: {}", component.len(), err),
+        }
+    }
+    if vec_alveola.is_empty() {
+        return Err(anyhow::Error::msg("No first alveola found"));
+    }
+    Ok(vec_alveola)
+}
+
 /// Computes the full skeletonization of a delaunay mesh
-pub fn full_skeletonization(mesh: &mut ManifoldMesh3D) -> Result<Skeleton3D> {
-    println!("Init skeleton interface");
+///
+/// Giving a seed makes the run reproducible.
+/// The observer is notified of progress, and may stop the computation (partial skeleton is then
+/// returned, with a stopped status)
+/// Components selects the mesh connected components to skeletonize
+pub fn full_skeletonization<T: Real>(
+    mesh: &mut ManifoldMesh3D<T>,
+    opt_seed: Option<u64>,
+    components: ComponentSelection,
+    mut opt_observer: Option<&mut dyn SkeletonObserver>,
+) -> Result<(Skeleton3D<T>, SkeletonStatus)> {
+    let mut rng = seeded_rng(opt_seed);
+
+    info!("Init skeleton interface");
     let mut skeleton_interface = SkeletonInterface3D::init(mesh)?;
 
-    println!("Finding some first alveola");
-    let ind_first_alveola = skeleton_operations::first_alveola_in(&mut skeleton_interface)?;
-    let mut vec_alveola = Vec::new();
-    vec_alveola.push(ind_first_alveola);
+    info!("Finding some first alveola");
+    let mut vec_alveola = first_alveolae(&mut skeleton_interface, components, &mut rng)?;
 
-    println!("Propagating skeleton");
+    info!("Propagating skeleton");
     loop {
         if let Some(ind_alveola) = vec_alveola.pop() {
             let alveola = skeleton_interface.get_alveola(ind_alveola)?;
@@ -37,23 +109,41 @@ pub fn full_skeletonization(mesh: &mut ManifoldMesh3D) -> Result<Skeleton3D> {
                     None,
                 )?;
             }
-            print!("\r{} alveolae remaining     ", vec_alveola.len());
+            debug!("{} alveolae remaining", vec_alveola.len());
+            let event = SkeletonEvent::Propagation {
+                alveolae_remaining: vec_alveola.len(),
+            };
+            if observer::notify_stop(&mut opt_observer, event) {
+                info!("Skeletonization stopped");
+                return Ok((
+                    skeleton_interface.get_skeleton().clone(),
+                    SkeletonStatus::Stopped,
+                ));
+            }
         } else {
             break;
         }
     }
-    println!("");
 
-    println!("Checking skeleton");
+    info!("Checking skeleton");
     skeleton_interface.check()?;
 
-    Ok(skeleton_interface.get_skeleton().clone())
+    Ok((
+        skeleton_interface.get_skeleton().clone(),
+        SkeletonStatus::Completed,
+    ))
 }
 
-fn loop_skeletonization(
-    skeleton_interface: &mut SkeletonInterface3D,
-    opt_epsilon: Option<f32>,
-) -> Result<()> {
+/// Sheet skeletonization loops, returns early if stopped by the observer
+///
+/// A stop at the start of a loop keeps the skeleton of the previous loop
+fn loop_skeletonization<T: Real>(
+    skeleton_interface: &mut SkeletonInterface3D<T>,
+    opt_epsilon: Option<T>,
+    components: ComponentSelection,
+    rng: &mut StdRng,
+    opt_observer: &mut Option<&mut dyn SkeletonObserver>,
+) -> Result<SkeletonStatus> {
     // println!("Finding some first alveola");
     // let mut ind_first_alveola = skeleton_operations::first_alveola_in(skeleton_interface)?;
     let mut cpt_loop = 0;
@@ -61,38 +151,65 @@ fn loop_skeletonization(
     let mut label;
     loop {
         cpt_loop = cpt_loop + 1;
-        label = 1;
+        label = 0;
         let mut modif_done = false;
 
+        info!("Loop {}", cpt_loop);
+        let event = SkeletonEvent::LoopStarted {
+            loop_number: cpt_loop,
+        };
+        if observer::notify_stop(opt_observer, event) {
+            info!("Skeletonization stopped");
+            return Ok(SkeletonStatus::Stopped);
+        }
         skeleton_interface.reinit_skeleton();
-        println!("Loop {}", cpt_loop);
-        println!("Propagating first sheet");
-        let ind_first_alveola = skeleton_operations::first_alveola_in(skeleton_interface)?;
-        skeleton_operations::compute_sheet(skeleton_interface, ind_first_alveola, label)?;
-        let current_sheet = skeleton_interface.get_sheet(label);
-        let mut sheet_siz_max = current_sheet.len();
-        for &ind_alveola in current_sheet.iter() {
-            if skeleton_interface.get_alveola(ind_alveola)?.is_full() {
-                skeleton_operations::include_alveola_in_skel(
-                    skeleton_interface,
-                    ind_alveola,
-                    Some(label),
-                )?;
+        info!("Propagating first sheets");
+        let mut sheet_siz_max = 0;
+        let mut vec_pedges = Vec::new();
+        for ind_first_alveola in first_alveolae(skeleton_interface, components, rng)? {
+            // seeds of a same sheet (e.g. nested components)
+            if skeleton_interface
+                .get_alveola(ind_first_alveola)?
+                .label()
+                .is_some()
+            {
+                continue;
+            }
+            label = label + 1;
+            skeleton_operations::compute_sheet(skeleton_interface, ind_first_alveola, label)?;
+            let current_sheet = skeleton_interface.get_sheet(label);
+            if current_sheet.len() > sheet_siz_max {
+                sheet_siz_max = current_sheet.len();
+            }
+            for &ind_alveola in current_sheet.iter() {
+                if skeleton_interface.get_alveola(ind_alveola)?.is_full() {
+                    skeleton_operations::include_alveola_in_skel(
+                        skeleton_interface,
+                        ind_alveola,
+                        Some(label),
+                    )?;
+                }
             }
+            let mut vec_pedges_new =
+                skeleton_operations::outer_partial_edges(&skeleton_interface, &current_sheet);
+            vec_pedges.append(&mut vec_pedges_new);
         }
-        let mut vec_pedges =
-            skeleton_operations::outer_partial_edges(&skeleton_interface, &current_sheet);
         vec_pedges.sort();
         vec_pedges.dedup();
 
-        println!("Searching paths");
+        info!("Searching paths");
         loop {
             if let Some(ind_pedge) = vec_pedges.pop() {
-                print!(
-                    "\rSheet {},  {} pedges remaining                                   ",
-                    label,
-                    vec_pedges.len()
-                );
+                debug!("Sheet {}, {} pedges remaining", label, vec_pedges.len());
+                let event = SkeletonEvent::SheetPropagation {
+                    loop_number: cpt_loop,
+                    nb_sheets: label,
+                    pedges_remaining: vec_pedges.len(),
+                };
+                if observer::notify_stop(opt_observer, event) {
+                    info!("Skeletonization stopped");
+                    return Ok(SkeletonStatus::Stopped);
+                }
                 if skeleton_interface
                     .get_partial_edge(ind_pedge)?
                     .partial_alveola()
@@ -170,27 +287,28 @@ fn loop_skeletonization(
             }
         }
 
-        println!(
-            "\r{} Sheets,  {} pedges remaining                                   ",
-            label,
-            vec_pedges.len()
-        );
+        info!("{} sheets, {} pedges remaining", label, vec_pedges.len());
 
         if !modif_done || nb_sheets_prev == label {
             break;
         }
         nb_sheets_prev = label;
 
-        println!("Boundary edges correction");
+        info!("Boundary edges correction");
         let vec_pedges = skeleton_operations::boundary_partial_edges(skeleton_interface);
         let mut saliencies =
             skeleton_operations::estimate_saliencies(skeleton_interface, &vec_pedges)?;
         skeleton_operations::sort_saliencies(&mut saliencies);
         loop {
-            print!(
-                "\r{} boundary pedges remaining                                   ",
-                saliencies.len()
-            );
+            debug!("{} boundary pedges remaining", saliencies.len());
+            let event = SkeletonEvent::BoundaryCorrection {
+                loop_number: cpt_loop,
+                pedges_remaining: saliencies.len(),
+            };
+            if observer::notify_stop(opt_observer, event) {
+                info!("Skeletonization stopped");
+                return Ok(SkeletonStatus::Stopped);
+            }
             if let Some((ind_pedge, _)) = saliencies.pop() {
                 let pedge = skeleton_interface.get_partial_edge(ind_pedge)?;
                 if pedge.edge().degree() != 1 {
@@ -243,18 +361,19 @@ fn loop_skeletonization(
                 break;
             }
         }
-        println!(
-            "\r{} boundary pedges remaining                                   ",
-            saliencies.len()
-        );
+        info!("{} boundary pedges remaining", saliencies.len());
     }
-    println!("Problematic edges correction");
+    info!("Problematic edges correction");
     let mut problematics = skeleton_operations::problematic_partial_edges(skeleton_interface);
     loop {
-        print!(
-            "\r{} problematic pedges remaining                                   ",
-            problematics.len()
-        );
+        debug!("{} problematic pedges remaining", problematics.len());
+        let event = SkeletonEvent::ProblematicCorrection {
+            pedges_remaining: problematics.len(),
+        };
+        if observer::notify_stop(opt_observer, event) {
+            info!("Skeletonization stopped");
+            return Ok(SkeletonStatus::Stopped);
+        }
         if let Some(ind_pedge) = problematics.pop() {
             let pedge = skeleton_interface.get_partial_edge(ind_pedge)?;
             if !pedge.edge().is_non_manifold() {
@@ -272,30 +391,67 @@ fn loop_skeletonization(
             break;
         }
     }
-    println!(
-        "\r{} problematic pedges remaining                                   ",
-        problematics.len()
-    );
-    println!("Checking skeleton");
+    info!("{} problematic pedges remaining", problematics.len());
+    info!("Checking skeleton");
     skeleton_interface.check()?;
-    Ok(())
+    Ok(SkeletonStatus::Completed)
 }
 
 /// Computes the sheet based skeletonization of a delaunay mesh
-pub fn sheet_skeletonization(
-    mesh: &mut ManifoldMesh3D,
-    opt_epsilon: Option<f32>,
-) -> Result<(Skeleton3D, ManifoldMesh3D, Vec<GenericMesh3D>)> {
-    println!("Init skeleton interface");
+///
+/// Giving a seed makes the run reproducible.
+/// The observer is notified of progress, and may stop the computation (partial skeleton is then
+/// returned, with a stopped status, and mesh faces are not labeled)
+/// An error during the sheet loops also returns the partial skeleton, with a failed status
+/// The mesh tetrahedralization can be given (e.g. loaded from a previous run) to avoid computing it again
+/// Components selects the mesh connected components to skeletonize
+pub fn sheet_skeletonization<T: Real>(
+    mesh: &mut ManifoldMesh3D<T>,
+    opt_epsilon: Option<T>,
+    opt_seed: Option<u64>,
+    components: ComponentSelection,
+    mut opt_observer: Option<&mut dyn SkeletonObserver>,
+    opt_tetrahedra: Option<Vec<[usize; 4]>>,
+) -> Result<(
+    Skeleton3D<T>,
+    ManifoldMesh3D<T>,
+    Vec<GenericMesh3D<T>>,
+    SkeletonStatus,
+)> {
+    let mut rng = seeded_rng(opt_seed);
+
+    info!("Init skeleton interface");
     let mut mesh_cl = mesh.clone();
-    let mut skeleton_interface = SkeletonInterface3D::init(&mut mesh_cl)?;
+    let mut skeleton_interface = if let Some(tetrahedra) = opt_tetrahedra {
+        SkeletonInterface3D::init_from_tetrahedra(&mut mesh_cl, tetrahedra)?
+    } else {
+        SkeletonInterface3D::init(&mut mesh_cl)?
+    };
     skeleton_interface.check()?;
 
-    if let Some(err) = loop_skeletonization(&mut skeleton_interface, opt_epsilon).err() {
-        println!("{}", err);
+    let status = match loop_skeletonization(
+        &mut skeleton_interface,
+        opt_epsilon,
+        components,
+        &mut rng,
+        &mut opt_observer,
+    ) {
+        Ok(status) => status,
+        Err(err) => {
+            warn!("Skeletonization failed: {}", err);
+            SkeletonStatus::Failed
+        }
+    };
+    if status != SkeletonStatus::Completed {
+        return Ok((
+            skeleton_interface.get_skeleton().clone(),
+            skeleton_interface.get_mesh().clone(),
+            skeleton_interface.get_debug_meshes().clone(),
+            status,
+        ));
     }
 
-    println!("Computing labels");
+    info!("Computing labels");
     let label_per_vertex = skeleton_interface.get_label_per_vertex()?;
     let mut assignment: Vec<(usize, usize)> = Vec::new();
     for (&ind_face, _) in mesh.faces() {
@@ -316,7 +472,8 @@ pub fn sheet_skeletonization(
             nb_vote_per_lab
                 .iter()
                 .fold((None, 0), |(lab, nb), (&lab_cur, &nb_cur)| {
-                    if nb_cur > nb {
+                    // ties broken by smallest label, for reproducible runs
+                    if nb_cur > nb || (nb_cur == nb && Some(lab_cur) < lab) {
                         (Some(lab_cur), nb_cur)
                     } else {
                         (lab, nb)
@@ -335,5 +492,115 @@ pub fn sheet_skeletonization(
         skeleton_interface.get_skeleton().clone(),
         skeleton_interface.get_mesh().clone(),
         skeleton_interface.get_debug_meshes().clone(),
+        status,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::observer::ObserverAction;
+    use crate::mesh3d;
+    use crate::mesh3d::test_meshes::{self, jittered_bipyramid};
+    use crate::skeleton3d;
+
+    fn skeletonize(
+        opt_observer: Option<&mut dyn SkeletonObserver>,
+    ) -> (Skeleton3D<f64>, SkeletonStatus) {
+        let mut mesh = jittered_bipyramid();
+        let (skeleton, _, _, status) = sheet_skeletonization(
+            &mut mesh,
+            None,
+            Some(0),
+            ComponentSelection::Single,
+            opt_observer,
+            None,
+        )
+        .unwrap();
+        (skeleton, status)
+    }
+
+    #[test]
+    fn observer_stop_returns_partial_skeleton() {
+        let (skeleton, status) = skeletonize(None);
+        assert_eq!(status, SkeletonStatus::Completed);
+        let nb_nodes = skeleton.get_nodes().len();
+
+        let mut events = Vec::new();
+        let mut observer = |event: SkeletonEvent| {
+            events.push(event);
+            match event {
+                SkeletonEvent::SheetPropagation { nb_sheets: 3, .. } => ObserverAction::Stop,
+                _ => ObserverAction::Continue,
+            }
+        };
+        let (skeleton, status) = skeletonize(Some(&mut observer));
+        assert_eq!(status, SkeletonStatus::Stopped);
+        let nb_nodes_partial = skeleton.get_nodes().len();
+        assert!(nb_nodes_partial > 0 && nb_nodes_partial < nb_nodes);
+        // no event after the stop request
+        assert!(matches!(
+            events.last(),
+            Some(SkeletonEvent::SheetPropagation { nb_sheets: 3, .. })
+        ));
+    }
+
+    /// Whether the skeleton of the two shells mesh has nodes in the first shell, and in the second
+    fn skeletonized_shells(components: ComponentSelection) -> Result<[bool; 2]> {
+        let mut mesh = test_meshes::two_shells();
+        let (skeleton, status) = full_skeletonization(&mut mesh, Some(0), components, None)?;
+        assert_eq!(status, SkeletonStatus::Completed);
+        let in_shell = |ind_shell: usize| {
+            skeleton
+                .get_nodes()
+                .values()
+                .any(|sph| (sph.center[0] > 2.0) == (ind_shell == 1))
+        };
+        Ok([in_shell(0), in_shell(1)])
+    }
+
+    #[test]
+    fn selected_components_are_seeded() {
+        let single = skeletonized_shells(ComponentSelection::Single).unwrap();
+        assert!(single[0] != single[1]);
+        let all = skeletonized_shells(ComponentSelection::All).unwrap();
+        assert_eq!(all, [true, true]);
+        let min_faces = skeletonized_shells(ComponentSelection::MinFaces(12)).unwrap();
+        assert_eq!(min_faces, [true, true]);
+        let min_faces = skeletonized_shells(ComponentSelection::MinFaces(13)).unwrap();
+        assert_eq!(min_faces, [true, false]);
+        assert!(skeletonized_shells(ComponentSelection::MinFaces(19)).is_err());
+    }
+
+    #[test]
+    fn seeded_runs_are_reproducible() {
+        let seed = Some(7);
+        let run = || {
+            let mut outputs = Vec::new();
+            let mut mesh = test_meshes::two_shells();
+            let (skeleton, _) =
+                full_skeletonization(&mut mesh, seed, ComponentSelection::All, None).unwrap();
+            let colors = skeleton3d::io::label_colors(&skeleton, seed);
+            let mut content = Vec::new();
+            skeleton3d::io::write_ply(&mut content, &skeleton, Some(colors)).unwrap();
+            outputs.push(content);
+
+            // sheet labels, and mesh faces labelled from them
+            let mut mesh = test_meshes::two_shells();
+            let (skeleton, _, _, _) =
+                sheet_skeletonization(&mut mesh, None, seed, ComponentSelection::All, None, None)
+                    .unwrap();
+            let colors = skeleton3d::io::label_colors(&skeleton, seed);
+            let mut content = Vec::new();
+            let colors = skeleton3d::io::write_ply(&mut content, &skeleton, Some(colors)).unwrap();
+            outputs.push(content);
+            let mut content = Vec::new();
+            mesh3d::io::write_ply_manifold(&mut content, &mesh, Some(colors)).unwrap();
+            outputs.push(content);
+            outputs
+        };
+        let outputs = run();
+        assert!(outputs.iter().all(|content| !content.is_empty()));
+        assert!(outputs == run());
+    }
+}
@@ -1,7 +1,12 @@
 use anyhow::Result;
-use std::collections::{HashMap, HashSet};
-use tritet::{StrError, Tetgen};
-
+use log::debug;
+use nalgebra::base::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Bound::{Excluded, Included, Unbounded};
+
+use super::tetrahedralizer::{DefaultTetrahedralizer, Tetrahedralizer};
+use crate::geometry::predicates;
+use crate::geometry::Real;
 use crate::mesh3d::mesh_operations;
 use crate::mesh3d::{manifold_mesh3d, ManifoldMesh3D};
 
@@ -10,86 +15,369 @@ pub type Triangle = [usize; 3];
 pub type Tetrahedron = [usize; 4];
 
 /// Delaunay structure
-pub struct DelaunayInterface<'a> {
-    mesh: &'a mut ManifoldMesh3D,
+pub struct DelaunayInterface<'a, T = f32> {
+    mesh: &'a mut ManifoldMesh3D<T>,
 
     edges: HashSet<Edge>,
     faces: HashMap<Triangle, Vec<Tetrahedron>>,
     tetras: HashSet<Tetrahedron>,
 
+    // number of tetrahedra around each edge, for local removal
+    edge_tetras_number: HashMap<Edge, usize>,
+    // starting tetrahedron of the next point location
+    last_tetra: Option<Tetrahedron>,
+
+    // work queues of mesh elements missing from the Delaunay structure
+    non_del_halfedges: BTreeSet<usize>,
+    non_del_faces: BTreeSet<usize>,
+
     initial_vertices_number: usize,
+    tetrahedralizer: Box<dyn Tetrahedralizer>,
+}
+
+/// Edges of a sorted tetrahedron
+fn tetra_edges(tetra: &Tetrahedron) -> [Edge; 6] {
+    [
+        [tetra[0], tetra[1]],
+        [tetra[0], tetra[2]],
+        [tetra[0], tetra[3]],
+        [tetra[1], tetra[2]],
+        [tetra[1], tetra[3]],
+        [tetra[2], tetra[3]],
+    ]
 }
 
-fn to_anyhow(err: StrError) -> anyhow::Error {
-    anyhow::Error::msg(err.to_string())
+/// Faces of a sorted tetrahedron, with their opposite vertex
+fn tetra_faces(tetra: &Tetrahedron) -> [(Triangle, usize); 4] {
+    [
+        ([tetra[1], tetra[2], tetra[3]], tetra[0]),
+        ([tetra[0], tetra[2], tetra[3]], tetra[1]),
+        ([tetra[0], tetra[1], tetra[3]], tetra[2]),
+        ([tetra[0], tetra[1], tetra[2]], tetra[3]),
+    ]
 }
 
-impl<'a> DelaunayInterface<'a> {
+impl<'a, T: Real> DelaunayInterface<'a, T> {
     fn insert_tetra(&mut self, tetra: &mut Tetrahedron) -> () {
         tetra.sort();
 
-        self.edges.insert([tetra[0], tetra[1]]);
-        self.edges.insert([tetra[0], tetra[2]]);
-        self.edges.insert([tetra[0], tetra[3]]);
-        self.edges.insert([tetra[1], tetra[2]]);
-        self.edges.insert([tetra[1], tetra[3]]);
-        self.edges.insert([tetra[2], tetra[3]]);
+        for edge in tetra_edges(tetra) {
+            self.edges.insert(edge);
+            *self.edge_tetras_number.entry(edge).or_default() += 1;
+        }
 
+        for (face, _) in tetra_faces(tetra) {
+            self.faces.entry(face).or_default().push(*tetra);
+        }
+
+        self.tetras.insert(*tetra);
+    }
+
+    fn remove_tetra(&mut self, tetra: &Tetrahedron) {
+        for edge in tetra_edges(tetra) {
+            if let Some(nb_tetras) = self.edge_tetras_number.get_mut(&edge) {
+                *nb_tetras -= 1;
+                if *nb_tetras == 0 {
+                    self.edge_tetras_number.remove(&edge);
+                    self.edges.remove(&edge);
+                }
+            }
+        }
+
+        for (face, _) in tetra_faces(tetra) {
+            if let Some(face_tetras) = self.faces.get_mut(&face) {
+                face_tetras.retain(|tet| tet != tetra);
+                if face_tetras.is_empty() {
+                    self.faces.remove(&face);
+                }
+            }
+        }
+
+        self.tetras.remove(tetra);
+    }
+
+    fn point(&self, ind_vertex: usize) -> Result<Vector3<f64>> {
+        let vert = self.mesh.get_vertex(ind_vertex)?.vertex();
+        Ok(vert.map(|x| x.to_f64()))
+    }
+
+    /// Tetrahedron on the other side of a face, if any
+    fn neighbor_tetra(&self, tetra: &Tetrahedron, face: &Triangle) -> Option<Tetrahedron> {
         self.faces
-            .entry([tetra[0], tetra[1], tetra[2]])
-            .or_insert(Vec::new())
-            .push(*tetra);
-        self.faces
-            .entry([tetra[0], tetra[1], tetra[3]])
-            .or_insert(Vec::new())
-            .push(*tetra);
-        self.faces
-            .entry([tetra[0], tetra[2], tetra[3]])
-            .or_insert(Vec::new())
-            .push(*tetra);
-        self.faces
-            .entry([tetra[1], tetra[2], tetra[3]])
-            .or_insert(Vec::new())
-            .push(*tetra);
+            .get(face)
+            .and_then(|face_tetras| face_tetras.iter().find(|&tet| tet != tetra).copied())
+    }
 
-        self.tetras.insert([tetra[0], tetra[1], tetra[2], tetra[3]]);
+    /// Checks if a face separates its opposite vertex from a point (strictly)
+    fn separates(&self, face: &Triangle, ind_opp: usize, pt: &Vector3<f64>) -> Result<bool> {
+        let [pa, pb, pc] = [
+            self.point(face[0])?,
+            self.point(face[1])?,
+            self.point(face[2])?,
+        ];
+        let side_opp = predicates::orient_3d(&pa, &pb, &pc, &self.point(ind_opp)?);
+        let side_pt = predicates::orient_3d(&pa, &pb, &pc, pt);
+        Ok(side_opp * side_pt < 0.0)
     }
 
-    fn generate_struct(&mut self) -> Result<()> {
-        let mut tetgen = Tetgen::new(
-            self.mesh.get_nb_vertices(),
-            Some(vec![3; self.mesh.get_nb_faces()]),
-            None,
-            None,
-        )
-        .map_err(to_anyhow)?;
+    /// Checks if a point is strictly inside the circumsphere of a tetrahedron
+    fn in_circumsphere(&self, tetra: &Tetrahedron, pt: &Vector3<f64>) -> Result<bool> {
+        let [pa, pb, pc, pd] = [
+            self.point(tetra[0])?,
+            self.point(tetra[1])?,
+            self.point(tetra[2])?,
+            self.point(tetra[3])?,
+        ];
+        let orient = predicates::orient_3d(&pa, &pb, &pc, &pd);
+        let insphere = predicates::in_sphere(&pa, &pb, &pc, &pd, pt);
+        Ok(orient * insphere > 0.0)
+    }
+
+    /// Finds a tetrahedron containing a point by walking through the structure,
+    /// returns None if the point is outside the convex hull
+    fn locate_tetra(&self, pt: &Vector3<f64>) -> Result<Option<Tetrahedron>> {
+        let start = self
+            .last_tetra
+            .filter(|tetra| self.tetras.contains(tetra))
+            .or_else(|| self.tetras.iter().min().copied());
+        let mut tetra = match start {
+            Some(tetra) => tetra,
+            None => return Ok(None),
+        };
+
+        // the walk ends in Delaunay tetrahedralizations, the bound only guards against degenerate cases
+        for _ in 0..self.tetras.len() {
+            let mut next = None;
+            for (face, ind_opp) in tetra_faces(&tetra) {
+                if self.separates(&face, ind_opp, pt)? {
+                    match self.neighbor_tetra(&tetra, &face) {
+                        Some(neighbor) => next = Some(neighbor),
+                        None => return Ok(None),
+                    }
+                    break;
+                }
+            }
+            match next {
+                Some(neighbor) => tetra = neighbor,
+                None => return Ok(Some(tetra)),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Inserts a mesh vertex in the Delaunay structure (Bowyer-Watson insertion)
+    ///
+    /// Tetrahedra whose circumsphere contains the vertex are removed, and the boundary of the cavity
+    /// is connected to the vertex. Returns the vertices of the modified tetrahedra, or None, leaving
+    /// the structure untouched, if the vertex is outside the convex hull or if the cavity is not
+    /// star-shaped (degenerate configurations)
+    fn insert_vertex(&mut self, ind_vertex: usize) -> Result<Option<Vec<usize>>> {
+        let pt = self.point(ind_vertex)?;
+
+        let first = match self.locate_tetra(&pt)? {
+            Some(tetra) => tetra,
+            None => return Ok(None),
+        };
+        if !self.in_circumsphere(&first, &pt)? {
+            return Ok(None);
+        }
+
+        let mut cavity = HashSet::new();
+        let mut outside = HashSet::new();
+        let mut to_visit = vec![first];
+        cavity.insert(first);
+        while let Some(tetra) = to_visit.pop() {
+            for (face, _) in tetra_faces(&tetra) {
+                if let Some(neighbor) = self.neighbor_tetra(&tetra, &face) {
+                    if cavity.contains(&neighbor) || outside.contains(&neighbor) {
+                        continue;
+                    }
+                    if self.in_circumsphere(&neighbor, &pt)? {
+                        cavity.insert(neighbor);
+                        to_visit.push(neighbor);
+                    } else {
+                        outside.insert(neighbor);
+                    }
+                }
+            }
+        }
+
+        // sorted cavity, for reproducible runs
+        let mut cavity_tetras: Vec<Tetrahedron> = cavity.iter().copied().collect();
+        cavity_tetras.sort();
+
+        let mut new_tetras = Vec::new();
+        for tetra in cavity_tetras.iter() {
+            for (face, ind_opp) in tetra_faces(tetra) {
+                let neighbor = self.neighbor_tetra(tetra, &face);
+                if neighbor.is_some_and(|tet| cavity.contains(&tet)) {
+                    continue;
+                }
+                let [pa, pb, pc] = [
+                    self.point(face[0])?,
+                    self.point(face[1])?,
+                    self.point(face[2])?,
+                ];
+                let side_opp = predicates::orient_3d(&pa, &pb, &pc, &self.point(ind_opp)?);
+                let side_pt = predicates::orient_3d(&pa, &pb, &pc, &pt);
+                if side_pt == 0.0 && neighbor.is_none() {
+                    // vertex on a convex hull face, which gets split by the new tetrahedra
+                    continue;
+                }
+                if side_opp * side_pt <= 0.0 {
+                    return Ok(None);
+                }
+                new_tetras.push([face[0], face[1], face[2], ind_vertex]);
+            }
+        }
 
+        for tetra in cavity_tetras.iter() {
+            self.remove_tetra(tetra);
+        }
+        for tetra in new_tetras.iter_mut() {
+            self.insert_tetra(tetra);
+        }
+        self.last_tetra = new_tetras.last().copied();
+
+        let mut cavity_vertices: Vec<usize> = cavity_tetras.into_iter().flatten().collect();
+        cavity_vertices.sort();
+        cavity_vertices.dedup();
+        Ok(Some(cavity_vertices))
+    }
+
+    /// Updates the structure after the insertion of the last mesh vertex,
+    /// recomputing it entirely if the local update fails
+    ///
+    /// The given vertices are the ones whose surrounding mesh elements were modified
+    fn update_struct(&mut self, mut modified_vertices: Vec<usize>) -> Result<()> {
+        let ind_vertex = self.mesh.get_nb_vertices() - 1;
+        match self.insert_vertex(ind_vertex)? {
+            Some(cavity_vertices) => {
+                modified_vertices.push(ind_vertex);
+                modified_vertices.extend(cavity_vertices);
+                self.update_non_del_around(&modified_vertices)
+            }
+            None => {
+                debug!(
+                    "Local Delaunay update failed for vertex {}, full recomputation",
+                    ind_vertex
+                );
+                self.recompute_struct()
+            }
+        }
+    }
+
+    fn update_halfedge_status(&mut self, ind_halfedge: usize) -> Result<()> {
+        let seg = self.mesh.get_halfedge(ind_halfedge)?.halfedge();
+        if self.is_edge_in(&seg) {
+            self.non_del_halfedges.remove(&ind_halfedge);
+        } else {
+            self.non_del_halfedges.insert(ind_halfedge);
+        }
+        Ok(())
+    }
+
+    fn update_face_status(&mut self, ind_face: usize) -> Result<()> {
+        let face = self.mesh.get_face(ind_face)?.vertices_inds();
+        if self.is_face_in(&face) {
+            self.non_del_faces.remove(&ind_face);
+        } else {
+            self.non_del_faces.insert(ind_face);
+        }
+        Ok(())
+    }
+
+    /// Halfedges (with their opposite) and faces around given vertices
+    fn elements_around(&self, vertices: &[usize]) -> Result<(Vec<usize>, Vec<usize>)> {
+        let mut halfedges = Vec::new();
+        let mut faces = Vec::new();
+        for &ind_vertex in vertices.iter() {
+            for he in self.mesh.get_vertex(ind_vertex)?.halfedges() {
+                halfedges.push(he.ind());
+                if let Some(he_opp) = he.opposite_halfedge() {
+                    halfedges.push(he_opp.ind());
+                }
+                if let Some(face) = he.face() {
+                    faces.push(face.ind());
+                }
+            }
+        }
+        Ok((halfedges, faces))
+    }
+
+    /// Removes elements around given vertices from the work queues, before a mesh modification
+    fn forget_non_del_around(&mut self, vertices: &[usize]) -> Result<()> {
+        let (halfedges, faces) = self.elements_around(vertices)?;
+        for ind_he in halfedges {
+            self.non_del_halfedges.remove(&ind_he);
+        }
+        for ind_face in faces {
+            self.non_del_faces.remove(&ind_face);
+        }
+        Ok(())
+    }
+
+    /// Updates the work queues around given vertices, after a mesh or structure modification
+    fn update_non_del_around(&mut self, vertices: &[usize]) -> Result<()> {
+        let (halfedges, faces) = self.elements_around(vertices)?;
+        for ind_he in halfedges {
+            self.update_halfedge_status(ind_he)?;
+        }
+        for ind_face in faces {
+            self.update_face_status(ind_face)?;
+        }
+        Ok(())
+    }
+
+    /// Fills the work queues by scanning the whole mesh
+    fn scan_non_del(&mut self) {
+        self.non_del_halfedges = BTreeSet::new();
+        self.non_del_faces = BTreeSet::new();
+        for (&ind_he, seg) in self.mesh.halfedges() {
+            if !self.is_edge_in(seg) {
+                self.non_del_halfedges.insert(ind_he);
+            }
+        }
+        for (&ind_face, _) in self.mesh.faces() {
+            let face = self.mesh.get_face(ind_face).unwrap().vertices_inds();
+            if !self.is_face_in(&face) {
+                self.non_del_faces.insert(ind_face);
+            }
+        }
+    }
+
+    /// Vertices of the two faces around an halfedge
+    fn halfedge_vertices(&self, ind_halfedge: usize) -> Result<Vec<usize>> {
+        let he = self.mesh.get_halfedge(ind_halfedge)?;
+        let mut vertices = vec![he.first_vertex().ind(), he.last_vertex().ind()];
+        if let Some(he_next) = he.next_halfedge() {
+            vertices.push(he_next.last_vertex().ind());
+        }
+        if let Some(he_opp_next) = he
+            .opposite_halfedge()
+            .and_then(|he_opp| he_opp.next_halfedge())
+        {
+            vertices.push(he_opp_next.last_vertex().ind());
+        }
+        Ok(vertices)
+    }
+
+    fn generate_struct(&mut self) -> Result<()> {
+        let mut points = vec![Vector3::zeros(); self.mesh.get_nb_vertices()];
         for v in self.mesh.vertex_indices() {
-            // let mut i = 0;
-            // for (&v, vert) in self.mesh.vertices() {
-            //     println!("{}:{} ({}, {}, {})", i, v, vert[0], vert[1], vert[2]);
-            //     i = i + 1;
             let vert = self.mesh.get_vertex(v)?.vertex();
             if v >= self.mesh.get_nb_vertices() {
                 return Err(anyhow::Error::msg(
                     "generate_struct(): Vertex index over vertex number, not currently handled",
                 ));
             }
-            tetgen
-                .set_point(v, vert[0] as f64, vert[1] as f64, vert[2] as f64)
-                .map_err(to_anyhow)?;
+            points[v] = Vector3::new(vert[0].to_f64(), vert[1].to_f64(), vert[2].to_f64());
         }
 
-        tetgen.generate_delaunay(false).map_err(to_anyhow)?;
-
-        for t in 0..tetgen.ntet() {
-            let mut tetra = [0; 4];
-            for m in 0..4 {
-                tetra[m] = tetgen.tet_node(t, m);
-            }
-
+        for mut tetra in self.tetrahedralizer.tetrahedralize(&points)? {
             self.insert_tetra(&mut tetra);
         }
+        self.scan_non_del();
 
         Ok(())
     }
@@ -98,19 +386,34 @@ impl<'a> DelaunayInterface<'a> {
         self.edges = HashSet::new();
         self.faces = HashMap::new();
         self.tetras = HashSet::new();
+        self.edge_tetras_number = HashMap::new();
+        self.last_tetra = None;
 
         self.generate_struct()
     }
 
-    /// Creates Delaunay structure from mesh
-    pub fn from_mesh(mesh: &'a mut ManifoldMesh3D) -> Result<DelaunayInterface<'a>> {
+    /// Creates Delaunay structure from mesh, with the default tetrahedralization backend
+    pub fn from_mesh(mesh: &'a mut ManifoldMesh3D<T>) -> Result<DelaunayInterface<'a, T>> {
+        DelaunayInterface::from_mesh_with(mesh, Box::new(DefaultTetrahedralizer::default()))
+    }
+
+    /// Creates Delaunay structure from mesh, with given tetrahedralization backend
+    pub fn from_mesh_with(
+        mesh: &'a mut ManifoldMesh3D<T>,
+        tetrahedralizer: Box<dyn Tetrahedralizer>,
+    ) -> Result<DelaunayInterface<'a, T>> {
         let initial_vertices_number = mesh.get_nb_vertices();
         let mut deltet = DelaunayInterface {
             mesh,
             edges: HashSet::new(),
             faces: HashMap::new(),
             tetras: HashSet::new(),
+            edge_tetras_number: HashMap::new(),
+            last_tetra: None,
+            non_del_halfedges: BTreeSet::new(),
+            non_del_faces: BTreeSet::new(),
             initial_vertices_number,
+            tetrahedralizer,
         };
 
         deltet.generate_struct()?;
@@ -119,7 +422,7 @@ impl<'a> DelaunayInterface<'a> {
     }
 
     /// Mesh getter
-    pub fn get_mesh(&self) -> &ManifoldMesh3D {
+    pub fn get_mesh(&self) -> &ManifoldMesh3D<T> {
         self.mesh
     }
 
@@ -178,25 +481,15 @@ impl<'a> DelaunayInterface<'a> {
 
     /// Count number of non Delaunay halfedges
     pub fn count_non_del_halfedges(&self) -> Result<usize> {
-        let mut nb_non_del = 0;
-        for (_, &he) in self.mesh.halfedges() {
-            // let he = self.mesh.get_halfedge(i)?.halfedge();
-            nb_non_del = nb_non_del + if self.is_edge_in(&he) { 0 } else { 1 };
-        }
-        Ok(nb_non_del)
+        Ok(self.non_del_halfedges.len())
     }
 
     /// Count number of non Delaunay faces
     pub fn count_non_del_faces(&self) -> Result<usize> {
-        let mut nb_non_del = 0;
-        for (&i, _) in self.mesh.faces() {
-            let face = self.mesh.get_face(i).unwrap().vertices_inds();
-            nb_non_del = nb_non_del + if self.is_face_in(&face) { 0 } else { 1 };
-        }
-        Ok(nb_non_del)
+        Ok(self.non_del_faces.len())
     }
 
-    fn get_opposite_angle(&self, halfedge: manifold_mesh3d::IterHalfEdge) -> Result<f32> {
+    fn get_opposite_angle(&self, halfedge: manifold_mesh3d::IterHalfEdge<T>) -> Result<T> {
         let vert1 = halfedge.first_vertex().vertex();
         let vert2 = halfedge.last_vertex().vertex();
         let vert3 = halfedge
@@ -214,119 +507,157 @@ impl<'a> DelaunayInterface<'a> {
         Ok(angle)
     }
 
-    /// Gets first locally non Delaunay halfedge, starting from a shift
+    /// Indices of a work queue after a given index, wrapping around to the beginning
+    fn queue_after(queue: &BTreeSet<usize>, after: Option<usize>) -> impl Iterator<Item = &usize> {
+        let (tail, head) = match after {
+            Some(after) => (
+                queue.range((Excluded(after), Unbounded)),
+                queue.range((Unbounded, Included(after))),
+            ),
+            None => (queue.range(..), queue.range(..0)),
+        };
+        tail.chain(head)
+    }
+
+    /// Gets first locally non Delaunay halfedge, starting after a given halfedge index
     pub fn get_local_non_del_halfedge(
         &self,
         shift: Option<usize>,
-    ) -> Result<Option<manifold_mesh3d::IterHalfEdge>> {
-        let shift = shift.unwrap_or(0) % self.mesh.get_nb_halfedges();
-        let mut i = 0;
-        for (&ind_he, seg) in self.mesh.halfedges() {
-            if i <= shift {
-                i = i + 1;
-                continue;
+    ) -> Result<Option<manifold_mesh3d::IterHalfEdge<T>>> {
+        for &ind_he in Self::queue_after(&self.non_del_halfedges, shift) {
+            let he = self.mesh.get_halfedge(ind_he)?;
+            let angle1 = self.get_opposite_angle(he)?;
+            let angle2 = self.get_opposite_angle(he.opposite_halfedge().ok_or(
+                anyhow::Error::msg("get_opposite_angle(): No opposite halfedge"),
+            )?)?;
+
+            if angle1 + angle2 >= T::pi() {
+                return Ok(Some(he));
             }
-            if !self.is_edge_in(&seg) {
-                let he = self.mesh.get_halfedge(ind_he)?;
-                let angle1 = self.get_opposite_angle(he)?;
-                let angle2 = self.get_opposite_angle(he.opposite_halfedge().ok_or(
-                    anyhow::Error::msg("get_opposite_angle(): No opposite halfedge"),
-                )?)?;
-
-                if angle1 + angle2 >= std::f32::consts::PI {
-                    return Ok(Some(he));
-                }
-            };
         }
         Ok(None)
     }
 
-    /// Gets first globally non Delaunay halfedge, starting from a shift
+    /// Gets first globally non Delaunay halfedge, starting after a given halfedge index
     pub fn get_non_del_halfedge(
         &self,
         shift: Option<usize>,
-    ) -> Result<Option<manifold_mesh3d::IterHalfEdge>> {
-        let shift = shift.unwrap_or(0) % self.mesh.get_nb_halfedges();
-        let mut i = 0;
-        for (&ind_he, seg) in self.mesh.halfedges() {
-            if i <= shift {
-                i = i + 1;
-                continue;
-            }
-            if !self.is_edge_in(&seg) {
-                let he = self.mesh.get_halfedge(ind_he)?;
-                return Ok(Some(he));
-            };
+    ) -> Result<Option<manifold_mesh3d::IterHalfEdge<T>>> {
+        match Self::queue_after(&self.non_del_halfedges, shift).next() {
+            Some(&ind_he) => Ok(Some(self.mesh.get_halfedge(ind_he)?)),
+            None => Ok(None),
         }
-        Ok(None)
     }
 
-    /// Gets first globally non Delaunay face, starting from a shift
+    /// Gets first globally non Delaunay face, starting after a given face index
     pub fn get_non_del_face(
         &self,
         shift: Option<usize>,
-    ) -> Result<Option<manifold_mesh3d::IterFace>> {
-        let shift = shift.unwrap_or(0) % self.mesh.get_nb_halfedges();
-        let mut i = 0;
-        for (&ind_face, _) in self.mesh.faces() {
-            if i <= shift {
-                i = i + 1;
-                continue;
-            }
-            let face = self.mesh.get_face(ind_face)?;
-            let face_ind = face.vertices_inds();
-            if !self.is_face_in(&face_ind) {
-                return Ok(Some(face));
-            };
+    ) -> Result<Option<manifold_mesh3d::IterFace<T>>> {
+        match Self::queue_after(&self.non_del_faces, shift).next() {
+            Some(&ind_face) => Ok(Some(self.mesh.get_face(ind_face)?)),
+            None => Ok(None),
         }
-        Ok(None)
     }
 
     /// Gets all non delaunay halfedges
     pub fn get_all_non_del_halfedge(&self) -> Result<Vec<usize>> {
-        let mut non_del = Vec::new();
-
-        for (&ind_he, seg) in self.mesh.halfedges() {
-            if !self.is_edge_in(&seg) {
-                non_del.push(ind_he);
-            };
-        }
-
-        Ok(non_del)
+        Ok(self.non_del_halfedges.iter().copied().collect())
     }
 
     /// Gets all non delaunay faces
     pub fn get_all_non_del_face(&self) -> Result<Vec<usize>> {
-        let mut non_del = Vec::new();
-
-        for (&ind_fac, _) in self.mesh.faces() {
-            let face = self.mesh.get_face(ind_fac).unwrap().vertices_inds();
-            if !self.is_face_in(&face) {
-                non_del.push(ind_fac);
-            };
-        }
-
-        Ok(non_del)
+        Ok(self.non_del_faces.iter().copied().collect())
     }
 
     /// Flips given halfedge
     pub fn flip_halfedge(&mut self, ind_halfedge: usize) -> Result<bool> {
-        mesh_operations::flip_halfedge(self.mesh, ind_halfedge)
+        let vertices = self.halfedge_vertices(ind_halfedge)?;
+        self.forget_non_del_around(&vertices)?;
+        let flipped = mesh_operations::flip_halfedge(self.mesh, ind_halfedge)?;
+        self.update_non_del_around(&vertices)?;
+        Ok(flipped)
     }
 
     /// Splits given halfedge
     pub fn split_halfedge(
         &mut self,
-        vert: &manifold_mesh3d::Vertex,
+        vert: &manifold_mesh3d::Vertex<T>,
         ind_halfedge: usize,
     ) -> Result<()> {
+        let vertices = self.halfedge_vertices(ind_halfedge)?;
+        self.forget_non_del_around(&vertices)?;
         mesh_operations::split_halfedge(self.mesh, vert, ind_halfedge)?;
-        self.recompute_struct()
+        self.update_struct(vertices)
     }
 
     /// Splits given face
-    pub fn split_face(&mut self, vert: &manifold_mesh3d::Vertex, ind_face: usize) -> Result<()> {
+    pub fn split_face(&mut self, vert: &manifold_mesh3d::Vertex<T>, ind_face: usize) -> Result<()> {
+        let vertices = self.mesh.get_face(ind_face)?.vertices_inds().to_vec();
+        self.forget_non_del_around(&vertices)?;
         mesh_operations::split_face(self.mesh, vert, ind_face)?;
-        self.recompute_struct()
+        self.update_struct(vertices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh3d::test_meshes::jittered_bipyramid;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn sorted<U: Ord + Copy>(set: impl Iterator<Item = U>) -> Vec<U> {
+        let mut vec: Vec<U> = set.collect();
+        vec.sort();
+        vec
+    }
+
+    #[test]
+    fn incremental_update_matches_recomputation() {
+        let mut mesh = jittered_bipyramid();
+        let mut deltet = DelaunayInterface::from_mesh(&mut mesh).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for i in 0..30 {
+            if i % 2 == 0 {
+                let face_inds = sorted(deltet.get_mesh().faces().keys().copied());
+                let ind_face = face_inds[rng.gen_range(0..face_inds.len())];
+                let pts = deltet
+                    .get_mesh()
+                    .get_face(ind_face)
+                    .unwrap()
+                    .vertices()
+                    .map(|vert| vert.vertex());
+                let (u, v) = (rng.gen_range(0.1..0.4), rng.gen_range(0.1..0.4));
+                let vert = pts[0] * (1.0 - u - v) + pts[1] * u + pts[2] * v;
+                deltet.split_face(&vert, ind_face).unwrap();
+            } else {
+                let he_inds = sorted(deltet.get_mesh().halfedges().keys().copied());
+                let ind_he = he_inds[rng.gen_range(0..he_inds.len())];
+                let [ind_v1, ind_v2] = deltet.get_mesh().get_halfedge(ind_he).unwrap().halfedge();
+                let t = rng.gen_range(0.3..0.7);
+                let vert = deltet.get_mesh().get_vertex(ind_v1).unwrap().vertex() * (1.0 - t)
+                    + deltet.get_mesh().get_vertex(ind_v2).unwrap().vertex() * t;
+                deltet.split_halfedge(&vert, ind_he).unwrap();
+            }
+        }
+
+        let tetras = sorted(deltet.get_tetrahedra().iter().copied());
+        let edges = sorted(deltet.get_edges().iter().copied());
+        let faces = sorted(deltet.get_faces().keys().copied());
+        let non_del_halfedges = deltet.get_all_non_del_halfedge().unwrap();
+        let non_del_faces = deltet.get_all_non_del_face().unwrap();
+
+        deltet.recompute_struct().unwrap();
+
+        assert_eq!(tetras, sorted(deltet.get_tetrahedra().iter().copied()));
+        assert_eq!(edges, sorted(deltet.get_edges().iter().copied()));
+        assert_eq!(faces, sorted(deltet.get_faces().keys().copied()));
+        assert_eq!(
+            non_del_halfedges,
+            deltet.get_all_non_del_halfedge().unwrap()
+        );
+        assert_eq!(non_del_faces, deltet.get_all_non_del_face().unwrap());
     }
 }
@@ -8,10 +8,17 @@ mod skeleton_interface;
 mod skeleton_problematic_path;
 mod skeleton_separation;
 mod skeleton_singular_path;
+mod tetrahedralizer;
 
 pub use delaunay_interface::DelaunayInterface;
 pub use skeleton_interface::SkeletonInterface3D;
 pub use skeleton_separation::SkeletonSeparation;
+#[cfg(feature = "tetgen")]
+pub use tetrahedralizer::TetgenTetrahedralizer;
+pub use tetrahedralizer::{
+    BowyerWatsonTetrahedralizer, DefaultTetrahedralizer, PrecomputedTetrahedralizer,
+    Tetrahedralizer,
+};
 
 use movable_delaunay_path::MovableDelaunayPath;
 use skeleton_singular_path::SkeletonSingularPath;
@@ -1,31 +1,23 @@
 use anyhow::Result;
 use nalgebra::Vector3;
 use std::collections::HashSet;
+use std::fmt;
+
+use crate::geometry::Real;
 
 use super::SkeletonInterface3D;
 
-pub struct MovableDelaunayPath<'a, 'b> {
-    skeleton_interface: &'b SkeletonInterface3D<'a>,
+pub struct MovableDelaunayPath<'a, 'b, T = f32> {
+    skeleton_interface: &'b SkeletonInterface3D<'a, T>,
     ind_palves: Vec<usize>,
-    normals: Vec<Vector3<f32>>,
+    normals: Vec<Vector3<T>>,
     faces_prev: Vec<Option<[usize; 3]>>,
     faces_prev_prior: Vec<Option<i8>>,
     has_face_connected: Vec<bool>,
 }
 
-impl<'a, 'b> MovableDelaunayPath<'a, 'b> {
-    fn new(skeleton_interface: &'b SkeletonInterface3D<'a>) -> MovableDelaunayPath<'a, 'b> {
-        MovableDelaunayPath {
-            skeleton_interface,
-            ind_palves: Vec::new(),
-            normals: Vec::new(),
-            faces_prev: Vec::new(),
-            faces_prev_prior: Vec::new(),
-            has_face_connected: Vec::new(),
-        }
-    }
-
-    pub fn print(&self) -> () {
+impl<'a, 'b, T: Real> fmt::Display for MovableDelaunayPath<'a, 'b, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for i in 0..self.ind_palves.len() {
             let ind_palve = self.ind_palves[i];
             let palve = self
@@ -44,9 +36,22 @@ impl<'a, 'b> MovableDelaunayPath<'a, 'b> {
                 format!("N")
             };
             let hasfac = if self.has_face_connected[i] { "1" } else { "0" };
-            print!("[{} -> {}, {}, {}], ", seg[0], seg[1], strfac, hasfac);
+            write!(f, "[{} -> {}, {}, {}], ", seg[0], seg[1], strfac, hasfac)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'b, T: Real> MovableDelaunayPath<'a, 'b, T> {
+    fn new(skeleton_interface: &'b SkeletonInterface3D<'a, T>) -> MovableDelaunayPath<'a, 'b, T> {
+        MovableDelaunayPath {
+            skeleton_interface,
+            ind_palves: Vec::new(),
+            normals: Vec::new(),
+            faces_prev: Vec::new(),
+            faces_prev_prior: Vec::new(),
+            has_face_connected: Vec::new(),
         }
-        println!("");
     }
 
     fn compute_face_prev(&mut self, ind: usize) -> Result<()> {
@@ -90,7 +95,7 @@ impl<'a, 'b> MovableDelaunayPath<'a, 'b> {
                         .get_vertex(seg[1])?
                         .vertex();
                     let nor = (vert2 - vert1).cross(&(vert3 - vert2)).normalize();
-                    if nor.dot(&self.normals[ind]) < 0.0 {
+                    if nor.dot(&self.normals[ind]) < T::zero() {
                         self.faces_prev_prior[ind] = Some(3);
                     } else if !self.has_face_connected[ind] || !self.has_face_connected[ind_prev] {
                         self.faces_prev_prior[ind] = Some(0);
@@ -114,10 +119,10 @@ impl<'a, 'b> MovableDelaunayPath<'a, 'b> {
     }
 
     pub fn create(
-        skeleton_interface: &'b SkeletonInterface3D<'a>,
+        skeleton_interface: &'b SkeletonInterface3D<'a, T>,
         ind_palves: Vec<usize>,
         unfaced_hedges: &HashSet<[usize; 2]>,
-    ) -> Result<MovableDelaunayPath<'a, 'b>> {
+    ) -> Result<MovableDelaunayPath<'a, 'b, T>> {
         let mut path = MovableDelaunayPath::new(skeleton_interface);
         for ind in 0..ind_palves.len() {
             path.ind_palves.push(ind_palves[ind]);
@@ -185,7 +190,7 @@ impl<'a, 'b> MovableDelaunayPath<'a, 'b> {
         None
     }
 
-    pub fn fusion_couple(&self, couple: [usize; 2]) -> Result<Vec<MovableDelaunayPath<'a, 'b>>> {
+    pub fn fusion_couple(&self, couple: [usize; 2]) -> Result<Vec<MovableDelaunayPath<'a, 'b, T>>> {
         let mut path1 = MovableDelaunayPath::new(self.skeleton_interface);
         let mut path2 = MovableDelaunayPath::new(self.skeleton_interface);
         let (ind_min, ind_max) = if couple[0] < couple[1] {
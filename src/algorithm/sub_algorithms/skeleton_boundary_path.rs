@@ -2,12 +2,14 @@ use std::collections::HashSet;
 
 use anyhow::Result;
 
+use crate::geometry::Real;
+
 use super::SkeletonInterface3D;
 use super::SkeletonSingularPath;
 
-pub fn next_boundary_edge(
+pub fn next_boundary_edge<T: Real>(
     ind_pedge: usize,
-    skeleton_interface: &SkeletonInterface3D,
+    skeleton_interface: &SkeletonInterface3D<T>,
 ) -> Option<usize> {
     let pedge_cur = skeleton_interface.get_partial_edge_uncheck(ind_pedge);
     if pedge_cur.edge().degree() != 1 {
@@ -39,10 +41,10 @@ pub fn next_boundary_edge(
     Some(pedge_next[0])
 }
 
-pub fn compute_saliency(
+pub fn compute_saliency<T: Real>(
     ind_pedge: usize,
-    skeleton_interface: &SkeletonInterface3D,
-) -> Result<Option<f32>> {
+    skeleton_interface: &SkeletonInterface3D<T>,
+) -> Result<Option<T>> {
     if let Some(ind_aft) = next_boundary_edge(ind_pedge, skeleton_interface) {
         let pedge_cur = skeleton_interface.get_partial_edge_uncheck(ind_pedge);
         let pedge_aft = skeleton_interface.get_partial_edge_uncheck(ind_aft);
@@ -74,9 +76,9 @@ pub fn compute_saliency(
         let nor = vec_cur.cross(&vec_nex).normalize();
         let vec_aft = (vert3 - vert2).normalize();
         let vec_cross = vec_cur.cross(&vec_aft);
-        if vec_cross.dot(&nor) > 0.0 {
+        if vec_cross.dot(&nor) > T::zero() {
             let sin_ang = vec_cross.norm();
-            if vec_cur.dot(&vec_aft) < 0.0 {
+            if vec_cur.dot(&vec_aft) < T::zero() {
                 return Ok(Some(sin_ang));
             }
         }
@@ -84,9 +86,9 @@ pub fn compute_saliency(
     Ok(None)
 }
 
-pub fn excluded_alveolae(
+pub fn excluded_alveolae<T: Real>(
     ind_pedge: usize,
-    skeleton_interface: &SkeletonInterface3D,
+    skeleton_interface: &SkeletonInterface3D<T>,
 ) -> HashSet<usize> {
     let pnode = skeleton_interface
         .get_partial_edge_uncheck(ind_pedge)
@@ -106,9 +108,9 @@ pub fn excluded_alveolae(
         .collect()
 }
 
-pub fn singular_path_to_exclude_alveolae(
+pub fn singular_path_to_exclude_alveolae<T: Real>(
     set_alve_to_exclude: &HashSet<usize>,
-    skeleton_interface: &mut SkeletonInterface3D,
+    skeleton_interface: &mut SkeletonInterface3D<T>,
 ) -> Result<Option<SkeletonSingularPath>> {
     let mut opt_ind_first_pedge = None;
     for &ind_alve in set_alve_to_exclude {
@@ -3,17 +3,19 @@ use nalgebra::base::*;
 use std::collections::{HashMap, HashSet};
 
 use crate::geometry::geometry_operations;
+use crate::geometry::Real;
 use crate::mesh3d::GenericMesh3D;
 use crate::mesh3d::ManifoldMesh3D;
 use crate::skeleton3d::Skeleton3D;
 
 use super::DelaunayInterface;
+use super::{DefaultTetrahedralizer, PrecomputedTetrahedralizer, Tetrahedralizer};
 
 /// Skeleton interface structure
-pub struct SkeletonInterface3D<'a> {
-    pub(super) mesh: &'a mut ManifoldMesh3D,
-    pub(super) skeleton: Skeleton3D,
-    pub(super) debug_meshes: Vec<GenericMesh3D>,
+pub struct SkeletonInterface3D<'a, T = f32> {
+    pub(super) mesh: &'a mut ManifoldMesh3D<T>,
+    pub(super) skeleton: Skeleton3D<T>,
+    pub(super) debug_meshes: Vec<GenericMesh3D<T>>,
 
     // For non linked vertices
     pub(super) out_vert_per_face: HashMap<usize, Vec<usize>>,
@@ -67,45 +69,61 @@ pub struct SkeletonInterface3D<'a> {
 }
 
 #[derive(Copy, Clone)]
-pub struct IterNode<'a, 'b> {
-    skeleton_interface: &'b SkeletonInterface3D<'a>,
+pub struct IterNode<'a, 'b, T = f32> {
+    skeleton_interface: &'b SkeletonInterface3D<'a, T>,
     ind_node: usize,
 }
 
 #[derive(Copy, Clone)]
-pub struct IterEdge<'a, 'b> {
-    skeleton_interface: &'b SkeletonInterface3D<'a>,
+pub struct IterEdge<'a, 'b, T = f32> {
+    skeleton_interface: &'b SkeletonInterface3D<'a, T>,
     ind_edge: usize,
 }
 
 #[derive(Copy, Clone)]
-pub struct IterAlveola<'a, 'b> {
-    skeleton_interface: &'b SkeletonInterface3D<'a>,
+pub struct IterAlveola<'a, 'b, T = f32> {
+    skeleton_interface: &'b SkeletonInterface3D<'a, T>,
     ind_alveola: usize,
 }
 
 #[derive(Copy, Clone)]
-pub struct IterPartialNode<'a, 'b> {
-    skeleton_interface: &'b SkeletonInterface3D<'a>,
+pub struct IterPartialNode<'a, 'b, T = f32> {
+    skeleton_interface: &'b SkeletonInterface3D<'a, T>,
     ind_pnode: usize,
 }
 
 #[derive(Copy, Clone)]
-pub struct IterPartialEdge<'a, 'b> {
-    skeleton_interface: &'b SkeletonInterface3D<'a>,
+pub struct IterPartialEdge<'a, 'b, T = f32> {
+    skeleton_interface: &'b SkeletonInterface3D<'a, T>,
     ind_pedge: usize,
 }
 
 #[derive(Copy, Clone)]
-pub struct IterPartialAlveola<'a, 'b> {
-    skeleton_interface: &'b SkeletonInterface3D<'a>,
+pub struct IterPartialAlveola<'a, 'b, T = f32> {
+    skeleton_interface: &'b SkeletonInterface3D<'a, T>,
     ind_palveola: usize,
 }
 
-impl<'a, 'b> SkeletonInterface3D<'a> {
+impl<'a, 'b, T: Real> SkeletonInterface3D<'a, T> {
     /// Skeleton interface initialisation from Delaunay mesh
-    pub fn init(mesh: &'a mut ManifoldMesh3D) -> Result<SkeletonInterface3D<'a>> {
-        let deltet = DelaunayInterface::from_mesh(mesh)?;
+    pub fn init(mesh: &'a mut ManifoldMesh3D<T>) -> Result<SkeletonInterface3D<'a, T>> {
+        SkeletonInterface3D::init_with(mesh, Box::new(DefaultTetrahedralizer::default()))
+    }
+
+    /// Skeleton interface initialisation from Delaunay mesh and its tetrahedralization, computed beforehand
+    pub fn init_from_tetrahedra(
+        mesh: &'a mut ManifoldMesh3D<T>,
+        tetrahedra: Vec<[usize; 4]>,
+    ) -> Result<SkeletonInterface3D<'a, T>> {
+        SkeletonInterface3D::init_with(mesh, Box::new(PrecomputedTetrahedralizer::new(tetrahedra)))
+    }
+
+    /// Skeleton interface initialisation from Delaunay mesh, with given tetrahedralization backend
+    pub fn init_with(
+        mesh: &'a mut ManifoldMesh3D<T>,
+        tetrahedralizer: Box<dyn Tetrahedralizer>,
+    ) -> Result<SkeletonInterface3D<'a, T>> {
+        let deltet = DelaunayInterface::from_mesh_with(mesh, tetrahedralizer)?;
         let nb_non_del_hedges = deltet.count_non_del_halfedges()?;
         let nb_non_del_faces = deltet.count_non_del_faces()?;
 
@@ -173,7 +191,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     }
 
     /// Adds a skeletal node
-    pub fn add_node(&'b mut self, del_tet: &[usize; 4]) -> Result<IterNode<'a, 'b>> {
+    pub fn add_node(&'b mut self, del_tet: &[usize; 4]) -> Result<IterNode<'a, 'b, T>> {
         if let Some(&ind_node) = self.del_tet.get(del_tet) {
             return Ok(IterNode {
                 skeleton_interface: self,
@@ -332,7 +350,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
             let tet2 = self.mesh.get_vertex(ind_tet2).unwrap().vertex();
             let tet3 = self.mesh.get_vertex(ind_tet3).unwrap().vertex();
             let tet4 = self.mesh.get_vertex(ind_tet4).unwrap().vertex();
-            let tet_mid = (tet1 + tet2 + tet3 + tet4) * 0.25;
+            let tet_mid = (tet1 + tet2 + tet3 + tet4) * nalgebra::convert::<f64, T>(0.25);
 
             let [ind_tri1, ind_tri2, ind_tri3] = self.edge_tri[ind_edges[0]];
             let tri1 = self.mesh.get_vertex(ind_tri1).unwrap().vertex();
@@ -340,9 +358,9 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
             let tri3 = self.mesh.get_vertex(ind_tri3).unwrap().vertex();
 
             let nor = (tri2 - tri1).cross(&(tri3 - tri2));
-            let tri_mid = (tri1 + tri2 + tri3) / 3.0;
+            let tri_mid = (tri1 + tri2 + tri3) / nalgebra::convert::<f64, T>(3.0);
 
-            if (tri_mid - tet_mid).dot(&nor) > 0.0 {
+            if (tri_mid - tet_mid).dot(&nor) > T::zero() {
                 Ok([1, 0, 1, 0])
             } else {
                 Ok([0, 1, 0, 1])
@@ -414,35 +432,41 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
         self.palve_pedge[self.alve_palve[ind_alve[0]][1]].push(self.edge_pedge_opp[ind_edge][2]);
     }
 
-    pub(super) fn get_node_uncheck(&'b self, ind_node: usize) -> IterNode<'a, 'b> {
+    pub(super) fn get_node_uncheck(&'b self, ind_node: usize) -> IterNode<'a, 'b, T> {
         IterNode {
             skeleton_interface: self,
             ind_node,
         }
     }
 
-    pub(super) fn get_partial_node_uncheck(&'b self, ind_pnode: usize) -> IterPartialNode<'a, 'b> {
+    pub(super) fn get_partial_node_uncheck(
+        &'b self,
+        ind_pnode: usize,
+    ) -> IterPartialNode<'a, 'b, T> {
         IterPartialNode {
             skeleton_interface: self,
             ind_pnode,
         }
     }
 
-    pub(super) fn get_edge_uncheck(&'b self, ind_edge: usize) -> IterEdge<'a, 'b> {
+    pub(super) fn get_edge_uncheck(&'b self, ind_edge: usize) -> IterEdge<'a, 'b, T> {
         IterEdge {
             skeleton_interface: self,
             ind_edge,
         }
     }
 
-    pub(super) fn get_partial_edge_uncheck(&'b self, ind_pedge: usize) -> IterPartialEdge<'a, 'b> {
+    pub(super) fn get_partial_edge_uncheck(
+        &'b self,
+        ind_pedge: usize,
+    ) -> IterPartialEdge<'a, 'b, T> {
         IterPartialEdge {
             skeleton_interface: self,
             ind_pedge,
         }
     }
 
-    pub(super) fn get_alveola_uncheck(&'b self, ind_alveola: usize) -> IterAlveola<'a, 'b> {
+    pub(super) fn get_alveola_uncheck(&'b self, ind_alveola: usize) -> IterAlveola<'a, 'b, T> {
         IterAlveola {
             skeleton_interface: self,
             ind_alveola,
@@ -452,7 +476,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     pub(super) fn get_partial_alveola_uncheck(
         &'b self,
         ind_palveola: usize,
-    ) -> IterPartialAlveola<'a, 'b> {
+    ) -> IterPartialAlveola<'a, 'b, T> {
         IterPartialAlveola {
             skeleton_interface: self,
             ind_palveola,
@@ -460,7 +484,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     }
 
     /// Node getter
-    pub fn get_node(&'b self, ind_node: usize) -> Result<IterNode<'a, 'b>> {
+    pub fn get_node(&'b self, ind_node: usize) -> Result<IterNode<'a, 'b, T>> {
         if ind_node >= self.node_tet.len() {
             return Err(anyhow::Error::msg("Node index out of bounds"));
         }
@@ -468,7 +492,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     }
 
     /// Partial node getter
-    pub fn get_partial_node(&'b self, ind_pnode: usize) -> Result<IterPartialNode<'a, 'b>> {
+    pub fn get_partial_node(&'b self, ind_pnode: usize) -> Result<IterPartialNode<'a, 'b, T>> {
         if ind_pnode >= self.pnode_node.len() {
             return Err(anyhow::Error::msg("Partial node index out of bounds"));
         }
@@ -476,7 +500,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     }
 
     /// Edge getter
-    pub fn get_edge(&'b self, ind_edge: usize) -> Result<IterEdge<'a, 'b>> {
+    pub fn get_edge(&'b self, ind_edge: usize) -> Result<IterEdge<'a, 'b, T>> {
         if ind_edge >= self.edge_tri.len() {
             return Err(anyhow::Error::msg("Edge index out of bounds"));
         }
@@ -484,7 +508,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     }
 
     /// Partial edge getter
-    pub fn get_partial_edge(&'b self, ind_pedge: usize) -> Result<IterPartialEdge<'a, 'b>> {
+    pub fn get_partial_edge(&'b self, ind_pedge: usize) -> Result<IterPartialEdge<'a, 'b, T>> {
         if ind_pedge >= self.pedge_edge.len() {
             return Err(anyhow::Error::msg("Partial edge index out of bounds"));
         }
@@ -492,7 +516,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     }
 
     /// Alveola getter
-    pub fn get_alveola(&'b self, ind_alveola: usize) -> Result<IterAlveola<'a, 'b>> {
+    pub fn get_alveola(&'b self, ind_alveola: usize) -> Result<IterAlveola<'a, 'b, T>> {
         if ind_alveola >= self.alve_seg.len() {
             return Err(anyhow::Error::msg("Alveola index out of bounds"));
         }
@@ -514,7 +538,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     pub fn get_partial_alveola(
         &'b self,
         ind_palveola: usize,
-    ) -> Result<IterPartialAlveola<'a, 'b>> {
+    ) -> Result<IterPartialAlveola<'a, 'b, T>> {
         if ind_palveola >= self.palve_alve.len() {
             return Err(anyhow::Error::msg("Partial alveola index out of bounds"));
         }
@@ -532,12 +556,12 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     }
 
     /// Skeleton getter
-    pub fn get_skeleton(&self) -> &Skeleton3D {
+    pub fn get_skeleton(&self) -> &Skeleton3D<T> {
         &self.skeleton
     }
 
     /// Mesh getter
-    pub fn get_mesh(&self) -> &ManifoldMesh3D {
+    pub fn get_mesh(&self) -> &ManifoldMesh3D<T> {
         self.mesh
     }
 
@@ -566,12 +590,12 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     }
 
     /// Debug meshed getter
-    pub fn get_debug_meshes(&self) -> &Vec<GenericMesh3D> {
+    pub fn get_debug_meshes(&self) -> &Vec<GenericMesh3D<T>> {
         &self.debug_meshes
     }
 
     /// Adds a debug mesh
-    pub fn add_debug_mesh(&mut self, mesh: &GenericMesh3D) -> () {
+    pub fn add_debug_mesh(&mut self, mesh: &GenericMesh3D<T>) -> () {
         self.debug_meshes.push(mesh.clone());
     }
 
@@ -1139,7 +1163,7 @@ impl<'a, 'b> SkeletonInterface3D<'a> {
     }
 }
 
-impl<'a, 'b> IterNode<'a, 'b> {
+impl<'a, 'b, T: Real> IterNode<'a, 'b, T> {
     pub fn ind(&self) -> usize {
         self.ind_node
     }
@@ -1148,8 +1172,8 @@ impl<'a, 'b> IterNode<'a, 'b> {
         self.skeleton_interface.node_tet[self.ind_node]
     }
 
-    pub fn center_and_radius(&self) -> Result<(Vector3<f32>, f32)> {
-        let tet_vert: Vec<Vector3<f32>> = self
+    pub fn center_and_radius(&self) -> Result<(Vector3<T>, T)> {
+        let tet_vert: Vec<Vector3<T>> = self
             .delaunay_tetrahedron()
             .iter()
             .map(|&ind| {
@@ -1167,7 +1191,7 @@ impl<'a, 'b> IterNode<'a, 'b> {
         .ok_or(anyhow::Error::msg("No center and radius found"))
     }
 
-    pub fn partial_nodes(&self) -> [IterPartialNode<'a, 'b>; 4] {
+    pub fn partial_nodes(&self) -> [IterPartialNode<'a, 'b, T>; 4] {
         [
             IterPartialNode {
                 skeleton_interface: self.skeleton_interface,
@@ -1188,7 +1212,7 @@ impl<'a, 'b> IterNode<'a, 'b> {
         ]
     }
 
-    pub fn edges(&self) -> [IterEdge<'a, 'b>; 4] {
+    pub fn edges(&self) -> [IterEdge<'a, 'b, T>; 4] {
         [
             IterEdge {
                 skeleton_interface: self.skeleton_interface,
@@ -1210,7 +1234,7 @@ impl<'a, 'b> IterNode<'a, 'b> {
     }
 }
 
-impl<'a, 'b> IterEdge<'a, 'b> {
+impl<'a, 'b, T: Real> IterEdge<'a, 'b, T> {
     pub fn ind(&self) -> usize {
         self.ind_edge
     }
@@ -1219,8 +1243,8 @@ impl<'a, 'b> IterEdge<'a, 'b> {
         self.skeleton_interface.edge_tri[self.ind_edge]
     }
 
-    pub fn nodes(&self) -> Vec<IterNode<'a, 'b>> {
-        let mut nods: Vec<IterNode> = Vec::new();
+    pub fn nodes(&self) -> Vec<IterNode<'a, 'b, T>> {
+        let mut nods: Vec<IterNode<T>> = Vec::new();
         self.skeleton_interface.edge_node[self.ind_edge]
             .iter()
             .fold(&mut nods, |v, &nod| {
@@ -1282,7 +1306,7 @@ impl<'a, 'b> IterEdge<'a, 'b> {
         false
     }
 
-    pub fn alveolae(&self) -> [IterAlveola<'a, 'b>; 3] {
+    pub fn alveolae(&self) -> [IterAlveola<'a, 'b, T>; 3] {
         [
             IterAlveola {
                 skeleton_interface: self.skeleton_interface,
@@ -1299,7 +1323,7 @@ impl<'a, 'b> IterEdge<'a, 'b> {
         ]
     }
 
-    pub fn partial_edges(&self) -> [IterPartialEdge<'a, 'b>; 6] {
+    pub fn partial_edges(&self) -> [IterPartialEdge<'a, 'b, T>; 6] {
         [
             IterPartialEdge {
                 skeleton_interface: self.skeleton_interface,
@@ -1329,7 +1353,7 @@ impl<'a, 'b> IterEdge<'a, 'b> {
     }
 }
 
-impl<'a, 'b> IterAlveola<'a, 'b> {
+impl<'a, 'b, T: Real> IterAlveola<'a, 'b, T> {
     pub fn ind(&self) -> usize {
         self.ind_alveola
     }
@@ -1342,7 +1366,7 @@ impl<'a, 'b> IterAlveola<'a, 'b> {
         self.skeleton_interface.alve_seg[self.ind_alveola]
     }
 
-    pub fn edges(&self) -> Vec<IterEdge<'a, 'b>> {
+    pub fn edges(&self) -> Vec<IterEdge<'a, 'b, T>> {
         self.skeleton_interface.alve_edge[self.ind_alveola]
             .iter()
             .map(|&ind_edge| IterEdge {
@@ -1370,7 +1394,7 @@ impl<'a, 'b> IterAlveola<'a, 'b> {
             .is_none()
     }
 
-    pub fn partial_alveolae(&self) -> [IterPartialAlveola<'a, 'b>; 2] {
+    pub fn partial_alveolae(&self) -> [IterPartialAlveola<'a, 'b, T>; 2] {
         [
             IterPartialAlveola {
                 skeleton_interface: self.skeleton_interface,
@@ -1384,7 +1408,7 @@ impl<'a, 'b> IterAlveola<'a, 'b> {
     }
 }
 
-impl<'a, 'b> IterPartialNode<'a, 'b> {
+impl<'a, 'b, T: Real> IterPartialNode<'a, 'b, T> {
     pub fn ind(&self) -> usize {
         self.ind_pnode
     }
@@ -1393,14 +1417,14 @@ impl<'a, 'b> IterPartialNode<'a, 'b> {
         self.skeleton_interface.pnode_corner[self.ind_pnode]
     }
 
-    pub fn node(&self) -> IterNode<'a, 'b> {
+    pub fn node(&self) -> IterNode<'a, 'b, T> {
         IterNode {
             skeleton_interface: self.skeleton_interface,
             ind_node: self.skeleton_interface.pnode_node[self.ind_pnode],
         }
     }
 
-    pub fn partial_edge_prev(&self) -> Vec<IterPartialEdge<'a, 'b>> {
+    pub fn partial_edge_prev(&self) -> Vec<IterPartialEdge<'a, 'b, T>> {
         self.skeleton_interface.pnode_pedge_prev[self.ind_pnode]
             .iter()
             .map(|(_ind_palve, &ind_pedge)| IterPartialEdge {
@@ -1410,7 +1434,7 @@ impl<'a, 'b> IterPartialNode<'a, 'b> {
             .collect()
     }
 
-    fn partial_edge_prev_on_alve(&self, ind_palve: usize) -> Option<IterPartialEdge<'a, 'b>> {
+    fn partial_edge_prev_on_alve(&self, ind_palve: usize) -> Option<IterPartialEdge<'a, 'b, T>> {
         if let Some(&ind_pedge) =
             self.skeleton_interface.pnode_pedge_prev[self.ind_pnode].get(&ind_palve)
         {
@@ -1423,7 +1447,7 @@ impl<'a, 'b> IterPartialNode<'a, 'b> {
         }
     }
 
-    pub fn partial_edge_next(&self) -> Vec<IterPartialEdge<'a, 'b>> {
+    pub fn partial_edge_next(&self) -> Vec<IterPartialEdge<'a, 'b, T>> {
         self.skeleton_interface.pnode_pedge_next[self.ind_pnode]
             .iter()
             .map(|(_ind_palve, &ind_pedge)| IterPartialEdge {
@@ -1433,7 +1457,7 @@ impl<'a, 'b> IterPartialNode<'a, 'b> {
             .collect()
     }
 
-    fn partial_edge_next_on_alve(&self, ind_palve: usize) -> Option<IterPartialEdge<'a, 'b>> {
+    fn partial_edge_next_on_alve(&self, ind_palve: usize) -> Option<IterPartialEdge<'a, 'b, T>> {
         if let Some(&ind_pedge) =
             self.skeleton_interface.pnode_pedge_next[self.ind_pnode].get(&ind_palve)
         {
@@ -1447,7 +1471,7 @@ impl<'a, 'b> IterPartialNode<'a, 'b> {
     }
 }
 
-impl<'a, 'b> IterPartialEdge<'a, 'b> {
+impl<'a, 'b, T: Real> IterPartialEdge<'a, 'b, T> {
     pub fn ind(&self) -> usize {
         self.ind_pedge
     }
@@ -1456,14 +1480,14 @@ impl<'a, 'b> IterPartialEdge<'a, 'b> {
         self.skeleton_interface.pedge_corner[self.ind_pedge]
     }
 
-    pub fn edge(&self) -> IterEdge<'a, 'b> {
+    pub fn edge(&self) -> IterEdge<'a, 'b, T> {
         IterEdge {
             skeleton_interface: self.skeleton_interface,
             ind_edge: self.skeleton_interface.pedge_edge[self.ind_pedge],
         }
     }
 
-    pub fn partial_node_first(&self) -> Option<IterPartialNode<'a, 'b>> {
+    pub fn partial_node_first(&self) -> Option<IterPartialNode<'a, 'b, T>> {
         match self.skeleton_interface.pedge_pnode[self.ind_pedge][0] {
             None => None,
             Some(ind_pnode) => Some(IterPartialNode {
@@ -1473,7 +1497,7 @@ impl<'a, 'b> IterPartialEdge<'a, 'b> {
         }
     }
 
-    pub fn partial_node_last(&self) -> Option<IterPartialNode<'a, 'b>> {
+    pub fn partial_node_last(&self) -> Option<IterPartialNode<'a, 'b, T>> {
         match self.skeleton_interface.pedge_pnode[self.ind_pedge][1] {
             None => None,
             Some(ind_pnode) => Some(IterPartialNode {
@@ -1483,21 +1507,21 @@ impl<'a, 'b> IterPartialEdge<'a, 'b> {
         }
     }
 
-    pub fn partial_edge_opposite(&self) -> IterPartialEdge<'a, 'b> {
+    pub fn partial_edge_opposite(&self) -> IterPartialEdge<'a, 'b, T> {
         IterPartialEdge {
             skeleton_interface: self.skeleton_interface,
             ind_pedge: self.skeleton_interface.pedge_opp[self.ind_pedge],
         }
     }
 
-    pub fn partial_edge_neighbor(&self) -> IterPartialEdge<'a, 'b> {
+    pub fn partial_edge_neighbor(&self) -> IterPartialEdge<'a, 'b, T> {
         IterPartialEdge {
             skeleton_interface: self.skeleton_interface,
             ind_pedge: self.skeleton_interface.pedge_neigh[self.ind_pedge],
         }
     }
 
-    pub fn partial_edge_prev(&self) -> Option<IterPartialEdge<'a, 'b>> {
+    pub fn partial_edge_prev(&self) -> Option<IterPartialEdge<'a, 'b, T>> {
         if let Some(pnode) = self.partial_node_first() {
             let palve = self.partial_alveola().ind();
             pnode.partial_edge_prev_on_alve(palve)
@@ -1506,7 +1530,7 @@ impl<'a, 'b> IterPartialEdge<'a, 'b> {
         }
     }
 
-    pub fn partial_edge_next(&self) -> Option<IterPartialEdge<'a, 'b>> {
+    pub fn partial_edge_next(&self) -> Option<IterPartialEdge<'a, 'b, T>> {
         if let Some(pnode) = self.partial_node_last() {
             let palve = self.partial_alveola().ind();
             pnode.partial_edge_next_on_alve(palve)
@@ -1515,7 +1539,7 @@ impl<'a, 'b> IterPartialEdge<'a, 'b> {
         }
     }
 
-    pub fn partial_alveola(&self) -> IterPartialAlveola<'a, 'b> {
+    pub fn partial_alveola(&self) -> IterPartialAlveola<'a, 'b, T> {
         IterPartialAlveola {
             skeleton_interface: self.skeleton_interface,
             ind_palveola: self.skeleton_interface.pedge_palve[self.ind_pedge],
@@ -1545,7 +1569,7 @@ impl<'a, 'b> IterPartialEdge<'a, 'b> {
     }
 }
 
-impl<'a, 'b> IterPartialAlveola<'a, 'b> {
+impl<'a, 'b, T: Real> IterPartialAlveola<'a, 'b, T> {
     pub fn ind(&self) -> usize {
         self.ind_palveola
     }
@@ -1554,21 +1578,21 @@ impl<'a, 'b> IterPartialAlveola<'a, 'b> {
         self.skeleton_interface.palve_corner[self.ind_palveola]
     }
 
-    pub fn alveola(&self) -> IterAlveola<'a, 'b> {
+    pub fn alveola(&self) -> IterAlveola<'a, 'b, T> {
         IterAlveola {
             skeleton_interface: self.skeleton_interface,
             ind_alveola: self.skeleton_interface.palve_alve[self.ind_palveola],
         }
     }
 
-    pub fn partial_alveola_opposite(&self) -> IterPartialAlveola<'a, 'b> {
+    pub fn partial_alveola_opposite(&self) -> IterPartialAlveola<'a, 'b, T> {
         IterPartialAlveola {
             skeleton_interface: self.skeleton_interface,
             ind_palveola: self.skeleton_interface.palve_opp[self.ind_palveola],
         }
     }
 
-    pub fn partial_edges(&self) -> Vec<IterPartialEdge<'a, 'b>> {
+    pub fn partial_edges(&self) -> Vec<IterPartialEdge<'a, 'b, T>> {
         self.skeleton_interface.palve_pedge[self.ind_palveola]
             .iter()
             .map(|&ind_pedge| IterPartialEdge {
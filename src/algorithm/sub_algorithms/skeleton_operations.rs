@@ -1,11 +1,14 @@
 use anyhow::Result;
+use log::{debug, trace};
 use nalgebra::base::*;
+use rand::rngs::StdRng;
 use rand::Rng;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use crate::algorithm::sub_algorithms::skeleton_problematic_path::{
     first_to_boundary, last_to_boundary, SkeletonProblematicPath,
 };
+use crate::geometry::Real;
 use crate::mesh3d::GenericMesh3D;
 
 use super::skeleton_boundary_path;
@@ -16,20 +19,33 @@ use super::SkeletonInterface3D;
 use super::SkeletonSeparation;
 
 /// Computes a random first node on skeleton
-pub fn first_node_in(skeleton_interface: &mut SkeletonInterface3D) -> Result<usize> {
-    let mut rng = rand::thread_rng();
-    let rand_fac = rng.gen_range(0..skeleton_interface.mesh.get_nb_faces());
-    println!("First face: {}", rand_fac);
-
-    let mut cpt = 0;
-    let mut ind_face = 0;
-    for fac in skeleton_interface.get_mesh().faces() {
-        cpt = cpt + 1;
-        if cpt >= rand_fac {
-            ind_face = *fac.0;
-            break;
-        }
+pub fn first_node_in<T: Real>(
+    skeleton_interface: &mut SkeletonInterface3D<T>,
+    rng: &mut StdRng,
+) -> Result<usize> {
+    // sorted indices, so that a seeded generator gives reproducible runs
+    let mut face_inds: Vec<usize> = skeleton_interface
+        .get_mesh()
+        .faces()
+        .keys()
+        .copied()
+        .collect();
+    face_inds.sort();
+    first_node_in_faces(skeleton_interface, &face_inds, rng)
+}
+
+/// Computes a random first node on skeleton, inside a random face among given ones
+pub fn first_node_in_faces<T: Real>(
+    skeleton_interface: &mut SkeletonInterface3D<T>,
+    face_inds: &[usize],
+    rng: &mut StdRng,
+) -> Result<usize> {
+    if face_inds.is_empty() {
+        return Err(anyhow::Error::msg("No face to find first node"));
     }
+    let rand_fac = rng.gen_range(0..face_inds.len());
+    let ind_face = face_inds[rand_fac];
+    debug!("First face: {}", ind_face);
     let face = skeleton_interface.get_mesh().get_face(ind_face)?;
 
     let mut triangle = face.vertices_inds();
@@ -53,14 +69,14 @@ pub fn first_node_in(skeleton_interface: &mut SkeletonInterface3D) -> Result<usi
         let v2 = skeleton_interface.get_mesh().get_vertex(tet[2])?.vertex();
         let v3 = skeleton_interface.get_mesh().get_vertex(tet[3])?.vertex();
 
-        let v_mean = (v0 + v1 + v2 + v3) * 0.25;
+        let v_mean = (v0 + v1 + v2 + v3) * nalgebra::convert::<f64, T>(0.25);
 
-        let inside = normal.dot(&(v_mean - pt_face)) < 0.0;
+        let inside = normal.dot(&(v_mean - pt_face)) < T::zero();
 
         if inside {
             let node = skeleton_interface.add_node(&tet)?;
-            println!(
-                "First node : ({}, {}, {}, {})",
+            debug!(
+                "First node: ({}, {}, {}, {})",
                 node.delaunay_tetrahedron()[0],
                 node.delaunay_tetrahedron()[1],
                 node.delaunay_tetrahedron()[2],
@@ -74,9 +90,31 @@ pub fn first_node_in(skeleton_interface: &mut SkeletonInterface3D) -> Result<usi
 }
 
 /// Computes a random first alveola on skeleton
-pub fn first_alveola_in(skeleton_interface: &mut SkeletonInterface3D) -> Result<usize> {
-    let ind_first_node = first_node_in(skeleton_interface)?;
+pub fn first_alveola_in<T: Real>(
+    skeleton_interface: &mut SkeletonInterface3D<T>,
+    rng: &mut StdRng,
+) -> Result<usize> {
+    let ind_first_node = first_node_in(skeleton_interface, rng)?;
+    first_alveola_from_node(skeleton_interface, ind_first_node)
+}
 
+/// Computes a random first alveola on skeleton, near a random face among given ones
+///
+/// Used to seed the skeletonization of a given mesh component
+pub fn first_alveola_in_faces<T: Real>(
+    skeleton_interface: &mut SkeletonInterface3D<T>,
+    face_inds: &[usize],
+    rng: &mut StdRng,
+) -> Result<usize> {
+    let ind_first_node = first_node_in_faces(skeleton_interface, face_inds, rng)?;
+    first_alveola_from_node(skeleton_interface, ind_first_node)
+}
+
+/// Alveola of a node edge not lying on the mesh
+fn first_alveola_from_node<T: Real>(
+    skeleton_interface: &SkeletonInterface3D<T>,
+    ind_first_node: usize,
+) -> Result<usize> {
     let cur_node = skeleton_interface.get_node(ind_first_node)?;
 
     let edges = cur_node.edges();
@@ -106,8 +144,8 @@ pub fn first_alveola_in(skeleton_interface: &mut SkeletonInterface3D) -> Result<
 }
 
 /// Includes an alveola in the final skeleton
-pub fn include_alveola_in_skel(
-    skeleton_interface: &mut SkeletonInterface3D,
+pub fn include_alveola_in_skel<T: Real>(
+    skeleton_interface: &mut SkeletonInterface3D<T>,
     ind_alveola: usize,
     opt_label: Option<usize>,
 ) -> Result<()> {
@@ -129,7 +167,7 @@ pub fn include_alveola_in_skel(
             .delaunay_tetrahedron()
             .iter()
             .map(|&ind_vertex| Ok(skeleton_interface.mesh.get_vertex(ind_vertex)?.vertex()))
-            .collect::<Result<Vec<Vector3<f32>>>>()?
+            .collect::<Result<Vec<Vector3<T>>>>()?
             .try_into()
             .map_err(|_x: Vec<_>| anyhow::Error::msg("Could not convert vec to array"))
             .unwrap();
@@ -163,8 +201,8 @@ pub fn include_alveola_in_skel(
 }
 
 /// Returns neighbor alveola indices
-pub fn neighbor_alveolae(
-    skeleton_interface: &mut SkeletonInterface3D,
+pub fn neighbor_alveolae<T: Real>(
+    skeleton_interface: &mut SkeletonInterface3D<T>,
     ind_alveola: usize,
 ) -> Result<Vec<usize>> {
     let mut vec_neigh = Vec::new();
@@ -186,8 +224,8 @@ pub fn neighbor_alveolae(
 }
 
 /// Computes full sheet starting from an alveola
-pub fn compute_sheet(
-    skeleton_interface: &mut SkeletonInterface3D,
+pub fn compute_sheet<T: Real>(
+    skeleton_interface: &mut SkeletonInterface3D<T>,
     ind_alveola: usize,
     label: usize,
 ) -> Result<()> {
@@ -226,8 +264,8 @@ pub fn compute_sheet(
 }
 
 /// Returns neighbor partial edges to each singular edge on the sheet
-pub fn outer_partial_edges(
-    skeleton_interface: &SkeletonInterface3D,
+pub fn outer_partial_edges<T: Real>(
+    skeleton_interface: &SkeletonInterface3D<T>,
     current_sheet: &Vec<usize>,
 ) -> Vec<usize> {
     let mut vec_pedges = Vec::new();
@@ -248,7 +286,7 @@ pub fn outer_partial_edges(
 }
 
 /// Returns boundary edges on skeleton
-pub fn boundary_partial_edges(skeleton_interface: &SkeletonInterface3D) -> Vec<usize> {
+pub fn boundary_partial_edges<T: Real>(skeleton_interface: &SkeletonInterface3D<T>) -> Vec<usize> {
     let mut vec_pedges = Vec::new();
     for ind_pedge in 0..skeleton_interface.pedge_edge.len() {
         let pedge = skeleton_interface.get_partial_edge_uncheck(ind_pedge);
@@ -260,7 +298,9 @@ pub fn boundary_partial_edges(skeleton_interface: &SkeletonInterface3D) -> Vec<u
 }
 
 /// Returns problematic edges on skeleton
-pub fn problematic_partial_edges(skeleton_interface: &SkeletonInterface3D) -> Vec<usize> {
+pub fn problematic_partial_edges<T: Real>(
+    skeleton_interface: &SkeletonInterface3D<T>,
+) -> Vec<usize> {
     let mut vec_pedges = Vec::new();
     for ind_pedge in 0..skeleton_interface.pedge_edge.len() {
         let pedge = skeleton_interface.get_partial_edge_uncheck(ind_pedge);
@@ -272,10 +312,10 @@ pub fn problematic_partial_edges(skeleton_interface: &SkeletonInterface3D) -> Ve
 }
 
 /// For each partial edge, computes its saliency
-pub fn estimate_saliencies(
-    skeleton_interface: &SkeletonInterface3D,
+pub fn estimate_saliencies<T: Real>(
+    skeleton_interface: &SkeletonInterface3D<T>,
     vec_pedges: &Vec<usize>,
-) -> Result<Vec<(usize, f32)>> {
+) -> Result<Vec<(usize, T)>> {
     let mut saliencies = Vec::new();
     for &ind_pedge in vec_pedges.iter() {
         if let Some(saliency) =
@@ -288,14 +328,14 @@ pub fn estimate_saliencies(
 }
 
 /// Sorts saliency map
-pub fn sort_saliencies(saliencies: &mut Vec<(usize, f32)>) -> () {
+pub fn sort_saliencies<T: Real>(saliencies: &mut Vec<(usize, T)>) -> () {
     saliencies.sort_by(|&(_, s1), &(_, s2)| (-s1).partial_cmp(&(-s2)).unwrap());
 }
 
 /// Computes singular path associated to boundary partial edge
-pub fn exclusion_singular_path(
+pub fn exclusion_singular_path<T: Real>(
     ind_pedge: usize,
-    skeleton_interface: &mut SkeletonInterface3D,
+    skeleton_interface: &mut SkeletonInterface3D<T>,
 ) -> Result<Option<(SkeletonSingularPath, Vec<usize>, HashSet<usize>)>> {
     let set_alve_to_exclude =
         skeleton_boundary_path::excluded_alveolae(ind_pedge, skeleton_interface);
@@ -330,10 +370,10 @@ pub fn exclusion_singular_path(
 }
 
 /// Computes skeleton separation starting from a partial edge
-pub fn extract_skeleton_separation<'a, 'b>(
-    skeleton_interface: &'b mut SkeletonInterface3D<'a>,
+pub fn extract_skeleton_separation<'a, 'b, T: Real>(
+    skeleton_interface: &'b mut SkeletonInterface3D<'a, T>,
     ind_pedge: usize,
-) -> Result<Option<SkeletonSeparation<'a, 'b>>> {
+) -> Result<Option<SkeletonSeparation<'a, 'b, T>>> {
     let pedge = skeleton_interface.get_partial_edge_uncheck(ind_pedge);
     if pedge.is_singular() {
         let mut skeleton_separation = SkeletonSeparation::create(skeleton_interface, ind_pedge)?;
@@ -347,8 +387,8 @@ pub fn extract_skeleton_separation<'a, 'b>(
 /// Tries to remove a set of faces and add a set of face to the mesh
 ///
 /// If operation fails, leaves mesh unchanged
-pub fn try_remove_and_add<'a, 'b>(
-    skeleton_interface: &'b mut SkeletonInterface3D<'a>,
+pub fn try_remove_and_add<'a, 'b, T: Real>(
+    skeleton_interface: &'b mut SkeletonInterface3D<'a, T>,
     vec_rem_faces: &Vec<usize>,
     vec_add_faces: &Vec<[usize; 3]>,
 ) -> Result<bool> {
@@ -371,13 +411,14 @@ pub fn try_remove_and_add<'a, 'b>(
         vec_free_vert.push(vert_inds[1]);
         vec_free_vert.push(vert_inds[2]);
     }
-    let mut set_free_vert: HashSet<usize> = HashSet::from_iter(vec_free_vert);
+    // ordered set, so that free vertices are given in the same order on each run
+    let mut set_free_vert: BTreeSet<usize> = BTreeSet::from_iter(vec_free_vert);
     for &[ind_v1, ind_v2, ind_v3] in vec_add_faces {
         set_free_vert.remove(&ind_v1);
         set_free_vert.remove(&ind_v2);
         set_free_vert.remove(&ind_v3);
     }
-    let vec_vert_mid: Vec<Vector3<f32>> = vec_add_faces
+    let vec_vert_mid: Vec<Vector3<T>> = vec_add_faces
         .iter()
         .map(|&[ind_v1, ind_v2, ind_v3]| {
             let vert1 = skeleton_interface
@@ -395,7 +436,7 @@ pub fn try_remove_and_add<'a, 'b>(
                 .get_vertex(ind_v3)
                 .unwrap()
                 .vertex();
-            (vert1 + vert2 + vert3) / 3.0
+            (vert1 + vert2 + vert3) / nalgebra::convert::<f64, T>(3.0)
         })
         .collect();
 
@@ -457,13 +498,13 @@ pub fn try_remove_and_add<'a, 'b>(
 }
 
 /// Collect list of faces on mesh portion described by separation
-pub fn collect_mesh_faces_index(
-    skeleton_separation: &SkeletonSeparation,
-    epsilon: f32,
+pub fn collect_mesh_faces_index<T: Real>(
+    skeleton_separation: &SkeletonSeparation<T>,
+    epsilon: T,
 ) -> Result<Option<Vec<usize>>> {
-    fn last_hedge_deletion(
+    fn last_hedge_deletion<T: Real>(
         mesh_paths_external: &mut Vec<Vec<usize>>,
-        skeleton_separation: &SkeletonSeparation,
+        skeleton_separation: &SkeletonSeparation<T>,
     ) -> Result<bool> {
         if let Some(mut mesh_path_external) = mesh_paths_external.pop() {
             if let Some(ind_hedge) = mesh_path_external.pop() {
@@ -495,10 +536,10 @@ pub fn collect_mesh_faces_index(
         Ok(false)
     }
 
-    fn last_hedge_fusion(
+    fn last_hedge_fusion<T: Real>(
         mesh_paths_external: &mut Vec<Vec<usize>>,
         mesh_paths_internal: &mut Vec<Vec<usize>>,
-        skeleton_separation: &SkeletonSeparation,
+        skeleton_separation: &SkeletonSeparation<T>,
     ) -> Result<bool> {
         if let Some(mut mesh_path_external) = mesh_paths_external.pop() {
             if let Some(ind_hedge) = mesh_path_external.pop() {
@@ -533,12 +574,12 @@ pub fn collect_mesh_faces_index(
         Ok(false)
     }
 
-    fn last_hedge_expansion(
+    fn last_hedge_expansion<T: Real>(
         mesh_paths_external: &mut Vec<Vec<usize>>,
-        skeleton_separation: &SkeletonSeparation,
-        center_mat: &MatrixXx3<f32>,
-        radius_mat: &MatrixXx1<f32>,
-        epsilon: f32,
+        skeleton_separation: &SkeletonSeparation<T>,
+        center_mat: &MatrixXx3<T>,
+        radius_mat: &MatrixXx1<T>,
+        epsilon: T,
         faces: &mut Vec<usize>,
     ) -> Result<bool> {
         if let Some(mut mesh_path_external) = mesh_paths_external.pop() {
@@ -695,8 +736,8 @@ pub fn collect_mesh_faces_index(
 }
 
 /// Estimates Delaunay faces to add on mesh to close the given separation
-pub fn collect_closing_faces(
-    skeleton_separation: &SkeletonSeparation,
+pub fn collect_closing_faces<T: Real>(
+    skeleton_separation: &SkeletonSeparation<T>,
     removed_faces: &Vec<usize>,
 ) -> Result<Option<Vec<[usize; 3]>>> {
     let mut unfaced_hedges = HashSet::new();
@@ -783,8 +824,8 @@ pub fn collect_closing_faces(
 }
 
 /// (Debug) Estimates Delaunay faces to add on mesh to close the given separation
-pub fn collectable_closing_faces(
-    skeleton_separation: &SkeletonSeparation,
+pub fn collectable_closing_faces<T: Real>(
+    skeleton_separation: &SkeletonSeparation<T>,
     removed_faces: &Vec<usize>,
 ) -> Result<Vec<[usize; 3]>> {
     let mut unfaced_hedges = HashSet::new();
@@ -821,12 +862,10 @@ pub fn collectable_closing_faces(
 
     let mut closing_faces = Vec::new();
     loop {
-        println!("new iter");
+        trace!("new iter");
         for path in palve_paths_external.iter() {
-            path.print();
-            println!("");
+            trace!("{}", path);
         }
-        println!("");
 
         if let Some(mut palve_path) = palve_paths_external.pop() {
             if palve_path.closed() {
@@ -842,12 +881,12 @@ pub fn collectable_closing_faces(
             }
 
             if let Some(couple) = palve_path.get_couple_to_fusion() {
-                println!("fusion");
+                trace!("fusion");
                 let mut paths_res = palve_path.fusion_couple(couple)?;
                 palve_paths_external.append(&mut paths_res);
                 continue;
             } else if let Some(ind_exp) = palve_path.get_ind_to_expand() {
-                println!("expand");
+                trace!("expand");
                 palve_path.expand_ind(ind_exp, &mut closing_faces)?;
                 palve_paths_external.push(palve_path);
                 continue;
@@ -862,11 +901,11 @@ pub fn collectable_closing_faces(
 }
 
 /// (Debug) Creates debug meshes associated to set of faces
-pub fn create_debug_meshes<'a, 'b>(
-    skeleton_separation: &SkeletonSeparation<'a, 'b>,
+pub fn create_debug_meshes<'a, 'b, T: Real>(
+    skeleton_separation: &SkeletonSeparation<'a, 'b, T>,
     vec_rem_faces: &Vec<usize>,
     vec_add_faces: &Vec<[usize; 3]>,
-) -> Result<Vec<GenericMesh3D>> {
+) -> Result<Vec<GenericMesh3D<T>>> {
     let mut debug_meshes = Vec::new();
     let mut debug_rem = GenericMesh3D::new();
     for &ind_face in vec_rem_faces {
@@ -1058,9 +1097,9 @@ pub fn create_debug_meshes<'a, 'b>(
 }
 
 /// Try to solve problematic path
-pub fn try_solve_problematic_path(
+pub fn try_solve_problematic_path<T: Real>(
     skel_prob: &mut SkeletonProblematicPath,
-    skeleton_interface: &mut SkeletonInterface3D,
+    skeleton_interface: &mut SkeletonInterface3D<T>,
     label_pedge: usize,
     new_label: usize,
 ) -> Result<bool> {
@@ -1093,9 +1132,9 @@ pub fn try_solve_problematic_path(
 }
 
 /// Problematic edges correction
-pub fn handle_problematic_pedge(
+pub fn handle_problematic_pedge<T: Real>(
     ind_pedge: usize,
-    skeleton_interface: &mut SkeletonInterface3D,
+    skeleton_interface: &mut SkeletonInterface3D<T>,
     label: usize,
 ) -> Result<usize> {
     let mut skel_prob =
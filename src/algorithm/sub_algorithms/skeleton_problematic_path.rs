@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use anyhow::Result;
 use nalgebra::base::*;
 
+use crate::geometry::Real;
+
 use super::SkeletonInterface3D;
 
 pub enum State {
@@ -20,9 +22,9 @@ pub struct SkeletonProblematicPath {
 }
 
 impl SkeletonProblematicPath {
-    pub fn create(
+    pub fn create<T: Real>(
         ind_pedge: usize,
-        skeleton_interface: &mut SkeletonInterface3D,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
     ) -> Result<SkeletonProblematicPath> {
         let edge = skeleton_interface
             .get_partial_edge_uncheck(ind_pedge)
@@ -48,7 +50,10 @@ impl SkeletonProblematicPath {
         &self.components_non_manifold
     }
 
-    pub fn append_last(&mut self, skeleton_interface: &mut SkeletonInterface3D) -> Result<State> {
+    pub fn append_last<T: Real>(
+        &mut self,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_last) = self.opt_ind_pedge_last {
             let edge = skeleton_interface
                 .get_partial_edge_uncheck(ind_pedge_last)
@@ -72,7 +77,10 @@ impl SkeletonProblematicPath {
         }
     }
 
-    pub fn append_first(&mut self, skeleton_interface: &mut SkeletonInterface3D) -> Result<State> {
+    pub fn append_first<T: Real>(
+        &mut self,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_first) = self.opt_ind_pedge_first {
             let edge = skeleton_interface
                 .get_partial_edge_uncheck(ind_pedge_first)
@@ -96,7 +104,7 @@ impl SkeletonProblematicPath {
         }
     }
 
-    fn check_end(&mut self, skeleton_interface: &SkeletonInterface3D) -> Result<State> {
+    fn check_end<T: Real>(&mut self, skeleton_interface: &SkeletonInterface3D<T>) -> Result<State> {
         if let Some(ind_pedge_last) = self.opt_ind_pedge_last {
             let edge_last = skeleton_interface
                 .get_partial_edge_uncheck(ind_pedge_last)
@@ -125,7 +133,7 @@ impl SkeletonProblematicPath {
         }
     }
 
-    fn check_beg(&mut self, skeleton_interface: &SkeletonInterface3D) -> Result<State> {
+    fn check_beg<T: Real>(&mut self, skeleton_interface: &SkeletonInterface3D<T>) -> Result<State> {
         if let Some(ind_pedge_first) = self.opt_ind_pedge_first {
             let edge_first = skeleton_interface
                 .get_partial_edge_uncheck(ind_pedge_first)
@@ -154,7 +162,10 @@ impl SkeletonProblematicPath {
         }
     }
 
-    pub fn rotate_last(&mut self, skeleton_interface: &SkeletonInterface3D) -> Result<State> {
+    pub fn rotate_last<T: Real>(
+        &mut self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_last) = self.opt_ind_pedge_last {
             let pedge = skeleton_interface.get_partial_edge_uncheck(ind_pedge_last);
             let pedge_neigh = pedge.partial_edge_neighbor();
@@ -175,7 +186,10 @@ impl SkeletonProblematicPath {
         }
     }
 
-    pub fn rotate_first(&mut self, skeleton_interface: &SkeletonInterface3D) -> Result<State> {
+    pub fn rotate_first<T: Real>(
+        &mut self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_first) = self.opt_ind_pedge_first {
             let pedge = skeleton_interface.get_partial_edge_uncheck(ind_pedge_first);
             let pedge_neigh = pedge.partial_edge_neighbor();
@@ -204,9 +218,9 @@ impl SkeletonProblematicPath {
         self.opt_ind_pedge_first
     }
 
-    pub fn follow_problematic_path(
+    pub fn follow_problematic_path<T: Real>(
         &mut self,
-        skeleton_interface: &mut SkeletonInterface3D,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
     ) -> Result<()> {
         loop {
             if let Some(ind_pedge_last) = self.ind_last_partial_edge() {
@@ -235,9 +249,9 @@ impl SkeletonProblematicPath {
         Ok(())
     }
 
-    pub fn append_after_last(
+    pub fn append_after_last<T: Real>(
         &mut self,
-        skeleton_interface: &mut SkeletonInterface3D,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
     ) -> Result<State> {
         if let Some(ind_pedge_after_last) = self.opt_ind_pedge_after_last {
             let edge = skeleton_interface
@@ -262,9 +276,9 @@ impl SkeletonProblematicPath {
         }
     }
 
-    pub fn append_before_first(
+    pub fn append_before_first<T: Real>(
         &mut self,
-        skeleton_interface: &mut SkeletonInterface3D,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
     ) -> Result<State> {
         if let Some(ind_pedge_before_first) = self.opt_ind_pedge_before_first {
             let edge = skeleton_interface
@@ -289,7 +303,10 @@ impl SkeletonProblematicPath {
         }
     }
 
-    fn check_after_end(&mut self, skeleton_interface: &SkeletonInterface3D) -> Result<State> {
+    fn check_after_end<T: Real>(
+        &mut self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_after_last) = self.opt_ind_pedge_after_last {
             if self.components_boundary.len() == 0 {
                 return Ok(State::Computing);
@@ -321,7 +338,10 @@ impl SkeletonProblematicPath {
         }
     }
 
-    fn check_before_beg(&mut self, skeleton_interface: &SkeletonInterface3D) -> Result<State> {
+    fn check_before_beg<T: Real>(
+        &mut self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_before_first) = self.opt_ind_pedge_before_first {
             let edge_first = skeleton_interface
                 .get_partial_edge_uncheck(ind_pedge_before_first)
@@ -350,7 +370,10 @@ impl SkeletonProblematicPath {
         }
     }
 
-    pub fn rotate_after_last(&mut self, skeleton_interface: &SkeletonInterface3D) -> Result<State> {
+    pub fn rotate_after_last<T: Real>(
+        &mut self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_after_last) = self.opt_ind_pedge_after_last {
             let pedge = skeleton_interface.get_partial_edge_uncheck(ind_pedge_after_last);
             let pedge_neigh = pedge.partial_edge_neighbor();
@@ -371,9 +394,9 @@ impl SkeletonProblematicPath {
         }
     }
 
-    pub fn rotate_before_first(
+    pub fn rotate_before_first<T: Real>(
         &mut self,
-        skeleton_interface: &SkeletonInterface3D,
+        skeleton_interface: &SkeletonInterface3D<T>,
     ) -> Result<State> {
         if let Some(ind_pedge_before_first) = self.opt_ind_pedge_before_first {
             let pedge = skeleton_interface.get_partial_edge_uncheck(ind_pedge_before_first);
@@ -395,9 +418,9 @@ impl SkeletonProblematicPath {
         }
     }
 
-    pub fn follow_boundary_path_from_first(
+    pub fn follow_boundary_path_from_first<T: Real>(
         self: &mut SkeletonProblematicPath,
-        skeleton_interface: &mut SkeletonInterface3D,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
     ) -> Result<bool> {
         let ind_pedge_first = self.components_non_manifold.first().unwrap();
         let pedge_first = skeleton_interface
@@ -425,9 +448,9 @@ impl SkeletonProblematicPath {
         Ok(self.components_boundary.len() != 0)
     }
 
-    pub fn follow_boundary_path_from_last(
+    pub fn follow_boundary_path_from_last<T: Real>(
         self: &mut SkeletonProblematicPath,
-        skeleton_interface: &mut SkeletonInterface3D,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
     ) -> Result<bool> {
         let ind_pedge_last = self.components_non_manifold.last().unwrap();
         let pedge_last = skeleton_interface
@@ -476,9 +499,9 @@ impl SkeletonProblematicPath {
     // }
 }
 
-fn next_pedges_to_eval(
+fn next_pedges_to_eval<T: Real>(
     ind_node: usize,
-    skeleton_interface: &SkeletonInterface3D,
+    skeleton_interface: &SkeletonInterface3D<T>,
     label: usize,
 ) -> Vec<usize> {
     let mut vec_res = Vec::new();
@@ -502,7 +525,7 @@ fn next_pedges_to_eval(
     vec_res
 }
 
-fn dist_min(ctr: &Vector3<f32>, vec_centers: &Vec<Vector3<f32>>) -> f32 {
+fn dist_min<T: Real>(ctr: &Vector3<T>, vec_centers: &Vec<Vector3<T>>) -> T {
     vec_centers
         .iter()
         .map(|ctr_cur| (ctr - ctr_cur).norm())
@@ -520,12 +543,12 @@ fn dist_min(ctr: &Vector3<f32>, vec_centers: &Vec<Vector3<f32>>) -> f32 {
         .unwrap()
 }
 
-pub(super) fn last_to_boundary(
+pub(super) fn last_to_boundary<T: Real>(
     skel_prob: &SkeletonProblematicPath,
-    skeleton_interface: &mut SkeletonInterface3D,
+    skeleton_interface: &mut SkeletonInterface3D<T>,
     label: usize,
 ) -> Result<Vec<usize>> {
-    let vec_centers: Vec<Vector3<f32>> = skel_prob
+    let vec_centers: Vec<Vector3<T>> = skel_prob
         .components_boundary
         .iter()
         .map(|&ind_pedge| {
@@ -546,7 +569,7 @@ pub(super) fn last_to_boundary(
 
     let node_init = pedge_last.partial_node_last().unwrap().node();
 
-    map_nodes_dist.insert(node_init.ind(), 0.0);
+    map_nodes_dist.insert(node_init.ind(), T::zero());
     map_nodes_prev.insert(node_init.ind(), None);
     map_nodes_next_to_eval.insert(
         node_init.ind(),
@@ -641,12 +664,12 @@ pub(super) fn last_to_boundary(
     Ok(vec_edges)
 }
 
-pub(super) fn first_to_boundary(
+pub(super) fn first_to_boundary<T: Real>(
     skel_prob: &SkeletonProblematicPath,
-    skeleton_interface: &mut SkeletonInterface3D,
+    skeleton_interface: &mut SkeletonInterface3D<T>,
     label: usize,
 ) -> Result<Vec<usize>> {
-    let vec_centers: Vec<Vector3<f32>> = skel_prob
+    let vec_centers: Vec<Vector3<T>> = skel_prob
         .components_boundary
         .iter()
         .map(|&ind_pedge| {
@@ -667,7 +690,7 @@ pub(super) fn first_to_boundary(
 
     let node_init = pedge_first.partial_node_first().unwrap().node();
 
-    map_nodes_dist.insert(node_init.ind(), 0.0);
+    map_nodes_dist.insert(node_init.ind(), T::zero());
     map_nodes_prev.insert(node_init.ind(), None);
     map_nodes_next_to_eval.insert(
         node_init.ind(),
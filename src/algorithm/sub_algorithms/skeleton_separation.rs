@@ -1,21 +1,23 @@
 use anyhow::Result;
 
+use crate::geometry::Real;
+
 use super::SkeletonInterface3D;
 use super::SkeletonSingularPath;
 
 /// Sepration on skeleton
-pub struct SkeletonSeparation<'a, 'b> {
-    skeleton_interface: &'b mut SkeletonInterface3D<'a>,
+pub struct SkeletonSeparation<'a, 'b, T = f32> {
+    skeleton_interface: &'b mut SkeletonInterface3D<'a, T>,
     external_path: SkeletonSingularPath,
     internal_paths: Vec<SkeletonSingularPath>,
 }
 
-impl<'a, 'b> SkeletonSeparation<'a, 'b> {
+impl<'a, 'b, T: Real> SkeletonSeparation<'a, 'b, T> {
     /// Separation constructor
     pub fn create(
-        skeleton_interface: &'b mut SkeletonInterface3D<'a>,
+        skeleton_interface: &'b mut SkeletonInterface3D<'a, T>,
         ind_pedge: usize,
-    ) -> Result<SkeletonSeparation<'a, 'b>> {
+    ) -> Result<SkeletonSeparation<'a, 'b, T>> {
         let external_path = SkeletonSingularPath::create(ind_pedge);
         Ok(SkeletonSeparation {
             skeleton_interface,
@@ -26,9 +28,9 @@ impl<'a, 'b> SkeletonSeparation<'a, 'b> {
 
     /// Creates separation from singular path alone
     pub fn from_singular_path(
-        skeleton_interface: &'b mut SkeletonInterface3D<'a>,
+        skeleton_interface: &'b mut SkeletonInterface3D<'a, T>,
         sing_path: SkeletonSingularPath,
-    ) -> SkeletonSeparation<'a, 'b> {
+    ) -> SkeletonSeparation<'a, 'b, T> {
         SkeletonSeparation {
             skeleton_interface,
             external_path: sing_path,
@@ -37,7 +39,7 @@ impl<'a, 'b> SkeletonSeparation<'a, 'b> {
     }
 
     /// Skeleton interface getter
-    pub fn skeleton_interface(&self) -> &SkeletonInterface3D {
+    pub fn skeleton_interface(&self) -> &SkeletonInterface3D<T> {
         self.skeleton_interface
     }
 
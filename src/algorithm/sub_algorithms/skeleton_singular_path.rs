@@ -1,6 +1,8 @@
 use anyhow::Result;
 use nalgebra::*;
 
+use crate::geometry::Real;
+
 use super::SkeletonInterface3D;
 
 #[derive(Copy, Clone)]
@@ -27,7 +29,7 @@ impl SkeletonSingularPath {
         }
     }
 
-    pub fn mesh_path(&self, skeleton_interface: &SkeletonInterface3D) -> Vec<usize> {
+    pub fn mesh_path<T: Real>(&self, skeleton_interface: &SkeletonInterface3D<T>) -> Vec<usize> {
         let mut path = Vec::new();
         for ind1 in 0..self.components.len() {
             let ind2 = (ind1 + 1) % self.components.len();
@@ -49,7 +51,10 @@ impl SkeletonSingularPath {
         &self.components
     }
 
-    pub fn append_last(&mut self, skeleton_interface: &mut SkeletonInterface3D) -> Result<State> {
+    pub fn append_last<T: Real>(
+        &mut self,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_last) = self.opt_ind_pedge_last {
             let edge = skeleton_interface
                 .get_partial_edge_uncheck(ind_pedge_last)
@@ -74,7 +79,10 @@ impl SkeletonSingularPath {
         }
     }
 
-    fn check_loop(&mut self, skeleton_interface: &SkeletonInterface3D) -> Result<State> {
+    fn check_loop<T: Real>(
+        &mut self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_last) = self.opt_ind_pedge_last {
             let part_first = self.components.first().unwrap();
             let looped = match part_first {
@@ -109,7 +117,10 @@ impl SkeletonSingularPath {
         }
     }
 
-    pub fn rotate_last(&mut self, skeleton_interface: &SkeletonInterface3D) -> Result<State> {
+    pub fn rotate_last<T: Real>(
+        &mut self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<State> {
         if let Some(ind_pedge_last) = self.opt_ind_pedge_last {
             let pedge = skeleton_interface.get_partial_edge_uncheck(ind_pedge_last);
             let pedge_neigh = pedge.partial_edge_neighbor();
@@ -159,7 +170,7 @@ impl SkeletonSingularPath {
             .collect()
     }
 
-    pub fn ind_alveolae(&self, skeleton_interface: &SkeletonInterface3D) -> Vec<usize> {
+    pub fn ind_alveolae<T: Real>(&self, skeleton_interface: &SkeletonInterface3D<T>) -> Vec<usize> {
         self.components
             .iter()
             .filter_map(|pp| match pp {
@@ -175,7 +186,10 @@ impl SkeletonSingularPath {
             .collect()
     }
 
-    pub fn closable_path(&self, skeleton_interface: &SkeletonInterface3D) -> Result<bool> {
+    pub fn closable_path<T: Real>(
+        &self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<bool> {
         let mut has_deg1 = false;
         for ind in 0..self.components.len() {
             let ind_next = (ind + 1) % self.components.len();
@@ -203,7 +217,7 @@ impl SkeletonSingularPath {
         return Ok(has_deg1);
     }
 
-    pub fn nodes(&self, skeleton_interface: &SkeletonInterface3D) -> Vec<usize> {
+    pub fn nodes<T: Real>(&self, skeleton_interface: &SkeletonInterface3D<T>) -> Vec<usize> {
         let mut nodes: Vec<usize> = self
             .components
             .iter()
@@ -221,13 +235,13 @@ impl SkeletonSingularPath {
         nodes
     }
 
-    pub fn basis_spheres_matrices(
+    pub fn basis_spheres_matrices<T: Real>(
         &self,
-        skeleton_interface: &SkeletonInterface3D,
-    ) -> Result<(MatrixXx3<f32>, MatrixXx1<f32>)> {
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<(MatrixXx3<T>, MatrixXx1<T>)> {
         let ind_nodes = self.nodes(skeleton_interface);
-        let mut center_mat = MatrixXx3::<f32>::zeros(ind_nodes.len());
-        let mut radius_mat = MatrixXx1::<f32>::zeros(ind_nodes.len());
+        let mut center_mat = MatrixXx3::<T>::zeros(ind_nodes.len());
+        let mut radius_mat = MatrixXx1::<T>::zeros(ind_nodes.len());
 
         for i in 0..ind_nodes.len() {
             let ind_node = ind_nodes[i];
@@ -244,9 +258,9 @@ impl SkeletonSingularPath {
         Ok((center_mat, radius_mat))
     }
 
-    pub fn follow_singular_path(
+    pub fn follow_singular_path<T: Real>(
         &mut self,
-        skeleton_interface: &mut SkeletonInterface3D,
+        skeleton_interface: &mut SkeletonInterface3D<T>,
     ) -> Result<()> {
         loop {
             if let Some(ind_pedge_last) = self.ind_last_partial_edge() {
@@ -263,7 +277,10 @@ impl SkeletonSingularPath {
         Ok(())
     }
 
-    pub fn halfedges_path(&self, skeleton_interface: &SkeletonInterface3D) -> Result<Vec<usize>> {
+    pub fn halfedges_path<T: Real>(
+        &self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<Vec<usize>> {
         let vec_bnd_path_vert = self.mesh_path(skeleton_interface);
         let mut mesh_path_hedge = Vec::new();
         for ind1 in 0..vec_bnd_path_vert.len() {
@@ -283,7 +300,10 @@ impl SkeletonSingularPath {
         Ok(mesh_path_hedge)
     }
 
-    pub fn alveolae_path(&self, skeleton_interface: &SkeletonInterface3D) -> Result<Vec<usize>> {
+    pub fn alveolae_path<T: Real>(
+        &self,
+        skeleton_interface: &SkeletonInterface3D<T>,
+    ) -> Result<Vec<usize>> {
         let mesh_path = self.mesh_path(skeleton_interface);
         let mut palve_path = Vec::new();
         for ind1 in 0..mesh_path.len() {
@@ -312,7 +332,7 @@ impl SkeletonSingularPath {
         Ok(palve_path)
     }
 
-    pub fn print(&self, skeleton_interface: &SkeletonInterface3D) -> () {
+    pub fn print<T: Real>(&self, skeleton_interface: &SkeletonInterface3D<T>) -> () {
         for &part in self.components.iter() {
             match part {
                 PathPart::PartialEdge(ind_pedge) => {
@@ -0,0 +1,505 @@
+use anyhow::Result;
+use log::warn;
+use nalgebra::base::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::geometry::predicates;
+
+/// Delaunay tetrahedralization backend
+pub trait Tetrahedralizer {
+    /// Computes the Delaunay tetrahedra of a point set, as indices in the point slice
+    fn tetrahedralize(&self, points: &[Vector3<f64>]) -> Result<Vec<[usize; 4]>>;
+}
+
+/// Backend used when none is given: Tetgen with the `tetgen` feature, pure Rust otherwise
+#[cfg(feature = "tetgen")]
+pub type DefaultTetrahedralizer = TetgenTetrahedralizer;
+/// Backend used when none is given: Tetgen with the `tetgen` feature, pure Rust otherwise
+#[cfg(not(feature = "tetgen"))]
+pub type DefaultTetrahedralizer = BowyerWatsonTetrahedralizer;
+
+/// Tetgen backend (C++ library through the tritet binding)
+#[cfg(feature = "tetgen")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TetgenTetrahedralizer;
+
+#[cfg(feature = "tetgen")]
+fn to_anyhow(err: tritet::StrError) -> anyhow::Error {
+    anyhow::Error::msg(err.to_string())
+}
+
+#[cfg(feature = "tetgen")]
+impl Tetrahedralizer for TetgenTetrahedralizer {
+    fn tetrahedralize(&self, points: &[Vector3<f64>]) -> Result<Vec<[usize; 4]>> {
+        let mut tetgen = tritet::Tetgen::new(points.len(), None, None, None).map_err(to_anyhow)?;
+        for (ind, point) in points.iter().enumerate() {
+            tetgen
+                .set_point(ind, point[0], point[1], point[2])
+                .map_err(to_anyhow)?;
+        }
+
+        tetgen.generate_delaunay(false).map_err(to_anyhow)?;
+
+        Ok((0..tetgen.ntet())
+            .map(|t| [0, 1, 2, 3].map(|m| tetgen.tet_node(t, m)))
+            .collect())
+    }
+}
+
+/// Exact coordinates of a point, as a hashable key
+fn point_key(point: &Vector3<f64>) -> [u64; 3] {
+    [0, 1, 2].map(|dim| point[dim].to_bits())
+}
+
+/// Tetrahedralization computed beforehand (e.g. loaded from a file), only valid for its point set
+///
+/// Points left out of the tetrahedra are only accepted if they duplicate a point in use, as
+/// duplicated points are skipped by the tetrahedralization backends
+#[derive(Clone, Debug)]
+pub struct PrecomputedTetrahedralizer {
+    tetrahedra: Vec<[usize; 4]>,
+}
+
+impl PrecomputedTetrahedralizer {
+    /// Wraps tetrahedra given as point indices
+    pub fn new(tetrahedra: Vec<[usize; 4]>) -> PrecomputedTetrahedralizer {
+        PrecomputedTetrahedralizer { tetrahedra }
+    }
+}
+
+impl Tetrahedralizer for PrecomputedTetrahedralizer {
+    fn tetrahedralize(&self, points: &[Vector3<f64>]) -> Result<Vec<[usize; 4]>> {
+        let mut used = vec![false; points.len()];
+        for tetra in self.tetrahedra.iter() {
+            for &ind in tetra.iter() {
+                if ind >= points.len() {
+                    return Err(anyhow::Error::msg(
+                        "tetrahedralize(): Precomputed tetrahedron refers to a missing point",
+                    ));
+                }
+                used[ind] = true;
+            }
+        }
+        // a point left out, unless duplicated, means the tetrahedralization belongs to another
+        // point set
+        let used_points: HashSet<[u64; 3]> = (0..points.len())
+            .filter(|&ind| used[ind])
+            .map(|ind| point_key(&points[ind]))
+            .collect();
+        let uncovered = (0..points.len())
+            .any(|ind| !used[ind] && !used_points.contains(&point_key(&points[ind])));
+        if uncovered {
+            return Err(anyhow::Error::msg(
+                "tetrahedralize(): Precomputed tetrahedra do not cover all points",
+            ));
+        }
+        Ok(self.tetrahedra.clone())
+    }
+}
+
+/// Pure Rust backend: incremental Bowyer-Watson insertion with exact predicates
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BowyerWatsonTetrahedralizer;
+
+// vertex at infinity, closing the convex hull with ghost tetrahedra
+const INFINITE: usize = usize::MAX;
+
+/// Triangulation under construction: tetrahedra are positively oriented, neighbor i is opposite to vertex i
+struct BowyerWatson<'a> {
+    points: &'a [Vector3<f64>],
+    tetras: Vec<[usize; 4]>,
+    neighbors: Vec<[usize; 4]>,
+    alive: Vec<bool>,
+    free: Vec<usize>,
+    stamps: Vec<usize>,
+    stamp: usize,
+    last: usize,
+    rand_state: u64,
+}
+
+impl<'a> BowyerWatson<'a> {
+    fn new(points: &'a [Vector3<f64>], tetra: [usize; 4]) -> BowyerWatson<'a> {
+        let mut bw = BowyerWatson {
+            points,
+            tetras: vec![tetra],
+            neighbors: vec![[0; 4]],
+            alive: vec![true],
+            free: Vec::new(),
+            stamps: vec![0],
+            stamp: 0,
+            last: 0,
+            rand_state: 0x2545_f491_4f6c_dd1d,
+        };
+        // one ghost tetrahedron per face, the infinite vertex being on the outer side
+        for i in 0..4 {
+            let mut ghost = tetra;
+            ghost[i] = INFINITE;
+            ghost.swap((i + 1) % 4, (i + 2) % 4);
+            let ind_ghost = bw.add_tetra(ghost);
+            bw.neighbors[0][i] = ind_ghost;
+            bw.neighbors[ind_ghost][i] = 0;
+        }
+        for g1 in 1..5 {
+            for g2 in 1..5 {
+                if g1 != g2 {
+                    let shared = bw.shared_face_index(g1, g2);
+                    bw.neighbors[g1][shared] = g2;
+                }
+            }
+        }
+        bw
+    }
+
+    fn add_tetra(&mut self, tetra: [usize; 4]) -> usize {
+        if let Some(ind) = self.free.pop() {
+            self.tetras[ind] = tetra;
+            self.alive[ind] = true;
+            ind
+        } else {
+            self.tetras.push(tetra);
+            self.neighbors.push([0; 4]);
+            self.alive.push(true);
+            self.stamps.push(0);
+            self.tetras.len() - 1
+        }
+    }
+
+    /// Index in t1 of the vertex missing from t2
+    fn shared_face_index(&self, t1: usize, t2: usize) -> usize {
+        (0..4)
+            .find(|&i| !self.tetras[t2].contains(&self.tetras[t1][i]))
+            .unwrap_or(0)
+    }
+
+    fn is_ghost(&self, t: usize) -> bool {
+        self.tetras[t].contains(&INFINITE)
+    }
+
+    /// Orientation of tetrahedron t with its vertex i replaced by p (vertex i may be infinite)
+    fn orient_with(&self, t: usize, i: usize, p: &Vector3<f64>) -> f64 {
+        let pt = |j: usize| {
+            if j == i {
+                p
+            } else {
+                &self.points[self.tetras[t][j]]
+            }
+        };
+        predicates::orient_3d(pt(0), pt(1), pt(2), pt(3))
+    }
+
+    fn in_conflict(&self, t: usize, p: &Vector3<f64>) -> bool {
+        let tetra = self.tetras[t];
+        if let Some(i) = tetra.iter().position(|&v| v == INFINITE) {
+            // ghost tetrahedron: p sees the hull face, or lies in its circumcircle
+            let orient = self.orient_with(t, i, p);
+            orient > 0.0 || (orient == 0.0 && self.in_conflict(self.neighbors[t][i], p))
+        } else {
+            let [a, b, c, d] = tetra.map(|v| &self.points[v]);
+            predicates::in_sphere(a, b, c, d, p) > 0.0
+        }
+    }
+
+    fn next_rand(&mut self) -> usize {
+        self.rand_state ^= self.rand_state << 13;
+        self.rand_state ^= self.rand_state >> 7;
+        self.rand_state ^= self.rand_state << 17;
+        self.rand_state as usize
+    }
+
+    /// Finds a tetrahedron in conflict with p by a stochastic visibility walk
+    fn locate(&mut self, p: &Vector3<f64>) -> Option<usize> {
+        let mut t = self.last;
+        let max_steps = 4 * self.tetras.len() + 16;
+        for _ in 0..max_steps {
+            if self.is_ghost(t) {
+                return if self.in_conflict(t, p) {
+                    Some(t)
+                } else {
+                    None
+                };
+            }
+            let start = self.next_rand() % 4;
+            let next = (0..4)
+                .map(|k| (start + k) % 4)
+                .find(|&i| self.orient_with(t, i, p) < 0.0);
+            match next {
+                Some(i) => t = self.neighbors[t][i],
+                None => return Some(t),
+            }
+        }
+        None
+    }
+
+    fn insert(&mut self, ind_point: usize) -> Result<()> {
+        let p = self.points[ind_point];
+        let t_start = match self.locate(&p) {
+            Some(t) if self.in_conflict(t, &p) => t,
+            _ => (0..self.tetras.len())
+                .find(|&t| self.alive[t] && self.in_conflict(t, &p))
+                .ok_or(anyhow::Error::msg(
+                    "tetrahedralize(): No tetrahedron in conflict with inserted point",
+                ))?,
+        };
+
+        // cavity of tetrahedra in conflict, and its boundary faces
+        self.stamp += 1;
+        self.stamps[t_start] = self.stamp;
+        let mut cavity = vec![t_start];
+        let mut boundary = Vec::new();
+        let mut ind_cav = 0;
+        while ind_cav < cavity.len() {
+            let t = cavity[ind_cav];
+            ind_cav += 1;
+            for i in 0..4 {
+                let t_nei = self.neighbors[t][i];
+                if self.stamps[t_nei] == self.stamp {
+                    continue;
+                }
+                if self.in_conflict(t_nei, &p) {
+                    self.stamps[t_nei] = self.stamp;
+                    cavity.push(t_nei);
+                } else {
+                    boundary.push((t, i, t_nei));
+                }
+            }
+        }
+
+        // cavity slots are only reused once the star is built, neighbors still refer to them
+        for &t in cavity.iter() {
+            self.alive[t] = false;
+        }
+
+        // star of p over the cavity boundary
+        let mut open_faces: HashMap<[usize; 3], (usize, usize)> = HashMap::new();
+        for (t, i, t_out) in boundary {
+            let mut tetra = self.tetras[t];
+            tetra[i] = ind_point;
+            let t_new = self.add_tetra(tetra);
+            self.stamps[t_new] = self.stamp;
+            self.neighbors[t_new][i] = t_out;
+            if let Some(j) = (0..4).find(|&j| self.neighbors[t_out][j] == t) {
+                self.neighbors[t_out][j] = t_new;
+            }
+            for j in (0..4).filter(|&j| j != i) {
+                let mut face = [0; 3];
+                let mut k = 0;
+                for (m, &v) in tetra.iter().enumerate() {
+                    if m != j {
+                        face[k] = v;
+                        k += 1;
+                    }
+                }
+                face.sort();
+                if let Some((t_other, j_other)) = open_faces.remove(&face) {
+                    self.neighbors[t_new][j] = t_other;
+                    self.neighbors[t_other][j_other] = t_new;
+                } else {
+                    open_faces.insert(face, (t_new, j));
+                }
+            }
+            if !self.is_ghost(t_new) {
+                self.last = t_new;
+            }
+        }
+        self.free.extend(cavity);
+        if !open_faces.is_empty() {
+            return Err(anyhow::Error::msg(
+                "tetrahedralize(): Cavity boundary is not closed",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Interleaves the bits of 3 coordinates quantized on 21 bits
+fn morton_code(point: &Vector3<f64>, min: &Vector3<f64>, size: f64) -> u64 {
+    let mut code = 0;
+    for dim in 0..3 {
+        let quant = (((point[dim] - min[dim]) / size) * ((1 << 21) - 1) as f64) as u64;
+        for bit in 0..21 {
+            code |= ((quant >> bit) & 1) << (3 * bit + dim);
+        }
+    }
+    code
+}
+
+impl Tetrahedralizer for BowyerWatsonTetrahedralizer {
+    fn tetrahedralize(&self, points: &[Vector3<f64>]) -> Result<Vec<[usize; 4]>> {
+        if points.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // spatial ordering keeps consecutive insertions close, duplicated points are skipped
+        let mut min = points[0];
+        let mut max = points[0];
+        for point in points.iter() {
+            min = min.inf(point);
+            max = max.sup(point);
+        }
+        let size = (max - min).max().max(f64::MIN_POSITIVE);
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by_key(|&ind| (morton_code(&points[ind], &min, size), ind));
+        let mut seen = HashMap::new();
+        order.retain(|&ind| seen.insert(point_key(&points[ind]), ind).is_none());
+        if order.len() != points.len() {
+            warn!(
+                "tetrahedralize(): {} duplicated points skipped",
+                points.len() - order.len()
+            );
+        }
+
+        // first non degenerate tetrahedron
+        let mut first = [order[0]; 4];
+        let ind_b = order
+            .iter()
+            .position(|&ind| points[ind] != points[first[0]]);
+        let ind_c = ind_b.and_then(|ind_b| {
+            first[1] = order[ind_b];
+            order.iter().position(|&ind| {
+                (points[first[1]] - points[first[0]])
+                    .cross(&(points[ind] - points[first[0]]))
+                    .norm_squared()
+                    != 0.0
+            })
+        });
+        let ind_d = ind_c.and_then(|ind_c| {
+            first[2] = order[ind_c];
+            order.iter().position(|&ind| {
+                predicates::orient_3d(
+                    &points[first[0]],
+                    &points[first[1]],
+                    &points[first[2]],
+                    &points[ind],
+                ) != 0.0
+            })
+        });
+        let ind_d = ind_d.ok_or(anyhow::Error::msg("tetrahedralize(): Points are coplanar"))?;
+        first[3] = order[ind_d];
+        if predicates::orient_3d(
+            &points[first[0]],
+            &points[first[1]],
+            &points[first[2]],
+            &points[first[3]],
+        ) < 0.0
+        {
+            first.swap(0, 1);
+        }
+
+        let mut bw = BowyerWatson::new(points, first);
+        for &ind in order.iter().filter(|ind| !first.contains(ind)) {
+            bw.insert(ind)?;
+        }
+
+        Ok((0..bw.tetras.len())
+            .filter(|&t| bw.alive[t] && !bw.is_ghost(t))
+            .map(|t| bw.tetras[t])
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Checks that tetrahedra are positively oriented, have empty circumspheres, use every point,
+    /// and fill the given convex hull volume
+    fn check_delaunay(points: &[Vector3<f64>], tetras: &[[usize; 4]], hull_volume: f64) {
+        let mut used = vec![false; points.len()];
+        let mut volume = 0.0;
+        for tetra in tetras.iter() {
+            let [a, b, c, d] = tetra.map(|ind| &points[ind]);
+            let orient = predicates::orient_3d(a, b, c, d);
+            assert!(orient > 0.0);
+            volume += orient / 6.0;
+            for (ind, point) in points.iter().enumerate() {
+                if !tetra.contains(&ind) {
+                    assert!(predicates::in_sphere(a, b, c, d, point) <= 0.0);
+                }
+            }
+            for &ind in tetra.iter() {
+                used[ind] = true;
+            }
+        }
+        assert!(used.iter().all(|&u| u));
+        assert!((volume - hull_volume).abs() <= 1e-12 * hull_volume);
+    }
+
+    #[test]
+    fn random_points() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // cube corners, so that the convex hull is the unit cube
+        let mut points: Vec<Vector3<f64>> = (0..8)
+            .map(|i| Vector3::new((i & 1) as f64, ((i >> 1) & 1) as f64, (i >> 2) as f64))
+            .collect();
+        for _ in 0..300 {
+            points.push(Vector3::new(rng.gen(), rng.gen(), rng.gen()));
+        }
+        let tetras = BowyerWatsonTetrahedralizer.tetrahedralize(&points).unwrap();
+        check_delaunay(&points, &tetras, 1.0);
+    }
+
+    #[test]
+    fn grid_points() {
+        let mut points = Vec::new();
+        for i in 0..5 {
+            for j in 0..5 {
+                for k in 0..5 {
+                    points.push(Vector3::new(i as f64, j as f64, k as f64));
+                }
+            }
+        }
+        let tetras = BowyerWatsonTetrahedralizer.tetrahedralize(&points).unwrap();
+        check_delaunay(&points, &tetras, 64.0);
+    }
+
+    #[test]
+    fn cospherical_points() {
+        // cuboctahedron, whose 12 vertices lie on a sphere and whose square faces are cocircular
+        let mut points = Vec::new();
+        for (i, j) in [(0, 1), (1, 2), (2, 0)] {
+            for (s1, s2) in [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)] {
+                let mut point = Vector3::new(0.5, 0.25, 0.125);
+                point[i] += s1;
+                point[j] += s2;
+                points.push(point);
+            }
+        }
+        let tetras = BowyerWatsonTetrahedralizer.tetrahedralize(&points).unwrap();
+        check_delaunay(&points, &tetras, 20.0 / 3.0);
+    }
+
+    #[test]
+    fn precomputed_duplicated_points() {
+        let mut points: Vec<Vector3<f64>> =
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]
+                .map(Vector3::from)
+                .to_vec();
+        let precomputed = PrecomputedTetrahedralizer::new(vec![[0, 1, 2, 3]]);
+        points.push(points[1]);
+        assert!(precomputed.tetrahedralize(&points).is_ok());
+        points.push(Vector3::new(1.0, 1.0, 1.0));
+        assert!(precomputed.tetrahedralize(&points).is_err());
+    }
+
+    #[cfg(feature = "tetgen")]
+    #[test]
+    fn same_as_tetgen() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let points: Vec<Vector3<f64>> = (0..300)
+            .map(|_| Vector3::new(rng.gen(), rng.gen(), rng.gen()))
+            .collect();
+        let sorted = |mut tetras: Vec<[usize; 4]>| {
+            for tetra in tetras.iter_mut() {
+                tetra.sort();
+            }
+            tetras.sort();
+            tetras
+        };
+        let tetras = sorted(BowyerWatsonTetrahedralizer.tetrahedralize(&points).unwrap());
+        let tetras_tetgen = sorted(TetgenTetrahedralizer.tetrahedralize(&points).unwrap());
+        assert_eq!(tetras, tetras_tetgen);
+    }
+}
@@ -3,6 +3,7 @@ use clap::Parser;
 use nalgebra::base::*;
 use std::time::Instant;
 
+use compact_skel_3d::algorithm::skeleton_alg::ComponentSelection;
 use compact_skel_3d::algorithm::{delaunay_alg, skeleton_alg};
 use compact_skel_3d::mesh3d::{self, ManifoldMesh3D};
 use compact_skel_3d::skeleton3d;
@@ -31,23 +32,43 @@ fn generate_test_mesh() -> Result<ManifoldMesh3D> {
 struct Cli {
     #[arg(long = "objinfile")]
     obj_in_path: Option<std::path::PathBuf>,
+    #[arg(default_value_t = 20.0, long = "angmax")]
+    ang_max_deg: f32,
+    #[arg(long = "featurefile")]
+    feature_path: Option<std::path::PathBuf>,
     #[arg(default_value = "./ressources/mesh.obj", long = "objoutfile")]
     obj_out_path: std::path::PathBuf,
     #[arg(default_value = "./ressources/skeleton.obj", long = "skeloutfile")]
     skel_out_path: std::path::PathBuf,
+    #[arg(long = "seed")]
+    seed: Option<u64>,
+    #[arg(long = "allcomponents")]
+    all_components: bool,
+    #[arg(long = "mincompfaces")]
+    min_component_faces: Option<usize>,
 }
 
 fn main() -> Result<()> {
     let args = Cli::parse();
+    // library progress is reported through the log facade (RUST_LOG=debug for details)
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
     let obj_out_path_str = args.obj_out_path.to_str().unwrap_or("");
     let skel_out_path_str = args.skel_out_path.to_str().unwrap_or("");
 
-    let mut mesh = if let Some(obj_in_path) = args.obj_in_path {
+    let (mut mesh, obj_feature_edges) = if let Some(obj_in_path) = &args.obj_in_path {
         let obj_in_path_str = obj_in_path.to_str().unwrap_or("");
-        mesh3d::io::load_obj_manifold(obj_in_path_str)?
+        (
+            mesh3d::io::load_obj_manifold(obj_in_path_str, None)?,
+            mesh3d::io::load_obj_feature_edges(obj_in_path_str)?,
+        )
     } else {
-        generate_test_mesh()?
+        (generate_test_mesh()?, Vec::new())
+    };
+    let feature_edges = if let Some(feature_path) = &args.feature_path {
+        mesh3d::io::load_feature_edges(feature_path.to_str().unwrap_or(""))?
+    } else {
+        obj_feature_edges
     };
 
     println!("Checking mesh");
@@ -56,7 +77,14 @@ fn main() -> Result<()> {
 
     println!("Mesh to delaunay");
     let now = Instant::now();
-    delaunay_alg::to_delaunay(&mut mesh, Some(std::f32::consts::PI * 20.0 / 180.0))?;
+    delaunay_alg::to_delaunay(
+        &mut mesh,
+        Some(args.ang_max_deg.to_radians()),
+        Some(&feature_edges),
+        None,
+        None,
+        None,
+    )?;
     let duration = now.elapsed();
     let sec = duration.as_secs();
     let min = sec / 60;
@@ -64,9 +92,18 @@ fn main() -> Result<()> {
     println!("Delaunay computed in {}m{}s", min, sec);
     println!("");
 
+    let components = if let Some(min_faces) = args.min_component_faces {
+        ComponentSelection::MinFaces(min_faces)
+    } else if args.all_components {
+        ComponentSelection::All
+    } else {
+        ComponentSelection::Single
+    };
+
     let now = Instant::now();
     println!("Full skeletonization");
-    let skeleton = skeleton_alg::full_skeletonization(&mut mesh)?;
+    let (skeleton, _status) =
+        skeleton_alg::full_skeletonization(&mut mesh, args.seed, components, None)?;
     let duration = now.elapsed();
     let sec = duration.as_secs();
     let min = sec / 60;
@@ -4,6 +4,7 @@ use nalgebra::base::*;
 use std::fs;
 use std::time::Instant;
 
+use compact_skel_3d::algorithm::skeleton_alg::ComponentSelection;
 use compact_skel_3d::algorithm::{delaunay_alg, skeleton_alg};
 use compact_skel_3d::mesh3d::{self, ManifoldMesh3D};
 use compact_skel_3d::skeleton3d;
@@ -32,6 +33,12 @@ fn generate_test_mesh() -> Result<ManifoldMesh3D> {
 struct Cli {
     #[arg(long = "objinfile")]
     obj_in_path: Option<std::path::PathBuf>,
+    #[arg(default_value_t = 20.0, long = "angmax")]
+    ang_max_deg: f32,
+    #[arg(long = "featurefile")]
+    feature_path: Option<std::path::PathBuf>,
+    #[arg(long = "tetinfile", conflicts_with_all = ["ang_max_deg", "feature_path"])]
+    tet_in_path: Option<std::path::PathBuf>,
     #[arg(long = "epsilon")]
     epsilon: Option<f32>,
     #[arg(default_value = "./output/", long = "pathout")]
@@ -40,16 +47,34 @@ struct Cli {
     mesh_out_name: std::path::PathBuf,
     #[arg(default_value = "skeleton.ply", long = "skeloutfile")]
     skel_out_name: std::path::PathBuf,
+    #[arg(long = "binary")]
+    binary: bool,
+    #[arg(long = "seed")]
+    seed: Option<u64>,
+    #[arg(long = "allcomponents")]
+    all_components: bool,
+    #[arg(long = "mincompfaces")]
+    min_component_faces: Option<usize>,
 }
 
 fn main() -> Result<()> {
     let args = Cli::parse();
+    // library progress is reported through the log facade (RUST_LOG=debug for details)
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
-    let mut mesh = if let Some(obj_in_path) = args.obj_in_path {
+    let (mut mesh, obj_feature_edges) = if let Some(obj_in_path) = &args.obj_in_path {
         let obj_in_path_str = obj_in_path.to_str().unwrap_or("");
-        mesh3d::io::load_obj_manifold(obj_in_path_str)?
+        (
+            mesh3d::io::load_obj_manifold(obj_in_path_str, None)?,
+            mesh3d::io::load_obj_feature_edges(obj_in_path_str)?,
+        )
     } else {
-        generate_test_mesh()?
+        (generate_test_mesh()?, Vec::new())
+    };
+    let feature_edges = if let Some(feature_path) = &args.feature_path {
+        mesh3d::io::load_feature_edges(feature_path.to_str().unwrap_or(""))?
+    } else {
+        obj_feature_edges
     };
 
     let epsilon = args.epsilon;
@@ -61,51 +86,37 @@ fn main() -> Result<()> {
     mesh.check_mesh()?;
     println!("");
 
-    println!("Mesh to delaunay");
-    let now = Instant::now();
-    delaunay_alg::to_delaunay(&mut mesh, Some(std::f32::consts::PI * 20.0 / 180.0))?;
-    let duration = now.elapsed();
-    let sec = duration.as_secs();
-    let min = sec / 60;
-    let sec = sec - min * 60;
-    println!("Delaunay computed in {}m{}s", min, sec);
-    println!("");
+    // a saved tetrahedralization comes from an already Delaunay mesh, no conversion needed
+    let opt_tetrahedra = if let Some(tet_in_path) = &args.tet_in_path {
+        println!("Loading tetrahedralization");
+        Some(mesh3d::io::load_tetgen(
+            tet_in_path.to_str().unwrap_or(""),
+            &mesh,
+        )?)
+    } else {
+        println!("Mesh to delaunay");
+        let now = Instant::now();
+        delaunay_alg::to_delaunay(
+            &mut mesh,
+            Some(args.ang_max_deg.to_radians()),
+            Some(&feature_edges),
+            None,
+            None,
+            None,
+        )?;
+        let duration = now.elapsed();
+        let sec = duration.as_secs();
+        let min = sec / 60;
+        let sec = sec - min * 60;
+        println!("Delaunay computed in {}m{}s", min, sec);
+        println!("");
+        None
+    };
 
     let epsilon = if let Some(val) = epsilon {
-        let (bb_min, bb_max) = mesh
-            .vertices()
-            .iter()
-            .fold(
-                None,
-                |bb_val: Option<(Vector3<f32>, Vector3<f32>)>, (_, &vert)| {
-                    if let Some((mut bb_min, mut bb_max)) = bb_val {
-                        if vert[0] < bb_min[0] {
-                            bb_min[0] = vert[0];
-                        }
-                        if vert[1] < bb_min[1] {
-                            bb_min[1] = vert[1];
-                        }
-                        if vert[2] < bb_min[2] {
-                            bb_min[2] = vert[2];
-                        }
-                        if vert[0] > bb_max[0] {
-                            bb_max[0] = vert[0];
-                        }
-                        if vert[1] > bb_max[1] {
-                            bb_max[1] = vert[1];
-                        }
-                        if vert[2] > bb_max[2] {
-                            bb_max[2] = vert[2];
-                        }
-                        Some((bb_min, bb_max))
-                    } else {
-                        Some((vert, vert))
-                    }
-                },
-            )
-            .ok_or(anyhow::Error::msg("No point in mesh"))?;
-
-        let length = (bb_min - bb_max).norm();
+        let (bb_min, bb_max) =
+            mesh3d::stats::bounding_box(&mesh).ok_or(anyhow::Error::msg("No point in mesh"))?;
+        let length = (bb_max - bb_min).norm();
 
         println!("Epsilon: {}% of diagonal = {}", val * 100.0, val * length);
         Some(val * length)
@@ -113,10 +124,24 @@ fn main() -> Result<()> {
         None
     };
 
+    let components = if let Some(min_faces) = args.min_component_faces {
+        ComponentSelection::MinFaces(min_faces)
+    } else if args.all_components {
+        ComponentSelection::All
+    } else {
+        ComponentSelection::Single
+    };
+
     let now = Instant::now();
     println!("Sheet skeletonization");
-    let (skeleton, _work_mesh, vec_debug_meshes) =
-        skeleton_alg::sheet_skeletonization(&mut mesh, epsilon)?;
+    let (skeleton, _work_mesh, vec_debug_meshes, _status) = skeleton_alg::sheet_skeletonization(
+        &mut mesh,
+        epsilon,
+        args.seed,
+        components,
+        None,
+        opt_tetrahedra,
+    )?;
     let duration = now.elapsed();
     let sec = duration.as_secs();
     let min = sec / 60;
@@ -132,16 +157,16 @@ fn main() -> Result<()> {
             &vec_debug_meshes[i],
         )?;
     }
-    let vec_col = skeleton3d::io::save_ply(
-        &format!("{}{}", out_path_str, skel_out_name_str),
-        &skeleton,
-        None,
-    )?;
-    mesh3d::io::save_ply_manifold(
-        &format!("{}{}", out_path_str, mesh_out_name_str),
-        &mesh,
-        Some(vec_col),
-    )?;
+    let skel_out_file = format!("{}{}", out_path_str, skel_out_name_str);
+    let mesh_out_file = format!("{}{}", out_path_str, mesh_out_name_str);
+    let colors = Some(skeleton3d::io::label_colors(&skeleton, args.seed));
+    if args.binary {
+        let vec_col = skeleton3d::io::save_ply_binary(&skel_out_file, &skeleton, colors)?;
+        mesh3d::io::save_ply_manifold_binary(&mesh_out_file, &mesh, Some(vec_col))?;
+    } else {
+        let vec_col = skeleton3d::io::save_ply(&skel_out_file, &skeleton, colors)?;
+        mesh3d::io::save_ply_manifold(&mesh_out_file, &mesh, Some(vec_col))?;
+    }
 
     Ok(())
 }
@@ -1,9 +1,8 @@
 use anyhow::Result;
 use clap::Parser;
-use std::time::Instant;
 
-use compact_skel_3d::algorithm::delaunay_alg;
-use compact_skel_3d::mesh3d::io;
+use compact_skel_3d::algorithm::delaunay_alg::{self, DelaunayLimitError, DelaunayLimits};
+use compact_skel_3d::mesh3d::{decimation, io, repair};
 
 #[derive(Parser)]
 struct Cli {
@@ -11,10 +10,40 @@ struct Cli {
     obj_in_path: std::path::PathBuf,
     #[arg(default_value = "./ressources/hand_del.obj", long = "objoutfile")]
     obj_out_path: std::path::PathBuf,
+    #[arg(default_value_t = 20.0, long = "angmax")]
+    ang_max_deg: f32,
+    #[arg(long = "featurefile")]
+    feature_path: Option<std::path::PathBuf>,
+    #[arg(long = "maxedgelength")]
+    max_edge_length: Option<f32>,
+    #[arg(long = "minangle")]
+    min_angle_deg: Option<f32>,
+    #[arg(long = "maxinserted")]
+    max_inserted_vertices: Option<usize>,
+    #[arg(long = "maxiterations")]
+    max_iterations: Option<usize>,
+    #[arg(long = "timebudget")]
+    time_budget_sec: Option<f64>,
+    #[arg(long = "reportfile")]
+    report_path: Option<std::path::PathBuf>,
+    #[arg(long = "tetoutfile")]
+    tet_out_path: Option<std::path::PathBuf>,
+    #[arg(long = "repair")]
+    repair: bool,
+    #[arg(long = "weldtolerance")]
+    weld_tolerance: Option<f32>,
+    #[arg(long = "maxholeedges")]
+    max_hole_edges: Option<usize>,
+    #[arg(long = "targetfaces")]
+    target_faces: Option<usize>,
+    #[arg(long = "maxerror")]
+    max_error: Option<f32>,
 }
 
 fn main() -> Result<()> {
     let args = Cli::parse();
+    // library progress is reported through the log facade (RUST_LOG=debug for details)
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
     let obj_in_path_str = args.obj_in_path.to_str().unwrap_or("");
     let obj_out_path_str = args.obj_out_path.to_str().unwrap_or("");
@@ -23,22 +52,92 @@ fn main() -> Result<()> {
     let extension = &obj_in_path_str[obj_in_path_str.len() - 3..];
     println!("{}", extension);
 
-    let mut mesh = if extension == "obj" {
-        io::load_obj_manifold(obj_in_path_str)?
+    let mut mesh = if args.repair {
+        let soup = if extension == "obj" {
+            io::load_obj_generic(obj_in_path_str, None)?
+        } else if extension == "off" {
+            io::load_off_generic(obj_in_path_str, None)?
+        } else if extension == "stl" {
+            io::load_stl_generic(obj_in_path_str, None)?
+        } else {
+            return Err(anyhow::Error::msg("Extension not handled"));
+        };
+        println!("Repairing mesh");
+        let (mesh, report) = repair::repair(&soup, args.weld_tolerance, args.max_hole_edges)?;
+        println!("{:#?}", report);
+        if !report.closed {
+            println!("Warning: repaired mesh is not closed");
+        }
+        mesh
+    } else if extension == "obj" {
+        io::load_obj_manifold(obj_in_path_str, None)?
     } else if extension == "off" {
-        io::load_off_manifold(obj_in_path_str)?
+        io::load_off_manifold(obj_in_path_str, None)?
+    } else if extension == "stl" {
+        io::load_stl_manifold(obj_in_path_str, None)?
     } else {
         return Err(anyhow::Error::msg("Extension not handled"));
     };
 
+    let mut feature_edges = if let Some(feature_path) = &args.feature_path {
+        io::load_feature_edges(feature_path.to_str().unwrap_or(""))?
+    } else if extension == "obj" {
+        io::load_obj_feature_edges(obj_in_path_str)?
+    } else {
+        Vec::new()
+    };
+
     println!("Checking mesh");
     mesh.check_mesh()?;
 
+    if args.target_faces.is_some() || args.max_error.is_some() {
+        println!("Decimating mesh");
+        let (decimated, vertex_map) = decimation::decimate(
+            &mesh,
+            args.target_faces,
+            args.max_error,
+            Some(&feature_edges),
+        )?;
+        mesh = decimated;
+        // feature edge vertices are kept by decimation
+        feature_edges = feature_edges
+            .iter()
+            .filter_map(|&[ind_v1, ind_v2]| {
+                let ind_new1 = vertex_map.get(ind_v1).copied().flatten()?;
+                let ind_new2 = vertex_map.get(ind_v2).copied().flatten()?;
+                Some([ind_new1, ind_new2])
+            })
+            .collect();
+        println!("{} faces after decimation", mesh.get_nb_faces());
+    }
+
     println!("Mesh to delaunay");
-    let now = Instant::now();
-    delaunay_alg::to_delaunay(&mut mesh, Some(std::f32::consts::PI * 20.0 / 180.0))?;
-    let duration = now.elapsed();
-    let sec = duration.as_secs();
+    let limits = DelaunayLimits {
+        max_inserted_vertices: args.max_inserted_vertices,
+        max_iterations: args.max_iterations,
+        time_budget: args.time_budget_sec.map(std::time::Duration::from_secs_f64),
+    };
+    let report = match delaunay_alg::to_delaunay(
+        &mut mesh,
+        Some(args.ang_max_deg.to_radians()),
+        Some(&feature_edges),
+        args.max_edge_length,
+        args.min_angle_deg.map(|ang| ang.to_radians()),
+        Some(limits),
+    ) {
+        Ok(report) => report,
+        Err(err) => {
+            if let Some(limit_err) = err.downcast_ref::<DelaunayLimitError>() {
+                println!("Save partial mesh");
+                io::save_obj_manifold(obj_out_path_str, &mesh, None)?;
+                if let Some(report_path) = args.report_path {
+                    std::fs::write(report_path, limit_err.report.to_json())?;
+                }
+            }
+            return Err(err);
+        }
+    };
+    let sec = report.elapsed.as_secs();
     let min = sec / 60;
     let sec = sec - min * 60;
     println!("Delaunay mesh computed in {}m{}s", min, sec);
@@ -49,5 +148,19 @@ fn main() -> Result<()> {
     println!("Save mesh");
     io::save_obj_manifold(obj_out_path_str, &mesh, None)?;
 
+    if let Some(report_path) = args.report_path {
+        println!("Save report");
+        std::fs::write(report_path, report.to_json())?;
+    }
+
+    if let Some(tet_out_path) = args.tet_out_path {
+        println!("Save tetrahedralization");
+        io::save_tetgen(
+            tet_out_path.to_str().unwrap_or(""),
+            &mesh,
+            &report.tetrahedra,
+        )?;
+    }
+
     Ok(())
 }
@@ -1,8 +1,12 @@
 use nalgebra::base::*;
+use nalgebra::ComplexField;
+
+use super::predicates;
+use super::Real;
 
 /// Checks if the 4 given points are planar
-pub fn is_flat(pts: [Vector3<f32>; 4], eps: Option<f32>) -> bool {
-    let eps_val = eps.unwrap_or(0.00001);
+pub fn is_flat<T: Real>(pts: [Vector3<T>; 4], eps: Option<T>) -> bool {
+    let eps_val = eps.unwrap_or(nalgebra::convert(0.00001));
     let vec_3_0n = (pts[0] - pts[3]).normalize();
     let vec_3_1n = (pts[1] - pts[3]).normalize();
     let vec_3_2n = (pts[2] - pts[3]).normalize();
@@ -14,13 +18,13 @@ pub fn is_flat(pts: [Vector3<f32>; 4], eps: Option<f32>) -> bool {
         vec_3_2n[0], vec_3_2n[1], vec_3_2n[2], 
     );
 
-    let det = mat_eval.determinant().abs();
+    let det = ComplexField::abs(mat_eval.determinant());
 
     det < eps_val
 }
 
 /// Computes sphere center associated to the 4 given points
-pub fn sphere_center(pts: [Vector3<f32>; 4]) -> Option<Vector3<f32>> {
+pub fn sphere_center<T: Real>(pts: [Vector3<T>; 4]) -> Option<Vector3<T>> {
     let vec_0_1 = pts[1] - pts[0];
     let vec_1_2 = pts[2] - pts[1];
     let vec_2_0 = pts[0] - pts[2];
@@ -43,13 +47,14 @@ pub fn sphere_center(pts: [Vector3<f32>; 4]) -> Option<Vector3<f32>> {
     let sqn2 = pts[2].norm_squared();
     let sqn3 = pts[3].norm_squared();
 
+    let half: T = nalgebra::convert(0.5);
     let vec_slv = Matrix6x1::new(
-        0.5 * (sqn1 - sqn0),
-        0.5 * (sqn2 - sqn1),
-        0.5 * (sqn0 - sqn2),
-        0.5 * (sqn0 - sqn3),
-        0.5 * (sqn1 - sqn3),
-        0.5 * (sqn2 - sqn3),
+        half * (sqn1 - sqn0),
+        half * (sqn2 - sqn1),
+        half * (sqn0 - sqn2),
+        half * (sqn0 - sqn3),
+        half * (sqn1 - sqn3),
+        half * (sqn2 - sqn3),
     );
     let mat_slv_mod = mat_slv.transpose() * mat_slv;
     let vec_slv_mod = mat_slv.transpose() * vec_slv;
@@ -58,7 +63,7 @@ pub fn sphere_center(pts: [Vector3<f32>; 4]) -> Option<Vector3<f32>> {
 }
 
 /// Computes circle center associated to the 3 given points
-pub fn circle_center(pts: [Vector3<f32>; 3]) -> Option<Vector3<f32>> {
+pub fn circle_center<T: Real>(pts: [Vector3<T>; 3]) -> Option<Vector3<T>> {
     let vec_0_1 = pts[1] - pts[0];
     let vec_1_2 = pts[2] - pts[1];
     let vec_2_0 = pts[0] - pts[2];
@@ -72,11 +77,12 @@ pub fn circle_center(pts: [Vector3<f32>; 3]) -> Option<Vector3<f32>> {
         vec_c[0], vec_c[1], vec_c[2], 
     );
 
+    let half: T = nalgebra::convert(0.5);
     #[rustfmt::skip]
     let vec_slv = Matrix4x1::new(
-        0.5 * vec_0_1.norm_squared() + vec_0_1.dot(&pts[0]),
-        0.5 * vec_1_2.norm_squared() + vec_1_2.dot(&pts[1]),
-        0.5 * vec_2_0.norm_squared() + vec_2_0.dot(&pts[2]),
+        half * vec_0_1.norm_squared() + vec_0_1.dot(&pts[0]),
+        half * vec_1_2.norm_squared() + vec_1_2.dot(&pts[1]),
+        half * vec_2_0.norm_squared() + vec_2_0.dot(&pts[2]),
         vec_c.dot(&pts[0]),
     );
 
@@ -86,20 +92,150 @@ pub fn circle_center(pts: [Vector3<f32>; 3]) -> Option<Vector3<f32>> {
     mat_slv_mod.lu().solve(&vec_slv_mod)
 }
 
+/// Computes sphere center associated to the 4 given points, in f64 (Cramer's rule)
+///
+/// Returns None if the points are exactly planar
+pub fn sphere_center_f64(pts: [Vector3<f64>; 4]) -> Option<Vector3<f64>> {
+    if predicates::orient_3d(&pts[0], &pts[1], &pts[2], &pts[3]) == 0.0 {
+        return None;
+    }
+    let vec_0_1 = pts[1] - pts[0];
+    let vec_0_2 = pts[2] - pts[0];
+    let vec_0_3 = pts[3] - pts[0];
+
+    let denom = 2.0 * vec_0_1.dot(&vec_0_2.cross(&vec_0_3));
+    let num = vec_0_1.norm_squared() * vec_0_2.cross(&vec_0_3)
+        + vec_0_2.norm_squared() * vec_0_3.cross(&vec_0_1)
+        + vec_0_3.norm_squared() * vec_0_1.cross(&vec_0_2);
+
+    Some(pts[0] + num / denom)
+}
+
+/// Computes circle center associated to the 3 given points, in f64
+///
+/// Returns None if the points are aligned
+pub fn circle_center_f64(pts: [Vector3<f64>; 3]) -> Option<Vector3<f64>> {
+    let vec_0_1 = pts[1] - pts[0];
+    let vec_0_2 = pts[2] - pts[0];
+    let vec_n = vec_0_1.cross(&vec_0_2);
+
+    let denom = 2.0 * vec_n.norm_squared();
+    if denom == 0.0 {
+        return None;
+    }
+    let num = vec_0_1.norm_squared() * vec_0_2.cross(&vec_n)
+        + vec_0_2.norm_squared() * vec_n.cross(&vec_0_1);
+
+    Some(pts[0] + num / denom)
+}
+
+/// Computes circle center of the largest triangle among the 4 given points, in f64
+fn largest_circle_center_f64(pts: [Vector3<f64>; 4]) -> Option<Vector3<f64>> {
+    let triangles = [[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]];
+    let area = |tri: &[usize; 3]| {
+        (pts[tri[1]] - pts[tri[0]])
+            .cross(&(pts[tri[2]] - pts[tri[0]]))
+            .norm_squared()
+    };
+    let tri = triangles
+        .iter()
+        .max_by(|tri1, tri2| area(tri1).total_cmp(&area(tri2)))?;
+    circle_center_f64([pts[tri[0]], pts[tri[1]], pts[tri[2]]])
+}
+
 /// Computes sphere center and radius associated to the 4 given points
 ///
-/// If the 4 points are planar, a circle center is computed instead
-pub fn center_and_radius(pts: [Vector3<f32>; 4], eps: Option<f32>) -> Option<(Vector3<f32>, f32)> {
-    let center = if is_flat(pts, eps) {
-        circle_center([pts[0], pts[1], pts[2]])
+/// Flatness is decided with an exact orientation predicate, or also with is_flat if a tolerance
+/// is given. If the 4 points are planar, a circle center is computed instead, from the 3 points
+/// spanning the largest triangle
+pub fn center_and_radius<T: Real>(pts: [Vector3<T>; 4], eps: Option<T>) -> Option<(Vector3<T>, T)> {
+    let flat = eps.is_some_and(|eps| is_flat(pts, Some(eps)));
+    let pts = pts.map(|pt| pt.map(|x| x.to_f64()));
+    let center = if flat {
+        largest_circle_center_f64(pts)
     } else {
-        sphere_center(pts)
+        sphere_center_f64(pts).or_else(|| largest_circle_center_f64(pts))
+    }?;
+    let radius = (center - pts[0]).norm();
+    Some((center.map(nalgebra::convert), nalgebra::convert(radius)))
+}
+
+/// Polygon triangulation method
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PolygonTriangulation {
+    /// Fan around first vertex (convex polygons)
+    Fan,
+    /// Ear clipping (non convex planar polygons)
+    EarClipping,
+}
+
+fn fan_triangulation(nb_pts: usize) -> Vec<[usize; 3]> {
+    (1..(nb_pts - 1)).map(|i| [0, i, i + 1]).collect()
+}
+
+fn ear_clipping_triangulation<T: Real>(pts: &[Vector3<T>]) -> Option<Vec<[usize; 3]>> {
+    // polygon normal (Newell's method)
+    let mut normal = Vector3::zeros();
+    for i in 0..pts.len() {
+        let cur = pts[i];
+        let next = pts[(i + 1) % pts.len()];
+        normal[0] += (cur[1] - next[1]) * (cur[2] + next[2]);
+        normal[1] += (cur[2] - next[2]) * (cur[0] + next[0]);
+        normal[2] += (cur[0] - next[0]) * (cur[1] + next[1]);
+    }
+    if normal.norm() == T::zero() {
+        return None;
+    }
+
+    let is_convex = |a: usize, b: usize, c: usize| {
+        (pts[b] - pts[a]).cross(&(pts[c] - pts[b])).dot(&normal) > T::zero()
     };
-    match center {
-        Some(center) => {
-            let radius = (center - pts[0]).norm();
-            Some((center, radius))
+    let is_inside = |p: usize, a: usize, b: usize, c: usize| {
+        (pts[b] - pts[a]).cross(&(pts[p] - pts[a])).dot(&normal) >= T::zero()
+            && (pts[c] - pts[b]).cross(&(pts[p] - pts[b])).dot(&normal) >= T::zero()
+            && (pts[a] - pts[c]).cross(&(pts[p] - pts[c])).dot(&normal) >= T::zero()
+    };
+
+    let mut remaining: Vec<usize> = (0..pts.len()).collect();
+    let mut triangles = Vec::new();
+    while remaining.len() > 3 {
+        let nb_rem = remaining.len();
+        let opt_ear = (0..nb_rem).find(|&k| {
+            let a = remaining[(k + nb_rem - 1) % nb_rem];
+            let b = remaining[k];
+            let c = remaining[(k + 1) % nb_rem];
+            is_convex(a, b, c)
+                && !remaining
+                    .iter()
+                    .any(|&p| p != a && p != b && p != c && is_inside(p, a, b, c))
+        })?;
+        triangles.push([
+            remaining[(opt_ear + nb_rem - 1) % nb_rem],
+            remaining[opt_ear],
+            remaining[(opt_ear + 1) % nb_rem],
+        ]);
+        remaining.remove(opt_ear);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+
+    Some(triangles)
+}
+
+/// Triangulates a polygon given by its ordered vertices
+///
+/// Returns triangles as indices in the vertex list, with the polygon orientation.
+/// Ear clipping falls back to a fan if the polygon is degenerate.
+pub fn triangulate_polygon<T: Real>(
+    pts: &[Vector3<T>],
+    method: PolygonTriangulation,
+) -> Vec<[usize; 3]> {
+    if pts.len() < 3 {
+        return Vec::new();
+    }
+    match method {
+        PolygonTriangulation::Fan => fan_triangulation(pts.len()),
+        PolygonTriangulation::EarClipping => {
+            ear_clipping_triangulation(pts).unwrap_or(fan_triangulation(pts.len()))
         }
-        None => None,
     }
 }
@@ -1,2 +1,27 @@
+use nalgebra::RealField;
+use std::str::FromStr;
+
 /// Geometric operations
 pub mod geometry_operations;
+/// Robust geometric predicates
+pub mod predicates;
+
+/// Scalar type of coordinates, implemented for f32 and f64
+///
+/// Conversions from f64 go through `nalgebra::convert`
+pub trait Real: RealField + Copy + FromStr {
+    /// Conversion to f64
+    fn to_f64(self) -> f64;
+}
+
+impl Real for f32 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Real for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
@@ -0,0 +1,353 @@
+use nalgebra::base::*;
+
+// Adaptive precision predicates: a floating point evaluation is used when its error bound
+// guarantees the sign, otherwise the determinant is evaluated exactly with floating point
+// expansions (nonoverlapping components sorted by increasing magnitude, J. R. Shewchuk)
+
+const EPSILON: f64 = f64::EPSILON * 0.5;
+const ORIENT_3D_ERR_BOUND: f64 = (7.0 + 56.0 * EPSILON) * EPSILON;
+const IN_SPHERE_ERR_BOUND: f64 = (16.0 + 224.0 * EPSILON) * EPSILON;
+
+type Expansion = Vec<f64>;
+
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virt = x - a;
+    let a_virt = x - b_virt;
+    let err = (a - a_virt) + (b - b_virt);
+    (x, err)
+}
+
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    let err = a.mul_add(b, -x);
+    (x, err)
+}
+
+/// Exact difference a - b as an expansion
+fn diff_expansion(a: f64, b: f64) -> Expansion {
+    let (x, err) = two_sum(a, -b);
+    [err, x].into_iter().filter(|&c| c != 0.0).collect()
+}
+
+fn grow_expansion(e: &[f64], b: f64) -> Expansion {
+    let mut res = Vec::with_capacity(e.len() + 1);
+    let mut q = b;
+    for &comp in e.iter() {
+        let (sum, err) = two_sum(q, comp);
+        if err != 0.0 {
+            res.push(err);
+        }
+        q = sum;
+    }
+    if q != 0.0 {
+        res.push(q);
+    }
+    res
+}
+
+fn expansion_sum(e: &[f64], f: &[f64]) -> Expansion {
+    f.iter()
+        .fold(e.to_vec(), |sum, &comp| grow_expansion(&sum, comp))
+}
+
+fn expansion_diff(e: &[f64], f: &[f64]) -> Expansion {
+    let neg_f: Expansion = f.iter().map(|&comp| -comp).collect();
+    expansion_sum(e, &neg_f)
+}
+
+fn scale_expansion(e: &[f64], b: f64) -> Expansion {
+    let mut res = Vec::with_capacity(2 * e.len());
+    let mut q = 0.0;
+    for &comp in e.iter() {
+        let (prod, prod_err) = two_product(comp, b);
+        let (sum, err) = two_sum(q, prod_err);
+        if err != 0.0 {
+            res.push(err);
+        }
+        let (sum, err) = two_sum(prod, sum);
+        if err != 0.0 {
+            res.push(err);
+        }
+        q = sum;
+    }
+    if q != 0.0 {
+        res.push(q);
+    }
+    res
+}
+
+fn expansion_product(e: &[f64], f: &[f64]) -> Expansion {
+    f.iter().fold(Vec::new(), |prod, &comp| {
+        expansion_sum(&prod, &scale_expansion(e, comp))
+    })
+}
+
+/// Value of an expansion, with the sign of its most significant component
+fn estimate(e: &[f64]) -> f64 {
+    e.iter().sum()
+}
+
+/// Exact 2x2 minor ax * by - bx * ay
+fn minor(ax: &[f64], ay: &[f64], bx: &[f64], by: &[f64]) -> Expansion {
+    expansion_diff(&expansion_product(ax, by), &expansion_product(bx, ay))
+}
+
+/// Orientation of point d relatively to the plane (a, b, c)
+///
+/// Positive if d lies below the plane, where a, b and c appear counterclockwise seen from above,
+/// negative if it lies above, zero if the 4 points are coplanar. The sign is exact
+pub fn orient_3d(a: &Vector3<f64>, b: &Vector3<f64>, c: &Vector3<f64>, d: &Vector3<f64>) -> f64 {
+    let (ad, bd, cd) = (a - d, b - d, c - d);
+
+    let bdx_cdy = bd[0] * cd[1];
+    let cdx_bdy = cd[0] * bd[1];
+    let cdx_ady = cd[0] * ad[1];
+    let adx_cdy = ad[0] * cd[1];
+    let adx_bdy = ad[0] * bd[1];
+    let bdx_ady = bd[0] * ad[1];
+
+    let det =
+        ad[2] * (bdx_cdy - cdx_bdy) + bd[2] * (cdx_ady - adx_cdy) + cd[2] * (adx_bdy - bdx_ady);
+    let permanent = (bdx_cdy.abs() + cdx_bdy.abs()) * ad[2].abs()
+        + (cdx_ady.abs() + adx_cdy.abs()) * bd[2].abs()
+        + (adx_bdy.abs() + bdx_ady.abs()) * cd[2].abs();
+
+    if det.abs() > ORIENT_3D_ERR_BOUND * permanent {
+        return det;
+    }
+
+    let [adx, ady, adz] = [0, 1, 2].map(|i| diff_expansion(a[i], d[i]));
+    let [bdx, bdy, bdz] = [0, 1, 2].map(|i| diff_expansion(b[i], d[i]));
+    let [cdx, cdy, cdz] = [0, 1, 2].map(|i| diff_expansion(c[i], d[i]));
+
+    let bc = minor(&bdx, &bdy, &cdx, &cdy);
+    let ca = minor(&cdx, &cdy, &adx, &ady);
+    let ab = minor(&adx, &ady, &bdx, &bdy);
+
+    let det = expansion_sum(
+        &expansion_sum(&expansion_product(&adz, &bc), &expansion_product(&bdz, &ca)),
+        &expansion_product(&cdz, &ab),
+    );
+    estimate(&det)
+}
+
+/// Position of point e relatively to the sphere passing through a, b, c and d
+///
+/// Positive if e lies inside the sphere, negative if outside, zero if the 5 points are cospherical,
+/// assuming orient_3d(a, b, c, d) is positive (the sign is reversed otherwise). The sign is exact
+pub fn in_sphere(
+    a: &Vector3<f64>,
+    b: &Vector3<f64>,
+    c: &Vector3<f64>,
+    d: &Vector3<f64>,
+    e: &Vector3<f64>,
+) -> f64 {
+    let (ae, be, ce, de) = (a - e, b - e, c - e, d - e);
+
+    let aex_bey = ae[0] * be[1];
+    let bex_aey = be[0] * ae[1];
+    let bex_cey = be[0] * ce[1];
+    let cex_bey = ce[0] * be[1];
+    let cex_dey = ce[0] * de[1];
+    let dex_cey = de[0] * ce[1];
+    let dex_aey = de[0] * ae[1];
+    let aex_dey = ae[0] * de[1];
+    let aex_cey = ae[0] * ce[1];
+    let cex_aey = ce[0] * ae[1];
+    let bex_dey = be[0] * de[1];
+    let dex_bey = de[0] * be[1];
+
+    let ab = aex_bey - bex_aey;
+    let bc = bex_cey - cex_bey;
+    let cd = cex_dey - dex_cey;
+    let da = dex_aey - aex_dey;
+    let ac = aex_cey - cex_aey;
+    let bd = bex_dey - dex_bey;
+
+    let abc = ae[2] * bc - be[2] * ac + ce[2] * ab;
+    let bcd = be[2] * cd - ce[2] * bd + de[2] * bc;
+    let cda = ce[2] * da + de[2] * ac + ae[2] * cd;
+    let dab = de[2] * ab + ae[2] * bd + be[2] * da;
+
+    let (alift, blift, clift, dlift) = (
+        ae.norm_squared(),
+        be.norm_squared(),
+        ce.norm_squared(),
+        de.norm_squared(),
+    );
+
+    let det = (dlift * abc - clift * dab) + (blift * cda - alift * bcd);
+
+    let [aez, bez, cez, dez] = [ae[2].abs(), be[2].abs(), ce[2].abs(), de[2].abs()];
+    let ab_p = aex_bey.abs() + bex_aey.abs();
+    let bc_p = bex_cey.abs() + cex_bey.abs();
+    let cd_p = cex_dey.abs() + dex_cey.abs();
+    let da_p = dex_aey.abs() + aex_dey.abs();
+    let ac_p = aex_cey.abs() + cex_aey.abs();
+    let bd_p = bex_dey.abs() + dex_bey.abs();
+    let permanent = (cd_p * bez + bd_p * cez + bc_p * dez) * alift
+        + (da_p * cez + ac_p * dez + cd_p * aez) * blift
+        + (ab_p * dez + bd_p * aez + da_p * bez) * clift
+        + (bc_p * aez + ac_p * bez + ab_p * cez) * dlift;
+
+    if det.abs() > IN_SPHERE_ERR_BOUND * permanent {
+        return det;
+    }
+
+    let [aex, aey, aez] = [0, 1, 2].map(|i| diff_expansion(a[i], e[i]));
+    let [bex, bey, bez] = [0, 1, 2].map(|i| diff_expansion(b[i], e[i]));
+    let [cex, cey, cez] = [0, 1, 2].map(|i| diff_expansion(c[i], e[i]));
+    let [dex, dey, dez] = [0, 1, 2].map(|i| diff_expansion(d[i], e[i]));
+
+    let ab = minor(&aex, &aey, &bex, &bey);
+    let bc = minor(&bex, &bey, &cex, &cey);
+    let cd = minor(&cex, &cey, &dex, &dey);
+    let da = minor(&dex, &dey, &aex, &aey);
+    let ac = minor(&aex, &aey, &cex, &cey);
+    let bd = minor(&bex, &bey, &dex, &dey);
+
+    let abc = expansion_sum(
+        &expansion_diff(&expansion_product(&aez, &bc), &expansion_product(&bez, &ac)),
+        &expansion_product(&cez, &ab),
+    );
+    let bcd = expansion_sum(
+        &expansion_diff(&expansion_product(&bez, &cd), &expansion_product(&cez, &bd)),
+        &expansion_product(&dez, &bc),
+    );
+    let cda = expansion_sum(
+        &expansion_sum(&expansion_product(&cez, &da), &expansion_product(&dez, &ac)),
+        &expansion_product(&aez, &cd),
+    );
+    let dab = expansion_sum(
+        &expansion_sum(&expansion_product(&dez, &ab), &expansion_product(&aez, &bd)),
+        &expansion_product(&bez, &da),
+    );
+
+    let lift = |x: &[f64], y: &[f64], z: &[f64]| {
+        expansion_sum(
+            &expansion_sum(&expansion_product(x, x), &expansion_product(y, y)),
+            &expansion_product(z, z),
+        )
+    };
+    let alift = lift(&aex, &aey, &aez);
+    let blift = lift(&bex, &bey, &bez);
+    let clift = lift(&cex, &cey, &cez);
+    let dlift = lift(&dex, &dey, &dez);
+
+    let det = expansion_sum(
+        &expansion_diff(
+            &expansion_product(&dlift, &abc),
+            &expansion_product(&clift, &dab),
+        ),
+        &expansion_diff(
+            &expansion_product(&blift, &cda),
+            &expansion_product(&alift, &bcd),
+        ),
+    );
+    estimate(&det)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // Points on a grid of step 2^-SHIFT, so that exact determinants are computed with integers
+    const SHIFT: i32 = 30;
+
+    fn to_f64(pt: [i64; 3]) -> Vector3<f64> {
+        Vector3::new(pt[0] as f64, pt[1] as f64, pt[2] as f64) * 2.0_f64.powi(-SHIFT)
+    }
+
+    fn sub(a: [i64; 3], b: [i64; 3]) -> [i128; 3] {
+        [0, 1, 2].map(|i| a[i] as i128 - b[i] as i128)
+    }
+
+    fn det3(a: [i128; 3], b: [i128; 3], c: [i128; 3]) -> i128 {
+        a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+            + a[2] * (b[0] * c[1] - b[1] * c[0])
+    }
+
+    fn exact_orient_3d(a: [i64; 3], b: [i64; 3], c: [i64; 3], d: [i64; 3]) -> i128 {
+        det3(sub(a, d), sub(b, d), sub(c, d))
+    }
+
+    fn exact_in_sphere(a: [i64; 3], b: [i64; 3], c: [i64; 3], d: [i64; 3], e: [i64; 3]) -> i128 {
+        let rows = [a, b, c, d].map(|pt| sub(pt, e));
+        let lift = rows.map(|row| row.iter().map(|x| x * x).sum::<i128>());
+        // expansion along the lift column
+        -lift[0] * det3(rows[1], rows[2], rows[3]) + lift[1] * det3(rows[0], rows[2], rows[3])
+            - lift[2] * det3(rows[0], rows[1], rows[3])
+            + lift[3] * det3(rows[0], rows[1], rows[2])
+    }
+
+    fn random_point(rng: &mut StdRng, bits: u32) -> [i64; 3] {
+        [0; 3].map(|_| rng.gen_range((1_i64 << (bits - 1))..(1_i64 << bits)))
+    }
+
+    #[test]
+    fn orient_3d_sign_convention() {
+        let [a, b, c] = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]].map(Vector3::from);
+        assert!(orient_3d(&a, &b, &c, &Vector3::new(0.2, 0.3, -1.0)) > 0.0);
+        assert!(orient_3d(&a, &b, &c, &Vector3::new(0.2, 0.3, 1.0)) < 0.0);
+        assert_eq!(orient_3d(&a, &b, &c, &Vector3::new(0.2, 0.3, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn orient_3d_near_coplanar() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let [a, b, c] = [0; 3].map(|_| random_point(&mut rng, 40));
+            // d = b + c - a is coplanar, then moved by at most one grid step per coordinate
+            let d = [0, 1, 2].map(|i| b[i] + c[i] - a[i] + rng.gen_range(-1..=1));
+            let det = orient_3d(&to_f64(a), &to_f64(b), &to_f64(c), &to_f64(d));
+            let exact = exact_orient_3d(a, b, c, d);
+            assert_eq!(det.partial_cmp(&0.0), Some(exact.cmp(&0)));
+        }
+    }
+
+    #[test]
+    fn in_sphere_sign_convention() {
+        let [a, b, c, d] =
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., -1.]].map(Vector3::from);
+        assert!(orient_3d(&a, &b, &c, &d) > 0.0);
+        assert!(in_sphere(&a, &b, &c, &d, &Vector3::new(0.1, 0.1, -0.1)) > 0.0);
+        assert!(in_sphere(&a, &b, &c, &d, &Vector3::new(2.0, 2.0, 2.0)) < 0.0);
+        assert_eq!(in_sphere(&a, &b, &c, &d, &Vector3::new(1.0, 1.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn in_sphere_near_cospherical() {
+        // integer points at distance 5 from the center
+        let offsets = [
+            [3, 4, 0],
+            [4, 0, 3],
+            [0, 3, 4],
+            [-5, 0, 0],
+            [0, 0, 5],
+            [0, -3, -4],
+            [-4, 0, -3],
+            [3, 0, -4],
+        ];
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let center = random_point(&mut rng, 20);
+            let scale = rng.gen_range(1..1000);
+            let mut pts: Vec<[i64; 3]> = offsets
+                .iter()
+                .map(|off| [0, 1, 2].map(|i| center[i] + scale * off[i]))
+                .collect();
+            let [a, b, c, d] = [pts[0], pts[1], pts[2], pts[3]];
+            let ind_e = rng.gen_range(4..pts.len());
+            for coord in pts[ind_e].iter_mut() {
+                *coord += rng.gen_range(-1..=1);
+            }
+            let e = pts[ind_e];
+            let det = in_sphere(&to_f64(a), &to_f64(b), &to_f64(c), &to_f64(d), &to_f64(e));
+            let exact = exact_in_sphere(a, b, c, d, e);
+            assert_eq!(det.partial_cmp(&0.0), Some(exact.cmp(&0)));
+        }
+    }
+}
@@ -9,3 +9,6 @@ pub mod geometry;
 pub mod mesh3d;
 /// Skeleton object and operations
 pub mod skeleton3d;
+
+/// PLY file format reading
+mod ply;
@@ -0,0 +1,299 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::geometry::Real;
+use crate::mesh3d::{GenericMesh3D, ManifoldMesh3D};
+
+#[derive(Clone, Debug, Default)]
+/// Non manifold configurations preventing a conversion to manifold mesh
+pub struct NonManifoldError {
+    /// Edges shared by more than two faces
+    pub non_manifold_edges: Vec<[usize; 2]>,
+    /// Edges whose two faces have inconsistent orientations
+    pub inconsistent_edges: Vec<[usize; 2]>,
+    /// Vertices joining several fans of faces (vertices of the edges above excepted)
+    pub pinched_vertices: Vec<usize>,
+    /// Faces with repeated vertices
+    pub degenerate_faces: Vec<usize>,
+}
+
+impl NonManifoldError {
+    fn is_empty(&self) -> bool {
+        self.non_manifold_edges.is_empty()
+            && self.inconsistent_edges.is_empty()
+            && self.pinched_vertices.is_empty()
+            && self.degenerate_faces.is_empty()
+    }
+}
+
+impl fmt::Display for NonManifoldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Non manifold mesh: {} non manifold edge(s), {} inconsistent edge(s), {} pinched vertex(ices), {} degenerate face(s)",
+            self.non_manifold_edges.len(),
+            self.inconsistent_edges.len(),
+            self.pinched_vertices.len(),
+            self.degenerate_faces.len()
+        )
+    }
+}
+
+impl std::error::Error for NonManifoldError {}
+
+/// Faces around edges, with the edge direction in the face (true if from lower to upper index)
+pub(super) type EdgeFaces = HashMap<[usize; 2], Vec<(usize, bool)>>;
+
+fn sorted_edge(ind_vertex1: usize, ind_vertex2: usize) -> [usize; 2] {
+    [ind_vertex1.min(ind_vertex2), ind_vertex1.max(ind_vertex2)]
+}
+
+pub(super) fn edge_faces(faces: &[[usize; 3]]) -> EdgeFaces {
+    let mut edge_faces: EdgeFaces = HashMap::new();
+    for (ind_face, face) in faces.iter().enumerate() {
+        for i in 0..3 {
+            let (ind_vertex1, ind_vertex2) = (face[i], face[(i + 1) % 3]);
+            edge_faces
+                .entry(sorted_edge(ind_vertex1, ind_vertex2))
+                .or_default()
+                .push((ind_face, ind_vertex1 < ind_vertex2));
+        }
+    }
+    edge_faces
+}
+
+/// Whether the two faces of an edge are glued along it (consistently oriented)
+pub(super) fn is_glued(faces_around: &[(usize, bool)]) -> bool {
+    faces_around.len() == 2 && faces_around[0].1 != faces_around[1].1
+}
+
+/// Groups of faces around each vertex, joined through glued edges (fans)
+pub(super) fn vertex_fans(
+    nb_vertices: usize,
+    faces: &[[usize; 3]],
+    edge_faces: &EdgeFaces,
+) -> Vec<Vec<Vec<usize>>> {
+    let mut vertex_faces = vec![Vec::new(); nb_vertices];
+    for (ind_face, face) in faces.iter().enumerate() {
+        for &ind_vertex in face.iter() {
+            vertex_faces[ind_vertex].push(ind_face);
+        }
+    }
+
+    let mut fans = Vec::with_capacity(nb_vertices);
+    for (ind_vertex, around) in vertex_faces.iter().enumerate() {
+        let mut vertex_fans: Vec<Vec<usize>> = Vec::new();
+        let mut fan_of: HashMap<usize, usize> = HashMap::new();
+        for &ind_start in around.iter() {
+            if fan_of.contains_key(&ind_start) {
+                continue;
+            }
+            let ind_fan = vertex_fans.len();
+            fan_of.insert(ind_start, ind_fan);
+            let mut fan = vec![ind_start];
+            let mut ind_cur = 0;
+            while ind_cur < fan.len() {
+                let face = faces[fan[ind_cur]];
+                ind_cur += 1;
+                for &ind_other in face.iter().filter(|&&ind| ind != ind_vertex) {
+                    let faces_around = &edge_faces[&sorted_edge(ind_vertex, ind_other)];
+                    if !is_glued(faces_around) {
+                        continue;
+                    }
+                    for &(ind_neigh, _) in faces_around.iter() {
+                        if let Entry::Vacant(entry) = fan_of.entry(ind_neigh) {
+                            entry.insert(ind_fan);
+                            fan.push(ind_neigh);
+                        }
+                    }
+                }
+            }
+            vertex_fans.push(fan);
+        }
+        fans.push(vertex_fans);
+    }
+    fans
+}
+
+/// Edges shared by more than two faces, and edges whose two faces are inconsistently oriented
+fn edge_errors(edge_faces: &EdgeFaces) -> NonManifoldError {
+    let mut err = NonManifoldError::default();
+    for (edge, faces_around) in edge_faces.iter() {
+        if faces_around.len() > 2 {
+            err.non_manifold_edges.push(*edge);
+        } else if faces_around.len() == 2 && !is_glued(faces_around) {
+            err.inconsistent_edges.push(*edge);
+        }
+    }
+    err.non_manifold_edges.sort();
+    err.inconsistent_edges.sort();
+    err
+}
+
+/// Faces of a generic mesh, split into degenerate ones (indices) and valid ones
+fn mesh_faces<T: Real>(mesh: &GenericMesh3D<T>) -> (Vec<[usize; 3]>, Vec<usize>) {
+    let mut faces = Vec::with_capacity(mesh.get_nb_faces());
+    let mut degenerate = Vec::new();
+    for (ind_face, face) in mesh.faces.iter().enumerate() {
+        if face[0] == face[1] || face[1] == face[2] || face[2] == face[0] {
+            degenerate.push(ind_face);
+        } else {
+            faces.push(*face);
+        }
+    }
+    (faces, degenerate)
+}
+
+impl<T: Real> TryFrom<&GenericMesh3D<T>> for ManifoldMesh3D<T> {
+    type Error = NonManifoldError;
+
+    /// Converts a generic mesh, keeping vertex indices and face orientations
+    ///
+    /// Fails with all the non manifold edges, inconsistent orientations and pinched vertices found
+    fn try_from(mesh: &GenericMesh3D<T>) -> Result<ManifoldMesh3D<T>, NonManifoldError> {
+        let (faces, degenerate_faces) = mesh_faces(mesh);
+        let edge_faces = edge_faces(&faces);
+
+        let mut err = NonManifoldError {
+            degenerate_faces,
+            ..edge_errors(&edge_faces)
+        };
+        let bad_vertices: BTreeSet<usize> = err
+            .non_manifold_edges
+            .iter()
+            .chain(err.inconsistent_edges.iter())
+            .flatten()
+            .copied()
+            .collect();
+        for (ind_vertex, fans) in vertex_fans(mesh.get_nb_vertices(), &faces, &edge_faces)
+            .iter()
+            .enumerate()
+        {
+            if fans.len() > 1 && !bad_vertices.contains(&ind_vertex) {
+                err.pinched_vertices.push(ind_vertex);
+            }
+        }
+        if !err.is_empty() {
+            return Err(err);
+        }
+
+        let mut manifold = ManifoldMesh3D::new();
+        for vertex in mesh.vertices.iter() {
+            manifold.add_vertex(vertex);
+        }
+        for face in faces.iter() {
+            if manifold.add_face(face[0], face[1], face[2]).is_err() {
+                // face sides already used in the same direction by another face
+                for i in 0..3 {
+                    let (ind_vertex1, ind_vertex2) = (face[i], face[(i + 1) % 3]);
+                    if manifold.is_edge_in(ind_vertex1, ind_vertex2).is_some() {
+                        err.inconsistent_edges
+                            .push(sorted_edge(ind_vertex1, ind_vertex2));
+                    }
+                }
+            }
+        }
+        if !err.is_empty() {
+            err.inconsistent_edges.sort();
+            err.inconsistent_edges.dedup();
+            return Err(err);
+        }
+        Ok(manifold)
+    }
+}
+
+/// Converts a generic mesh to manifold mesh, splitting non manifold vertices
+///
+/// Each vertex is duplicated once per fan of faces, a fan being a set of faces joined through
+/// edges with exactly two consistently oriented faces. Degenerate faces are removed. Copies are
+/// added after the original vertices, the original vertex of each copy is returned with the mesh
+///
+/// Only vertices are split: fails with the edges shared by more than two faces or inconsistently
+/// oriented, if any
+pub fn split_non_manifold<T: Real>(
+    mesh: &GenericMesh3D<T>,
+) -> Result<(ManifoldMesh3D<T>, Vec<usize>), NonManifoldError> {
+    let (mut faces, _) = mesh_faces(mesh);
+    let edge_faces = edge_faces(&faces);
+    let err = edge_errors(&edge_faces);
+    if !err.is_empty() {
+        return Err(err);
+    }
+    let fans = vertex_fans(mesh.get_nb_vertices(), &faces, &edge_faces);
+
+    let mut split = GenericMesh3D::new();
+    for vertex in mesh.vertices.iter() {
+        split.add_vertex(vertex);
+    }
+    let mut copies = Vec::new();
+    for (ind_vertex, vertex_fans) in fans.iter().enumerate() {
+        for fan in vertex_fans.iter().skip(1) {
+            let ind_copy = split.add_vertex(&mesh.vertices[ind_vertex]);
+            copies.push(ind_vertex);
+            for &ind_face in fan.iter() {
+                for ind in faces[ind_face].iter_mut() {
+                    if *ind == ind_vertex {
+                        *ind = ind_copy;
+                    }
+                }
+            }
+        }
+    }
+    for face in faces.iter() {
+        // vertex indices are valid by construction
+        let _ = split.add_face(face[0], face[1], face[2]);
+    }
+
+    Ok((ManifoldMesh3D::try_from(&split)?, copies))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector3;
+
+    fn generic_mesh(nb_vertices: usize, faces: &[[usize; 3]]) -> GenericMesh3D<f64> {
+        let mut mesh = GenericMesh3D::new();
+        for ind in 0..nb_vertices {
+            let x = ind as f64;
+            mesh.add_vertex(&Vector3::new(x, x * x, x * x * x));
+        }
+        for face in faces.iter() {
+            mesh.add_face(face[0], face[1], face[2]).unwrap();
+        }
+        mesh
+    }
+
+    const TETRAHEDRON: [[usize; 3]; 4] = [[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+
+    #[test]
+    fn pinched_vertex_is_split() {
+        let mut faces = TETRAHEDRON.to_vec();
+        faces.extend(TETRAHEDRON.map(|face| face.map(|ind| if ind == 0 { 0 } else { ind + 3 })));
+        let mesh = generic_mesh(7, &faces);
+
+        let err = ManifoldMesh3D::try_from(&mesh).err().unwrap();
+        assert_eq!(err.pinched_vertices, vec![0]);
+
+        let (manifold, copies) = split_non_manifold(&mesh).unwrap();
+        assert_eq!(copies, vec![0]);
+        assert_eq!(manifold.get_nb_vertices(), 8);
+        manifold.check_mesh().unwrap();
+    }
+
+    #[test]
+    fn inconsistent_edges_are_not_split() {
+        let mut faces = TETRAHEDRON.to_vec();
+        faces[3].swap(1, 2);
+        let err = split_non_manifold(&generic_mesh(4, &faces)).err().unwrap();
+        assert_eq!(err.inconsistent_edges, vec![[1, 2], [1, 3], [2, 3]]);
+    }
+
+    #[test]
+    fn non_manifold_edges_are_not_split() {
+        let faces = [[0, 1, 2], [1, 0, 3], [0, 1, 4]];
+        let err = split_non_manifold(&generic_mesh(5, &faces)).err().unwrap();
+        assert_eq!(err.non_manifold_edges, vec![[0, 1]]);
+    }
+}
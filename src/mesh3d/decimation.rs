@@ -0,0 +1,329 @@
+use anyhow::Result;
+use log::{debug, info};
+use nalgebra::base::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::geometry::Real;
+use crate::mesh3d::mesh_operations;
+use crate::mesh3d::ManifoldMesh3D;
+
+/// Edge collapse candidate, ordered by increasing cost in a max heap
+struct Candidate<T> {
+    cost: f64,
+    edge: [usize; 2],
+    stamps: [usize; 2],
+    position: Vector3<T>,
+}
+
+impl<T> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Candidate<T> {}
+
+impl<T> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Candidate<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // ties broken by edge, for reproducible runs
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.edge.cmp(&self.edge))
+    }
+}
+
+/// Fundamental quadric of the plane of a face
+fn face_quadric<T: Real>(pts: [Vector3<T>; 3]) -> Matrix4<T> {
+    let normal = (pts[1] - pts[0]).cross(&(pts[2] - pts[0]));
+    let norm = normal.norm();
+    if norm == T::zero() {
+        return Matrix4::zeros();
+    }
+    let normal = normal / norm;
+    let plane = Vector4::new(normal[0], normal[1], normal[2], -normal.dot(&pts[0]));
+    plane * plane.transpose()
+}
+
+/// Quadric error of a position
+fn quadric_error<T: Real>(quadric: &Matrix4<T>, position: &Vector3<T>) -> T {
+    let hom = Vector4::new(position[0], position[1], position[2], T::one());
+    hom.dot(&(quadric * hom))
+}
+
+/// Position minimizing the quadric error of an edge collapse, with its error
+///
+/// Falls back to the best of edge ends and middle if the quadric is singular, or if the optimal
+/// position is farther from the edge middle than the edge length (nearly singular quadric)
+fn optimal_position<T: Real>(
+    quadric: &Matrix4<T>,
+    pt1: &Vector3<T>,
+    pt2: &Vector3<T>,
+) -> (Vector3<T>, T) {
+    let middle = (pt1 + pt2) * nalgebra::convert::<f64, T>(0.5);
+    let mut system = *quadric;
+    system[(3, 0)] = T::zero();
+    system[(3, 1)] = T::zero();
+    system[(3, 2)] = T::zero();
+    system[(3, 3)] = T::one();
+    if let Some(inv) = system.try_inverse() {
+        let hom = inv * Vector4::new(T::zero(), T::zero(), T::zero(), T::one());
+        let position = Vector3::new(hom[0], hom[1], hom[2]);
+        if (position - middle).norm() <= (pt2 - pt1).norm() {
+            return (position, quadric_error(quadric, &position));
+        }
+    }
+
+    [*pt1, *pt2, middle]
+        .iter()
+        .map(|&position| (position, quadric_error(quadric, &position)))
+        .fold(
+            None,
+            |best: Option<(Vector3<T>, T)>, (position, error)| match best {
+                Some((_, error_best)) if error_best <= error => best,
+                _ => Some((position, error)),
+            },
+        )
+        .unwrap()
+}
+
+/// Whether moving vertex 1 and 2 of halfedge (1->2) to a position flips a face around them
+fn flips_faces<T: Real>(
+    mesh: &ManifoldMesh3D<T>,
+    ind_halfedge: usize,
+    position: &Vector3<T>,
+) -> Result<bool> {
+    let [ind_v1, ind_v2] = mesh.get_halfedge(ind_halfedge)?.halfedge();
+    for ind_vertex in [ind_v1, ind_v2] {
+        for he in mesh.get_vertex(ind_vertex)?.halfedges() {
+            let face = he.face().ok_or(anyhow::Error::msg(
+                "flips_faces(): Halfedge should be linked to a face",
+            ))?;
+            let vertices = face.vertices_inds();
+            // collapsed faces
+            if vertices.contains(&ind_v1) && vertices.contains(&ind_v2) {
+                continue;
+            }
+            let pts = face.vertices().map(|vert| vert.vertex());
+            let new_pts = vertices.map(|ind| {
+                if ind == ind_vertex {
+                    *position
+                } else {
+                    mesh.vertices()[&ind]
+                }
+            });
+            let normal = (pts[1] - pts[0]).cross(&(pts[2] - pts[0]));
+            let new_normal = (new_pts[1] - new_pts[0]).cross(&(new_pts[2] - new_pts[0]));
+            if normal.dot(&new_normal) <= T::zero() {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Copy of a mesh with contiguous vertex indices, and new index of each vertex
+fn compact<T: Real>(
+    mesh: &ManifoldMesh3D<T>,
+    nb_vertices_init: usize,
+) -> Result<(ManifoldMesh3D<T>, Vec<Option<usize>>)> {
+    let mut compacted = ManifoldMesh3D::new();
+    let mut vertex_map = vec![None; nb_vertices_init];
+    for ind_vertex in mesh.vertex_indices() {
+        vertex_map[ind_vertex] = Some(compacted.add_vertex(&mesh.vertices()[&ind_vertex]));
+    }
+    let mut face_inds: Vec<usize> = mesh.faces().keys().copied().collect();
+    face_inds.sort();
+    for ind_face in face_inds {
+        let [ind_v1, ind_v2, ind_v3] = mesh
+            .get_face(ind_face)?
+            .vertices_inds()
+            .map(|ind| vertex_map[ind].unwrap());
+        let ind_new = compacted.add_face(ind_v1, ind_v2, ind_v3)?;
+        if let Some(&Some(group)) = mesh.groups.get(&ind_face) {
+            compacted.set_face_in_group(ind_new, group);
+        }
+    }
+    Ok((compacted, vertex_map))
+}
+
+/// Decimates a mesh by quadric error edge collapses
+///
+/// Cheapest edges are collapsed until the number of faces reaches opt_target_faces, or until the
+/// cheapest error (sum of squared distances to the planes of the original faces) exceeds
+/// opt_max_error. At least one of them should be given.
+/// Boundary vertices and feature edge vertices are kept, collapses flipping faces are skipped.
+///
+/// Returns the decimated mesh (with contiguous vertex indices) and the new index of each original
+/// vertex (none if removed)
+pub fn decimate<T: Real>(
+    mesh: &ManifoldMesh3D<T>,
+    opt_target_faces: Option<usize>,
+    opt_max_error: Option<T>,
+    opt_feature_edges: Option<&[[usize; 2]]>,
+) -> Result<(ManifoldMesh3D<T>, Vec<Option<usize>>)> {
+    if opt_target_faces.is_none() && opt_max_error.is_none() {
+        return Err(anyhow::Error::msg(
+            "decimate(): Target face number or maximum error should be given",
+        ));
+    }
+    let target_faces = opt_target_faces.unwrap_or(0);
+    let nb_vertices_init = mesh.vertex_indices().last().map_or(0, |&ind| ind + 1);
+
+    let mut work_mesh = mesh.clone();
+    let locked: HashSet<usize> = opt_feature_edges
+        .map(|feature_edges| feature_edges.iter().flatten().copied().collect())
+        .unwrap_or_default();
+
+    let mut quadrics: HashMap<usize, Matrix4<T>> = mesh
+        .vertices()
+        .keys()
+        .map(|&ind_vertex| (ind_vertex, Matrix4::zeros()))
+        .collect();
+    // sorted indices, so that sums do not depend on hash order
+    let mut face_inds: Vec<usize> = mesh.faces().keys().copied().collect();
+    face_inds.sort();
+    for ind_face in face_inds {
+        let face = mesh.get_face(ind_face)?;
+        let quadric = face_quadric(face.vertices().map(|vert| vert.vertex()));
+        for ind_vertex in face.vertices_inds() {
+            *quadrics.get_mut(&ind_vertex).unwrap() += quadric;
+        }
+    }
+
+    // stamps change with vertex moves, invalidating queued candidates
+    let mut stamps: HashMap<usize, usize> = mesh.vertices().keys().map(|&ind| (ind, 0)).collect();
+    let candidate = |work_mesh: &ManifoldMesh3D<T>,
+                     quadrics: &HashMap<usize, Matrix4<T>>,
+                     stamps: &HashMap<usize, usize>,
+                     edge: [usize; 2]| {
+        let quadric = quadrics[&edge[0]] + quadrics[&edge[1]];
+        let (position, error) = optimal_position(
+            &quadric,
+            &work_mesh.vertices()[&edge[0]],
+            &work_mesh.vertices()[&edge[1]],
+        );
+        Candidate {
+            cost: error.to_f64(),
+            edge,
+            stamps: [stamps[&edge[0]], stamps[&edge[1]]],
+            position,
+        }
+    };
+
+    let mut heap = BinaryHeap::new();
+    for he in work_mesh.halfedges().values() {
+        if he[0] < he[1] && !locked.contains(&he[0]) && !locked.contains(&he[1]) {
+            heap.push(candidate(&work_mesh, &quadrics, &stamps, *he));
+        }
+    }
+
+    info!("Decimating mesh from {} faces", work_mesh.get_nb_faces());
+    while let Some(cand) = heap.pop() {
+        if work_mesh.get_nb_faces() <= target_faces {
+            break;
+        }
+        if let Some(max_error) = opt_max_error {
+            if cand.cost > max_error.to_f64() {
+                break;
+            }
+        }
+        let [ind_v1, ind_v2] = cand.edge;
+        if stamps.get(&ind_v1) != Some(&cand.stamps[0])
+            || stamps.get(&ind_v2) != Some(&cand.stamps[1])
+        {
+            continue;
+        }
+        let ind_halfedge = if let Some(he) = work_mesh.is_edge_in(ind_v1, ind_v2) {
+            he.ind()
+        } else {
+            continue;
+        };
+        if flips_faces(&work_mesh, ind_halfedge, &cand.position)? {
+            continue;
+        }
+        if !mesh_operations::collapse_halfedge(&mut work_mesh, &cand.position, ind_halfedge)? {
+            continue;
+        }
+
+        let quadric = quadrics.remove(&ind_v2).unwrap();
+        *quadrics.get_mut(&ind_v1).unwrap() += quadric;
+        stamps.remove(&ind_v2);
+        *stamps.get_mut(&ind_v1).unwrap() += 1;
+        for he in work_mesh.get_vertex(ind_v1)?.halfedges() {
+            let ind_neigh = he.last_vertex().ind();
+            if !locked.contains(&ind_neigh) {
+                let edge = [ind_v1.min(ind_neigh), ind_v1.max(ind_neigh)];
+                heap.push(candidate(&work_mesh, &quadrics, &stamps, edge));
+            }
+        }
+        debug!("{} faces remaining", work_mesh.get_nb_faces());
+    }
+    info!("Decimated mesh: {} faces", work_mesh.get_nb_faces());
+
+    compact(&work_mesh, nb_vertices_init)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh3d::test_meshes;
+    use crate::mesh3d::MeshStats;
+
+    const NB_AROUND: usize = 16;
+    const NB_SECTION: usize = 8;
+
+    fn torus() -> ManifoldMesh3D<f64> {
+        test_meshes::torus(NB_AROUND, NB_SECTION)
+    }
+
+    #[test]
+    fn decimation_keeps_topology() {
+        let mesh = torus();
+        let (decimated, vertex_map) = decimate(&mesh, Some(100), None, None).unwrap();
+        decimated.check_mesh().unwrap();
+        assert!(decimated.get_nb_faces() <= 100);
+
+        let stats = MeshStats::compute(&decimated);
+        assert!(stats.is_closed());
+        assert_eq!((stats.nb_components, stats.genus), (1, 1));
+
+        // kept vertices are renumbered contiguously
+        let mut new_inds: Vec<usize> = vertex_map.iter().flatten().copied().collect();
+        new_inds.sort();
+        assert_eq!(vertex_map.len(), mesh.get_nb_vertices());
+        assert_eq!(new_inds, decimated.vertex_indices());
+        assert_eq!(
+            new_inds,
+            (0..decimated.get_nb_vertices()).collect::<Vec<usize>>()
+        );
+    }
+
+    #[test]
+    fn feature_edges_are_kept() {
+        let mesh = torus();
+        // outer equator of the torus
+        let feature_edges: Vec<[usize; 2]> = (0..NB_AROUND)
+            .map(|i| [i * NB_SECTION, ((i + 1) % NB_AROUND) * NB_SECTION])
+            .collect();
+        let (decimated, vertex_map) =
+            decimate(&mesh, Some(100), None, Some(&feature_edges)).unwrap();
+        for [ind_v1, ind_v2] in feature_edges {
+            let (new_v1, new_v2) = (vertex_map[ind_v1].unwrap(), vertex_map[ind_v2].unwrap());
+            assert!(decimated.is_edge_in(new_v1, new_v2).is_some());
+            assert_eq!(decimated.vertices()[&new_v1], mesh.vertices()[&ind_v1]);
+        }
+    }
+
+    #[test]
+    fn target_or_error_required() {
+        assert!(decimate(&torus(), None, None, None).is_err());
+    }
+}
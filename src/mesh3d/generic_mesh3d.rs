@@ -1,17 +1,24 @@
 use anyhow::Result;
 use nalgebra::base::*;
 
+use crate::geometry::Real;
+
 /// Mesh vertex
-pub type Vertex = Vector3<f32>;
+pub type Vertex<T = f32> = Vector3<T>;
 /// Mesh edge
 pub type Edge = [usize; 2];
 /// Mesh face (array of vertex indices)
 pub type Face = [usize; 3];
 
+fn sorted_face(mut face: Face) -> Face {
+    face.sort();
+    face
+}
+
 #[derive(Clone)]
 /// Generic non manifold Mesh
-pub struct GenericMesh3D {
-    pub(super) vertices: Vec<Vertex>,
+pub struct GenericMesh3D<T = f32> {
+    pub(super) vertices: Vec<Vertex<T>>,
     pub(super) edges: Vec<Edge>,
     pub(super) faces: Vec<Face>,
 
@@ -19,9 +26,9 @@ pub struct GenericMesh3D {
     pub(super) map_edg_face: Vec<Vec<usize>>,
 }
 
-impl GenericMesh3D {
+impl<T: Real> GenericMesh3D<T> {
     /// Generic mesh constructor
-    pub fn new() -> GenericMesh3D {
+    pub fn new() -> GenericMesh3D<T> {
         GenericMesh3D {
             vertices: Vec::new(),
             edges: Vec::new(),
@@ -33,18 +40,18 @@ impl GenericMesh3D {
     }
 
     /// Adds a vertex to the mesh
-    pub fn add_vertex(&mut self, point: &Vector3<f32>) -> usize {
+    pub fn add_vertex(&mut self, point: &Vector3<T>) -> usize {
         self.vertices.push(*point);
         self.map_vert_edg.push(Vec::new());
         self.vertices.len() - 1
     }
 
-    fn get_vertex_uncheck(&self, ind_vertex: usize) -> Vertex {
+    fn get_vertex_uncheck(&self, ind_vertex: usize) -> Vertex<T> {
         self.vertices[ind_vertex]
     }
 
     /// Vertex getter
-    pub fn get_vertex(&self, ind_vertex: usize) -> Result<Vertex> {
+    pub fn get_vertex(&self, ind_vertex: usize) -> Result<Vertex<T>> {
         if ind_vertex >= self.vertices.len() {
             return Err(anyhow::Error::msg("get_vertex(): Index out of bounds"));
         }
@@ -109,13 +116,16 @@ impl GenericMesh3D {
     }
 
     /// Adds a face to the mesh
+    ///
+    /// The vertex order (orientation) is kept, a face with the same vertices is only added once
     pub fn add_face(
         &mut self,
         ind_vertex1: usize,
         ind_vertex2: usize,
         ind_vertex3: usize,
     ) -> Result<usize> {
-        let mut face = [ind_vertex1, ind_vertex2, ind_vertex3];
+        let oriented_face = [ind_vertex1, ind_vertex2, ind_vertex3];
+        let mut face = oriented_face;
         face.sort();
         let [ind_vertex1, ind_vertex2, ind_vertex3] = face;
         if ind_vertex3 >= self.map_vert_edg.len() {
@@ -125,7 +135,7 @@ impl GenericMesh3D {
 
         let opt_fac = self.map_edg_face[ind_edge1]
             .iter()
-            .find(|&ind_face| self.faces[*ind_face] == face);
+            .find(|&ind_face| sorted_face(self.faces[*ind_face]) == face);
         if let Some(&ind_fac) = opt_fac {
             return Ok(ind_fac);
         }
@@ -133,7 +143,7 @@ impl GenericMesh3D {
         let ind_edge2 = self.add_edge(ind_vertex2, ind_vertex3)?;
         let ind_edge3 = self.add_edge(ind_vertex1, ind_vertex3)?;
 
-        self.faces.push([ind_vertex1, ind_vertex2, ind_vertex3]);
+        self.faces.push(oriented_face);
         let ind_face = self.faces.len() - 1;
 
         self.map_edg_face[ind_edge1].push(ind_face);
@@ -202,7 +212,7 @@ impl GenericMesh3D {
             if let Some(ind_edg) = opt_ind_edg {
                 let opt_fac = self.map_edg_face[ind_edg]
                     .iter()
-                    .find(|&&ind_face| self.faces[ind_face] == face);
+                    .find(|&&ind_face| sorted_face(self.faces[ind_face]) == face);
                 if let Some(&ind_fac) = opt_fac {
                     return Some(ind_fac);
                 }
@@ -1,137 +1,1009 @@
 use anyhow::Result;
 use nalgebra::base::*;
-use rand::Rng;
-use std::collections::HashMap;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs::File;
-use std::io::{self, BufRead, Write};
+use std::io::{self, BufRead, Read, Write};
+use std::str::FromStr;
 
+use crate::geometry::geometry_operations::{self, PolygonTriangulation};
+use crate::geometry::Real;
 use crate::mesh3d::GenericMesh3D;
 use crate::mesh3d::ManifoldMesh3D;
+use crate::ply::{self, PlyElement, PlyFormat, PlyProperty, PlyRow, PlyType};
 
-/// Loads obj file as manifold mesh
-pub fn load_obj_manifold(filename: &str) -> Result<ManifoldMesh3D> {
-    let mut mesh = ManifoldMesh3D::new();
+#[derive(Debug)]
+/// Cause of a loading error
+pub enum LoadErrorKind {
+    /// Input/output failure
+    Io(io::Error),
+    /// Missing or malformed value
+    Parse(String),
+    /// Vertex index not referring to a loaded vertex
+    InvalidVertexIndex(isize),
+    /// Face rejected by the manifold mesh
+    InvalidFace {
+        /// Face index in the input file
+        face: usize,
+        /// Face vertex indices (starting from 0)
+        vertices: Vec<usize>,
+        /// Rejection cause
+        cause: String,
+    },
+}
 
-    let file = File::open(filename)?;
-    let lines = io::BufReader::new(file).lines();
-    for line_ in lines {
-        if let Ok(line) = line_ {
-            if line.len() > 2 {
-                if &line[..2] == "v " {
-                    let mut line_split = line.split_whitespace();
-                    let mut vert: Vector3<f32> = Vector3::new(0.0, 0.0, 0.0);
-                    line_split.next();
-                    for i in 0..3 {
-                        let cur = line_split
-                            .next()
-                            .ok_or(anyhow::Error::msg("Expected value"))?;
-                        vert[i] = cur.parse::<f32>()?;
-                    }
+#[derive(Debug)]
+/// Mesh loading error, located in the input file
+pub struct LoadError {
+    /// Loaded file path
+    pub path: Option<String>,
+    /// Line number (starting from 1), for text formats
+    pub line: Option<usize>,
+    /// Error cause
+    pub kind: LoadErrorKind,
+}
+
+impl LoadError {
+    /// Loading error without location
+    pub fn new(kind: LoadErrorKind) -> LoadError {
+        LoadError {
+            path: None,
+            line: None,
+            kind,
+        }
+    }
+
+    /// Loading error from a parsing failure
+    pub fn parse(msg: &str) -> LoadError {
+        LoadError::new(LoadErrorKind::Parse(msg.to_string()))
+    }
+
+    /// Sets line number, if not already set
+    pub fn at_line(mut self, line: usize) -> LoadError {
+        self.line = self.line.or(Some(line));
+        self
+    }
+
+    /// Sets file path, if not already set
+    pub fn in_file(mut self, path: &str) -> LoadError {
+        self.path = self.path.or(Some(path.to_string()));
+        self
+    }
+}
+
+impl fmt::Display for LoadErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadErrorKind::Io(err) => write!(f, "{}", err),
+            LoadErrorKind::Parse(msg) => write!(f, "{}", msg),
+            LoadErrorKind::InvalidVertexIndex(ind) => write!(f, "Invalid vertex index {}", ind),
+            LoadErrorKind::InvalidFace {
+                face,
+                vertices,
+                cause,
+            } => write!(f, "Face {} {:?} rejected: {}", face, vertices, cause),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}:", path)?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "{}:", line)?;
+        }
+        if self.path.is_some() || self.line.is_some() {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            LoadErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> LoadError {
+        LoadError::new(LoadErrorKind::Io(err))
+    }
+}
+
+/// Parses next word of a line
+pub(crate) fn parse_next<'a, T: FromStr>(
+    words: &mut impl Iterator<Item = &'a str>,
+    what: &str,
+) -> std::result::Result<T, LoadError> {
+    let word = words
+        .next()
+        .ok_or(LoadError::parse(&format!("Expected {}", what)))?;
+    word.parse::<T>()
+        .map_err(|_| LoadError::parse(&format!("Invalid {} '{}'", what, word)))
+}
+
+/// Converts an obj vertex index (starting from 1, or negative) into a mesh vertex index
+fn obj_vertex_index(word: &str, nb_vertices: usize) -> std::result::Result<usize, LoadError> {
+    let mut word_split = word.split('/');
+    let ind: isize = parse_next(&mut word_split, "vertex index")?;
+    // negative indices are relative to the last read vertex
+    if ind > 0 && ind as usize <= nb_vertices {
+        Ok(ind as usize - 1)
+    } else if ind < 0 && ind.unsigned_abs() <= nb_vertices {
+        Ok(nb_vertices - ind.unsigned_abs())
+    } else {
+        Err(LoadError::new(LoadErrorKind::InvalidVertexIndex(ind)))
+    }
+}
+
+/// Mesh filled by the readers
+trait MeshBuilder<T> {
+    fn add_vertex(&mut self, vert: &Vector3<T>) -> usize;
+    fn vertex(&self, ind_vertex: usize) -> Option<Vector3<T>>;
+    fn nb_vertices(&self) -> usize;
+    fn add_triangle(&mut self, ind1: usize, ind2: usize, ind3: usize) -> Result<()>;
+}
+
+impl<T: Real> MeshBuilder<T> for ManifoldMesh3D<T> {
+    fn add_vertex(&mut self, vert: &Vector3<T>) -> usize {
+        ManifoldMesh3D::add_vertex(self, vert)
+    }
+
+    fn vertex(&self, ind_vertex: usize) -> Option<Vector3<T>> {
+        self.get_vertex(ind_vertex).ok().map(|vert| vert.vertex())
+    }
 
-                    mesh.add_vertex(&vert);
+    fn nb_vertices(&self) -> usize {
+        self.get_nb_vertices()
+    }
+
+    fn add_triangle(&mut self, ind1: usize, ind2: usize, ind3: usize) -> Result<()> {
+        self.add_face(ind1, ind2, ind3).map(|_| ())
+    }
+}
+
+impl<T: Real> MeshBuilder<T> for GenericMesh3D<T> {
+    fn add_vertex(&mut self, vert: &Vector3<T>) -> usize {
+        GenericMesh3D::add_vertex(self, vert)
+    }
+
+    fn vertex(&self, ind_vertex: usize) -> Option<Vector3<T>> {
+        self.get_vertex(ind_vertex).ok()
+    }
+
+    fn nb_vertices(&self) -> usize {
+        self.get_nb_vertices()
+    }
+
+    fn add_triangle(&mut self, ind1: usize, ind2: usize, ind3: usize) -> Result<()> {
+        self.add_face(ind1, ind2, ind3).map(|_| ())
+    }
+}
+
+fn add_polygon<T: Real, M: MeshBuilder<T>>(
+    mesh: &mut M,
+    ind_face: usize,
+    polygon: &[usize],
+    triangulation: PolygonTriangulation,
+) -> std::result::Result<(), LoadError> {
+    let invalid_face = |cause: String| {
+        LoadError::new(LoadErrorKind::InvalidFace {
+            face: ind_face,
+            vertices: polygon.to_vec(),
+            cause,
+        })
+    };
+    if polygon.len() < 3 {
+        return Err(invalid_face("less than 3 vertices".to_string()));
+    }
+    let mut pts = Vec::with_capacity(polygon.len());
+    for &ind in polygon.iter() {
+        let vert = mesh
+            .vertex(ind)
+            .ok_or(LoadError::new(LoadErrorKind::InvalidVertexIndex(
+                ind as isize,
+            )))?;
+        pts.push(vert);
+    }
+    for tri in geometry_operations::triangulate_polygon(&pts, triangulation) {
+        mesh.add_triangle(polygon[tri[0]], polygon[tri[1]], polygon[tri[2]])
+            .map_err(|err| invalid_face(err.to_string()))?;
+    }
+    Ok(())
+}
+
+fn read_obj<T: Real, M: MeshBuilder<T>, R: BufRead>(
+    reader: R,
+    mut mesh: M,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<M, LoadError> {
+    let triangulation = opt_triangulation.unwrap_or(PolygonTriangulation::Fan);
+
+    let mut ind_face = 0;
+    for (ind_line, line_) in reader.lines().enumerate() {
+        let located = |err: LoadError| err.at_line(ind_line + 1);
+        let line = line_.map_err(|err| located(LoadError::from(err)))?;
+        let mut line_split = line.split_whitespace();
+        match line_split.next() {
+            Some("v") => {
+                let mut vert: Vector3<T> = Vector3::zeros();
+                for i in 0..3 {
+                    vert[i] = parse_next(&mut line_split, "vertex coordinate").map_err(located)?;
                 }
-                if &line[..2] == "f " {
-                    let mut line_split = line.split_whitespace();
-                    let mut face: [usize; 3] = [0, 0, 0];
-                    line_split.next();
-                    for i in 0..3 {
-                        let cur = line_split
-                            .next()
-                            .ok_or(anyhow::Error::msg("Expected value"))?;
-                        let mut cur_split = cur.split('/');
-                        let ind = cur_split
-                            .next()
-                            .ok_or(anyhow::Error::msg("Expected value"))?;
-                        face[i] = ind.parse::<usize>()? - 1;
-                    }
 
-                    mesh.add_face(face[0], face[1], face[2])?;
+                mesh.add_vertex(&vert);
+            }
+            Some("f") => {
+                let mut face = Vec::new();
+                for cur in line_split {
+                    face.push(obj_vertex_index(cur, mesh.nb_vertices()).map_err(located)?);
                 }
+
+                add_polygon(&mut mesh, ind_face, &face, triangulation).map_err(located)?;
+                ind_face += 1;
             }
+            _ => (),
         }
     }
 
     Ok(mesh)
 }
 
-/// Loads off file as manifold mesh
-pub fn load_off_manifold(filename: &str) -> Result<ManifoldMesh3D> {
-    let mut mesh = ManifoldMesh3D::new();
+/// Reads obj content as manifold mesh
+///
+/// Polygonal faces are triangulated (fan triangulation if no method is given)
+pub fn read_obj_manifold<T: Real, R: BufRead>(
+    reader: R,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<ManifoldMesh3D<T>, LoadError> {
+    read_obj(reader, ManifoldMesh3D::new(), opt_triangulation)
+}
+
+/// Reads obj content as non manifold mesh (triangle soup)
+///
+/// Polygonal faces are triangulated (fan triangulation if no method is given)
+pub fn read_obj_generic<T: Real, R: BufRead>(
+    reader: R,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<GenericMesh3D<T>, LoadError> {
+    read_obj(reader, GenericMesh3D::new(), opt_triangulation)
+}
+
+/// Loads obj file as manifold mesh
+///
+/// Polygonal faces are triangulated (fan triangulation if no method is given)
+pub fn load_obj_manifold<T: Real>(
+    filename: &str,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<ManifoldMesh3D<T>, LoadError> {
+    let file = File::open(filename).map_err(|err| LoadError::from(err).in_file(filename))?;
+    read_obj_manifold(io::BufReader::new(file), opt_triangulation)
+        .map_err(|err| err.in_file(filename))
+}
+
+/// Loads obj file as non manifold mesh (triangle soup)
+///
+/// Polygonal faces are triangulated (fan triangulation if no method is given)
+pub fn load_obj_generic<T: Real>(
+    filename: &str,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<GenericMesh3D<T>, LoadError> {
+    let file = File::open(filename).map_err(|err| LoadError::from(err).in_file(filename))?;
+    read_obj_generic(io::BufReader::new(file), opt_triangulation)
+        .map_err(|err| err.in_file(filename))
+}
+
+/// Reads feature edges of obj content, given as polylines (`l` lines)
+///
+/// Vertex indices of the returned edges start from 0, as in the loaded mesh
+pub fn read_obj_feature_edges<R: BufRead>(
+    reader: R,
+) -> std::result::Result<Vec<[usize; 2]>, LoadError> {
+    let mut edges = Vec::new();
+    let mut nb_vertices = 0;
+    for (ind_line, line_) in reader.lines().enumerate() {
+        let located = |err: LoadError| err.at_line(ind_line + 1);
+        let line = line_.map_err(|err| located(LoadError::from(err)))?;
+        let mut line_split = line.split_whitespace();
+        match line_split.next() {
+            Some("v") => nb_vertices += 1,
+            Some("l") => {
+                let mut polyline = Vec::new();
+                for cur in line_split {
+                    polyline.push(obj_vertex_index(cur, nb_vertices).map_err(located)?);
+                }
+                edges.extend(polyline.windows(2).map(|win| [win[0], win[1]]));
+            }
+            _ => (),
+        }
+    }
+
+    Ok(edges)
+}
 
-    let file = File::open(filename)?;
-    let lines = io::BufReader::new(file).lines();
-    let mut opt_nb_vert = None;
-    let mut opt_nb_face = None;
+/// Loads feature edges of obj file, given as polylines (`l` lines)
+///
+/// Vertex indices of the returned edges start from 0, as in the loaded mesh
+pub fn load_obj_feature_edges(filename: &str) -> std::result::Result<Vec<[usize; 2]>, LoadError> {
+    let file = File::open(filename).map_err(|err| LoadError::from(err).in_file(filename))?;
+    read_obj_feature_edges(io::BufReader::new(file)).map_err(|err| err.in_file(filename))
+}
+
+/// Reads feature edges content
+///
+/// One edge per line, given by its two vertex indices (starting from 0), lines starting with #
+/// are ignored
+pub fn read_feature_edges<R: BufRead>(
+    reader: R,
+) -> std::result::Result<Vec<[usize; 2]>, LoadError> {
+    let mut edges = Vec::new();
+    for (ind_line, line_) in reader.lines().enumerate() {
+        let located = |err: LoadError| err.at_line(ind_line + 1);
+        let line = line_.map_err(|err| located(LoadError::from(err)))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut line_split = line.split_whitespace();
+        let ind1 = parse_next(&mut line_split, "vertex index").map_err(located)?;
+        let ind2 = parse_next(&mut line_split, "vertex index").map_err(located)?;
+        edges.push([ind1, ind2]);
+    }
+
+    Ok(edges)
+}
+
+/// Loads feature edges file (see read_feature_edges)
+pub fn load_feature_edges(filename: &str) -> std::result::Result<Vec<[usize; 2]>, LoadError> {
+    let file = File::open(filename).map_err(|err| LoadError::from(err).in_file(filename))?;
+    read_feature_edges(io::BufReader::new(file)).map_err(|err| err.in_file(filename))
+}
+
+fn read_off<T: Real, M: MeshBuilder<T>, R: BufRead>(
+    reader: R,
+    mut mesh: M,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<M, LoadError> {
+    let triangulation = opt_triangulation.unwrap_or(PolygonTriangulation::Fan);
+
+    let mut opt_nb_vert_face = None;
     let mut cur_vert = 0;
     let mut cur_face = 0;
-    for line_ in lines {
-        if let Ok(line) = line_ {
-            if opt_nb_vert.is_none() {
-                if line == "OFF" {
-                    continue;
+    for (ind_line, line_) in reader.lines().enumerate() {
+        let located = |err: LoadError| err.at_line(ind_line + 1);
+        let line = line_.map_err(|err| located(LoadError::from(err)))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut line_split = line.split_whitespace();
+        if let Some((nb_vert, nb_face)) = opt_nb_vert_face {
+            if cur_vert < nb_vert {
+                let mut vert: Vector3<T> = Vector3::zeros();
+                for i in 0..3 {
+                    vert[i] = parse_next(&mut line_split, "vertex coordinate").map_err(located)?;
                 }
-                let mut line_split = line.split_whitespace();
-                let nb_vert = line_split
-                    .next()
-                    .ok_or(anyhow::Error::msg("Expected value1"))?
-                    .parse::<usize>()?;
-                opt_nb_vert = Some(nb_vert);
-                let nb_face = line_split
-                    .next()
-                    .ok_or(anyhow::Error::msg("Expected value2"))?
-                    .parse::<usize>()?;
-                opt_nb_face = Some(nb_face);
-            } else {
-                let nb_vert = opt_nb_vert.unwrap();
-                let nb_face = opt_nb_face.unwrap();
-                if cur_vert < nb_vert {
-                    let mut line_split = line.split_whitespace();
-                    let mut vert: Vector3<f32> = Vector3::new(0.0, 0.0, 0.0);
-                    for i in 0..3 {
-                        let ind = line_split
-                            .next()
-                            .ok_or(anyhow::Error::msg("Expected value3"))?
-                            .parse::<f32>()?;
-                        vert[i] = ind;
-                    }
 
-                    mesh.add_vertex(&vert);
-                    cur_vert = cur_vert + 1;
-                } else if cur_vert < nb_face {
-                    let mut line_split = line.split_whitespace();
-                    let mut face = Vec::new();
-                    let nbv = line_split
-                        .next()
-                        .ok_or(anyhow::Error::msg("Expected value4"))?
-                        .parse::<usize>()?;
-                    for _ in 0..nbv {
-                        let ind = line_split
-                            .next()
-                            .ok_or(anyhow::Error::msg("Expected value5"))?
-                            .parse::<usize>()?;
-                        face.push(ind);
-                    }
-                    if face.len() != 3 {
-                        return Err(anyhow::Error::msg("Face with more than 3 vertices"));
+                mesh.add_vertex(&vert);
+                cur_vert += 1;
+            } else if cur_face < nb_face {
+                let nbv: usize = parse_next(&mut line_split, "face size").map_err(located)?;
+                let mut face = Vec::new();
+                for _ in 0..nbv {
+                    let ind: usize =
+                        parse_next(&mut line_split, "vertex index").map_err(located)?;
+                    face.push(ind);
+                }
+                add_polygon(&mut mesh, cur_face, &face, triangulation).map_err(located)?;
+                cur_face += 1;
+            }
+        } else {
+            if line == "OFF" {
+                continue;
+            }
+            let nb_vert: usize = parse_next(&mut line_split, "vertex number").map_err(located)?;
+            let nb_face: usize = parse_next(&mut line_split, "face number").map_err(located)?;
+            opt_nb_vert_face = Some((nb_vert, nb_face));
+        }
+    }
+
+    Ok(mesh)
+}
+
+/// Reads off content as manifold mesh
+///
+/// Polygonal faces are triangulated (fan triangulation if no method is given)
+pub fn read_off_manifold<T: Real, R: BufRead>(
+    reader: R,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<ManifoldMesh3D<T>, LoadError> {
+    read_off(reader, ManifoldMesh3D::new(), opt_triangulation)
+}
+
+/// Reads off content as non manifold mesh (triangle soup)
+///
+/// Polygonal faces are triangulated (fan triangulation if no method is given)
+pub fn read_off_generic<T: Real, R: BufRead>(
+    reader: R,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<GenericMesh3D<T>, LoadError> {
+    read_off(reader, GenericMesh3D::new(), opt_triangulation)
+}
+
+/// Loads off file as manifold mesh
+///
+/// Polygonal faces are triangulated (fan triangulation if no method is given)
+pub fn load_off_manifold<T: Real>(
+    filename: &str,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<ManifoldMesh3D<T>, LoadError> {
+    let file = File::open(filename).map_err(|err| LoadError::from(err).in_file(filename))?;
+    read_off_manifold(io::BufReader::new(file), opt_triangulation)
+        .map_err(|err| err.in_file(filename))
+}
+
+/// Loads off file as non manifold mesh (triangle soup)
+///
+/// Polygonal faces are triangulated (fan triangulation if no method is given)
+pub fn load_off_generic<T: Real>(
+    filename: &str,
+    opt_triangulation: Option<PolygonTriangulation>,
+) -> std::result::Result<GenericMesh3D<T>, LoadError> {
+    let file = File::open(filename).map_err(|err| LoadError::from(err).in_file(filename))?;
+    read_off_generic(io::BufReader::new(file), opt_triangulation)
+        .map_err(|err| err.in_file(filename))
+}
+
+/// Merges vertices closer than a given tolerance
+pub(crate) struct VertexWelder<T> {
+    tolerance: T,
+    pub(crate) vertices: Vec<Vector3<T>>,
+    cells: HashMap<[i64; 3], Vec<usize>>,
+}
+
+impl<T: Real> VertexWelder<T> {
+    pub(crate) fn new(tolerance: Option<T>) -> VertexWelder<T> {
+        VertexWelder {
+            tolerance: tolerance.unwrap_or(T::zero()).max(T::zero()),
+            vertices: Vec::new(),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, vert: &Vector3<T>) -> [i64; 3] {
+        if self.tolerance == T::zero() {
+            // -0.0 and 0.0 share a cell
+            let bits = |val: T| {
+                let val = val.to_f64();
+                if val == 0.0 {
+                    0
+                } else {
+                    val.to_bits() as i64
+                }
+            };
+            [bits(vert[0]), bits(vert[1]), bits(vert[2])]
+        } else {
+            [
+                (vert[0] / self.tolerance).floor().to_f64() as i64,
+                (vert[1] / self.tolerance).floor().to_f64() as i64,
+                (vert[2] / self.tolerance).floor().to_f64() as i64,
+            ]
+        }
+    }
+
+    /// Gets index of an existing vertex close to the given one, or inserts it
+    pub(crate) fn weld(&mut self, vert: &Vector3<T>) -> usize {
+        let cell = self.cell(vert);
+        if self.tolerance == T::zero() {
+            if let Some(&ind) = self.cells.get(&cell).and_then(|inds| inds.first()) {
+                return ind;
+            }
+        } else {
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let neigh = [cell[0] + dx, cell[1] + dy, cell[2] + dz];
+                        if let Some(inds) = self.cells.get(&neigh) {
+                            for &ind in inds.iter() {
+                                if (self.vertices[ind] - vert).norm() <= self.tolerance {
+                                    return ind;
+                                }
+                            }
+                        }
                     }
-                    mesh.add_face(face[0], face[1], face[2])?;
-                    cur_face = cur_face + 1;
                 }
             }
         }
+        self.vertices.push(*vert);
+        let ind = self.vertices.len() - 1;
+        self.cells.entry(cell).or_default().push(ind);
+        ind
+    }
+}
+
+fn read_stl_ascii<T: Real>(content: &str) -> std::result::Result<Vec<[Vector3<T>; 3]>, LoadError> {
+    let mut triangles = Vec::new();
+    let mut loop_verts = Vec::new();
+    let mut in_loop = false;
+    for (ind_line, line) in content.lines().enumerate() {
+        let located = |err: LoadError| err.at_line(ind_line + 1);
+        let mut line_split = line.split_whitespace();
+        match line_split.next() {
+            Some("outer") => {
+                in_loop = true;
+                loop_verts.clear();
+            }
+            Some("vertex") => {
+                if !in_loop {
+                    return Err(located(LoadError::parse("Vertex outside of facet loop")));
+                }
+                let mut vert: Vector3<T> = Vector3::zeros();
+                for i in 0..3 {
+                    vert[i] = parse_next(&mut line_split, "vertex coordinate").map_err(located)?;
+                }
+                loop_verts.push(vert);
+            }
+            Some("endloop") => {
+                if loop_verts.len() != 3 {
+                    return Err(located(LoadError::parse(
+                        "Facet without exactly 3 vertices",
+                    )));
+                }
+                triangles.push([loop_verts[0], loop_verts[1], loop_verts[2]]);
+                in_loop = false;
+            }
+            _ => (),
+        }
+    }
+    Ok(triangles)
+}
+
+fn read_stl_binary<T: Real>(
+    content: &[u8],
+) -> std::result::Result<Vec<[Vector3<T>; 3]>, LoadError> {
+    if content.len() < 84 {
+        return Err(LoadError::parse("Binary stl file too short"));
+    }
+    let nb_tri = u32::from_le_bytes([content[80], content[81], content[82], content[83]]) as usize;
+    if content.len() < 84 + 50 * nb_tri {
+        return Err(LoadError::parse("Binary stl file truncated"));
+    }
+
+    let read_f32 = |offset: usize| {
+        f32::from_le_bytes([
+            content[offset],
+            content[offset + 1],
+            content[offset + 2],
+            content[offset + 3],
+        ])
+    };
+
+    let mut triangles = Vec::with_capacity(nb_tri);
+    for t in 0..nb_tri {
+        // skipping normal
+        let offset = 84 + 50 * t + 12;
+        let mut tri = [Vector3::zeros(); 3];
+        for (v, vert) in tri.iter_mut().enumerate() {
+            for i in 0..3 {
+                vert[i] = nalgebra::convert(read_f32(offset + 12 * v + 4 * i) as f64);
+            }
+        }
+        triangles.push(tri);
+    }
+    Ok(triangles)
+}
+
+fn is_stl_binary(content: &[u8]) -> bool {
+    if content.len() >= 84 {
+        let nb_tri =
+            u32::from_le_bytes([content[80], content[81], content[82], content[83]]) as usize;
+        if content.len() == 84 + 50 * nb_tri {
+            return true;
+        }
+    }
+    let start = content
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(content.len());
+    !content[start..].starts_with(b"solid")
+}
+
+fn read_stl<T: Real, M: MeshBuilder<T>, R: Read>(
+    mut reader: R,
+    mut mesh: M,
+    tolerance: Option<T>,
+) -> std::result::Result<M, LoadError> {
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content)?;
+
+    let triangles = if is_stl_binary(&content) {
+        read_stl_binary(&content)?
+    } else {
+        let text = std::str::from_utf8(&content)
+            .map_err(|_| LoadError::parse("Invalid utf8 in ascii stl file"))?;
+        read_stl_ascii(text)?
+    };
+
+    let mut welder = VertexWelder::new(tolerance);
+    let faces: Vec<[usize; 3]> = triangles
+        .iter()
+        .map(|tri| {
+            [
+                welder.weld(&tri[0]),
+                welder.weld(&tri[1]),
+                welder.weld(&tri[2]),
+            ]
+        })
+        .collect();
+
+    for vert in welder.vertices.iter() {
+        mesh.add_vertex(vert);
+    }
+    for (ind_face, face) in faces.iter().enumerate() {
+        // facets collapsed by welding are dropped
+        if face[0] == face[1] || face[1] == face[2] || face[2] == face[0] {
+            continue;
+        }
+        mesh.add_triangle(face[0], face[1], face[2])
+            .map_err(|err| {
+                LoadError::new(LoadErrorKind::InvalidFace {
+                    face: ind_face,
+                    vertices: face.to_vec(),
+                    cause: err.to_string(),
+                })
+            })?;
     }
 
     Ok(mesh)
 }
 
-/// Save manifold mesh as obj file
-pub fn save_obj_manifold(
+/// Reads stl content (ascii or binary) as manifold mesh
+///
+/// Facet vertices closer than the given tolerance are welded together (exact matching if None)
+pub fn read_stl_manifold<T: Real, R: Read>(
+    reader: R,
+    tolerance: Option<T>,
+) -> std::result::Result<ManifoldMesh3D<T>, LoadError> {
+    read_stl(reader, ManifoldMesh3D::new(), tolerance)
+}
+
+/// Reads stl content (ascii or binary) as non manifold mesh (triangle soup)
+///
+/// Facet vertices closer than the given tolerance are welded together (exact matching if None)
+pub fn read_stl_generic<T: Real, R: Read>(
+    reader: R,
+    tolerance: Option<T>,
+) -> std::result::Result<GenericMesh3D<T>, LoadError> {
+    read_stl(reader, GenericMesh3D::new(), tolerance)
+}
+
+/// Loads stl file (ascii or binary) as manifold mesh
+///
+/// Facet vertices closer than the given tolerance are welded together (exact matching if None)
+pub fn load_stl_manifold<T: Real>(
     filename: &str,
-    mesh: &ManifoldMesh3D,
+    tolerance: Option<T>,
+) -> std::result::Result<ManifoldMesh3D<T>, LoadError> {
+    let file = File::open(filename).map_err(|err| LoadError::from(err).in_file(filename))?;
+    read_stl_manifold(io::BufReader::new(file), tolerance).map_err(|err| err.in_file(filename))
+}
+
+/// Loads stl file (ascii or binary) as non manifold mesh (triangle soup)
+///
+/// Facet vertices closer than the given tolerance are welded together (exact matching if None)
+pub fn load_stl_generic<T: Real>(
+    filename: &str,
+    tolerance: Option<T>,
+) -> std::result::Result<GenericMesh3D<T>, LoadError> {
+    let file = File::open(filename).map_err(|err| LoadError::from(err).in_file(filename))?;
+    read_stl_generic(io::BufReader::new(file), tolerance).map_err(|err| err.in_file(filename))
+}
+
+/// Reads ply content (ascii or binary) as manifold mesh
+///
+/// Face labels are restored as mesh groups
+pub fn read_ply_manifold<T: Real, R: BufRead>(
+    mut reader: R,
+) -> std::result::Result<ManifoldMesh3D<T>, LoadError> {
+    let ply_data = ply::read_ply(&mut reader)?;
+
+    let mut mesh = ManifoldMesh3D::new();
+
+    let vertex_elem = ply_data
+        .element("vertex")
+        .ok_or(LoadError::parse("Expected vertex element"))?;
+    let mut ind_coords = [0; 3];
+    for (i, name) in ["x", "y", "z"].iter().enumerate() {
+        ind_coords[i] = vertex_elem
+            .property_index(&[name])
+            .ok_or(LoadError::parse("Expected vertex coordinate"))?;
+    }
+    for (ind_row, row) in vertex_elem.rows.iter().enumerate() {
+        let located = |err: LoadError| match vertex_elem.row_line(ind_row) {
+            Some(line) => err.at_line(line),
+            None => err,
+        };
+        let mut vert: Vector3<T> = Vector3::zeros();
+        for i in 0..3 {
+            vert[i] = nalgebra::convert(row[ind_coords[i]].scalar().map_err(located)?);
+        }
+        mesh.add_vertex(&vert);
+    }
+
+    if let Some(face_elem) = ply_data.element("face") {
+        let ind_vertices = face_elem
+            .property_index(&["vertex_index", "vertex_indices"])
+            .ok_or(LoadError::parse("Expected face vertex indices"))?;
+        let opt_ind_label = face_elem.property_index(&["label"]);
+        let opt_unlabelled = ply_data.comment_value(ply::UNLABELLED_COMMENT);
+        for (ind_row, row) in face_elem.rows.iter().enumerate() {
+            let located = |err: LoadError| match face_elem.row_line(ind_row) {
+                Some(line) => err.at_line(line),
+                None => err,
+            };
+            let face: Vec<usize> = row[ind_vertices]
+                .list()
+                .map_err(located)?
+                .iter()
+                .map(|&ind| ind as usize)
+                .collect();
+            let invalid_face = |cause: String| {
+                located(LoadError::new(LoadErrorKind::InvalidFace {
+                    face: ind_row,
+                    vertices: face.clone(),
+                    cause,
+                }))
+            };
+            if face.len() != 3 {
+                return Err(invalid_face("not a triangle".to_string()));
+            }
+            let ind_face = mesh
+                .add_face(face[0], face[1], face[2])
+                .map_err(|err| invalid_face(err.to_string()))?;
+            if let Some(ind_label) = opt_ind_label {
+                let label = row[ind_label].scalar().map_err(located)? as usize;
+                if opt_unlabelled != Some(label) {
+                    mesh.set_face_in_group(ind_face, label);
+                }
+            }
+        }
+    }
+
+    Ok(mesh)
+}
+
+/// Loads ply file (ascii or binary) as manifold mesh
+///
+/// Face labels are restored as mesh groups
+pub fn load_ply_manifold<T: Real>(
+    filename: &str,
+) -> std::result::Result<ManifoldMesh3D<T>, LoadError> {
+    let file = File::open(filename).map_err(|err| LoadError::from(err).in_file(filename))?;
+    read_ply_manifold(io::BufReader::new(file)).map_err(|err| err.in_file(filename))
+}
+
+/// Data lines of tetgen content, without comments, with their line numbers
+fn tetgen_lines<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = std::result::Result<(usize, String), LoadError>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(|(ind_line, line_)| match line_ {
+            Ok(line) => {
+                let data = line.split('#').next().unwrap_or("").trim();
+                if data.is_empty() {
+                    None
+                } else {
+                    Some(Ok((ind_line + 1, data.to_string())))
+                }
+            }
+            Err(err) => Some(Err(LoadError::from(err).at_line(ind_line + 1))),
+        })
+}
+
+/// Reads tetgen .node content
+///
+/// Returns the points and the index of the first one (0 or 1), used by the .ele content
+pub fn read_tetgen_node<T: Real, R: BufRead>(
+    reader: R,
+) -> std::result::Result<(Vec<Vector3<T>>, usize), LoadError> {
+    let mut points = Vec::new();
+    let mut opt_nb_points = None;
+    let mut first_index = 0;
+    for line_ in tetgen_lines(reader) {
+        let (ind_line, line) = line_?;
+        let located = |err: LoadError| err.at_line(ind_line);
+        let mut line_split = line.split_whitespace();
+        if let Some(nb_points) = opt_nb_points {
+            if points.len() == nb_points {
+                break;
+            }
+            let ind: usize = parse_next(&mut line_split, "point index").map_err(located)?;
+            if points.is_empty() {
+                first_index = ind;
+            }
+            if ind != first_index + points.len() {
+                return Err(located(LoadError::parse(&format!(
+                    "Point index {} out of order",
+                    ind
+                ))));
+            }
+            let mut point: Vector3<T> = Vector3::zeros();
+            for i in 0..3 {
+                point[i] = parse_next(&mut line_split, "point coordinate").map_err(located)?;
+            }
+            points.push(point);
+        } else {
+            let nb_points: usize = parse_next(&mut line_split, "point number").map_err(located)?;
+            let dim: usize = parse_next(&mut line_split, "dimension").map_err(located)?;
+            if dim != 3 {
+                return Err(located(LoadError::parse(&format!(
+                    "Dimension {} not handled",
+                    dim
+                ))));
+            }
+            opt_nb_points = Some(nb_points);
+        }
+    }
+
+    if points.len() != opt_nb_points.unwrap_or(0) {
+        return Err(LoadError::parse("Missing points"));
+    }
+
+    Ok((points, first_index))
+}
+
+/// Reads tetgen .ele content
+///
+/// Node indices are shifted by the first point index of the .node content, to start from 0
+pub fn read_tetgen_ele<R: BufRead>(
+    reader: R,
+    first_index: usize,
+    nb_points: usize,
+) -> std::result::Result<Vec<[usize; 4]>, LoadError> {
+    let mut tetrahedra = Vec::new();
+    let mut opt_nb_tetrahedra = None;
+    for line_ in tetgen_lines(reader) {
+        let (ind_line, line) = line_?;
+        let located = |err: LoadError| err.at_line(ind_line);
+        let mut line_split = line.split_whitespace();
+        if let Some(nb_tetrahedra) = opt_nb_tetrahedra {
+            if tetrahedra.len() == nb_tetrahedra {
+                break;
+            }
+            let _ind: usize = parse_next(&mut line_split, "tetrahedron index").map_err(located)?;
+            let mut tetra = [0; 4];
+            for node in tetra.iter_mut() {
+                let ind: usize = parse_next(&mut line_split, "node index").map_err(located)?;
+                if ind < first_index || ind - first_index >= nb_points {
+                    return Err(located(LoadError::new(LoadErrorKind::InvalidVertexIndex(
+                        ind as isize,
+                    ))));
+                }
+                *node = ind - first_index;
+            }
+            tetrahedra.push(tetra);
+        } else {
+            let nb_tetrahedra: usize =
+                parse_next(&mut line_split, "tetrahedron number").map_err(located)?;
+            // quadratic tetrahedra start with their 4 corners
+            let nb_nodes: usize = parse_next(&mut line_split, "node number").map_err(located)?;
+            if nb_nodes < 4 {
+                return Err(located(LoadError::parse(&format!(
+                    "Tetrahedra with {} nodes not handled",
+                    nb_nodes
+                ))));
+            }
+            opt_nb_tetrahedra = Some(nb_tetrahedra);
+        }
+    }
+
+    if tetrahedra.len() != opt_nb_tetrahedra.unwrap_or(0) {
+        return Err(LoadError::parse("Missing tetrahedra"));
+    }
+
+    Ok(tetrahedra)
+}
+
+/// Loads tetrahedralization of a mesh from tetgen files basename.node and basename.ele
+///
+/// The points must be the mesh vertices in vertex index order (as saved by save_tetgen),
+/// tetrahedra are returned as vertex indices
+pub fn load_tetgen<T: Real>(
+    basename: &str,
+    mesh: &ManifoldMesh3D<T>,
+) -> std::result::Result<Vec<[usize; 4]>, LoadError> {
+    let node_filename = format!("{}.node", basename);
+    let file =
+        File::open(&node_filename).map_err(|err| LoadError::from(err).in_file(&node_filename))?;
+    let (points, first_index) = read_tetgen_node::<T, _>(io::BufReader::new(file))
+        .map_err(|err| err.in_file(&node_filename))?;
+    let vertex_inds = mesh.vertex_indices();
+    if points.len() != vertex_inds.len() {
+        return Err(LoadError::parse(&format!(
+            "{} points for {} mesh vertices",
+            points.len(),
+            vertex_inds.len()
+        ))
+        .in_file(&node_filename));
+    }
+    for (ind, (point, &ind_vertex)) in points.iter().zip(vertex_inds.iter()).enumerate() {
+        if !mesh
+            .get_vertex(ind_vertex)
+            .is_ok_and(|vertex| vertex.vertex() == *point)
+        {
+            return Err(LoadError::parse(&format!(
+                "Point {} does not match mesh vertex",
+                ind + first_index
+            ))
+            .in_file(&node_filename));
+        }
+    }
+
+    let ele_filename = format!("{}.ele", basename);
+    let file =
+        File::open(&ele_filename).map_err(|err| LoadError::from(err).in_file(&ele_filename))?;
+    let tetrahedra = read_tetgen_ele(io::BufReader::new(file), first_index, points.len())
+        .map_err(|err| err.in_file(&ele_filename))?;
+
+    Ok(tetrahedra
+        .into_iter()
+        .map(|tetra| tetra.map(|node| vertex_inds[node]))
+        .collect())
+}
+
+/// Random color palette, reproducible if a seed is given
+pub(crate) fn random_colors(nb_colors: usize, opt_seed: Option<u64>) -> Vec<[u8; 3]> {
+    let mut rng = match opt_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut vec_col = Vec::new();
+    for _ in 0..nb_colors {
+        let rand_r = rng.gen_range(0..11) as f32;
+        let rand_g = rng.gen_range(0..11) as f32;
+        let rand_b = rng.gen_range(0..11) as f32;
+        let col_r = (255.0 * rand_r / 10.0) as u8;
+        let col_g = (255.0 * rand_g / 10.0) as u8;
+        let col_b = (255.0 * rand_b / 10.0) as u8;
+        vec_col.push([col_r, col_g, col_b]);
+    }
+    vec_col
+}
+
+/// Computes a random color per face label, the last one being used for unlabelled faces
+///
+/// Giving a seed makes the colors reproducible
+pub fn label_colors<T: Real>(mesh: &ManifoldMesh3D<T>, opt_seed: Option<u64>) -> Vec<[u8; 3]> {
+    let lab_max = mesh.groups.iter().fold(0, |lm, (_, opt_lab)| {
+        if let &Some(lab) = opt_lab {
+            if lm > lab {
+                lm
+            } else {
+                lab
+            }
+        } else {
+            lm
+        }
+    }) + 1;
+    random_colors(lab_max + 1, opt_seed)
+}
+
+/// Writes manifold mesh in obj format
+pub fn write_obj_manifold<T: Real, W: Write>(
+    file: &mut W,
+    mesh: &ManifoldMesh3D<T>,
     opt_material_file: Option<&str>,
 ) -> Result<()> {
-    let mut file = File::create(filename)?;
-
     if let Some(material_file) = opt_material_file {
         writeln!(file, "mtllib {}", material_file)?;
     }
@@ -146,9 +1018,12 @@ pub fn save_obj_manifold(
         writeln!(file, "v {} {} {}", vert[0], vert[1], vert[2])?;
     }
 
-    let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
     let mut non_grouped = Vec::new();
-    for (&ind_face, opt_lab) in mesh.groups.iter() {
+    let mut face_inds: Vec<usize> = mesh.groups.keys().copied().collect();
+    face_inds.sort();
+    for ind_face in face_inds {
+        let opt_lab = &mesh.groups[&ind_face];
         if let Some(lab) = opt_lab {
             groups
                 .entry(format!("sheet{}", lab))
@@ -191,14 +1066,23 @@ pub fn save_obj_manifold(
             writeln!(file, "f {}// {}// {}//", ind0, ind1, ind2)?;
         }
     }
+    file.flush()?;
 
     Ok(())
 }
 
-/// Save non manifold mesh as obj file
-pub fn save_obj_generic(filename: &str, mesh: &GenericMesh3D) -> Result<()> {
-    let mut file = File::create(filename)?;
+/// Save manifold mesh as obj file
+pub fn save_obj_manifold<T: Real>(
+    filename: &str,
+    mesh: &ManifoldMesh3D<T>,
+    opt_material_file: Option<&str>,
+) -> Result<()> {
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_obj_manifold(&mut file, mesh, opt_material_file)
+}
 
+/// Writes non manifold mesh in obj format
+pub fn write_obj_generic<T: Real, W: Write>(file: &mut W, mesh: &GenericMesh3D<T>) -> Result<()> {
     for v in 0..mesh.get_nb_vertices() {
         let vert = mesh.get_vertex(v)?;
         writeln!(file, "v {} {} {}", vert[0], vert[1], vert[2])?;
@@ -214,95 +1098,390 @@ pub fn save_obj_generic(filename: &str, mesh: &GenericMesh3D) -> Result<()> {
             face[2] + 1
         )?;
     }
+    file.flush()?;
 
     Ok(())
 }
 
+/// Save non manifold mesh as obj file
+pub fn save_obj_generic<T: Real>(filename: &str, mesh: &GenericMesh3D<T>) -> Result<()> {
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_obj_generic(&mut file, mesh)
+}
+
+fn write_ply_manifold_format<T: Real, W: Write>(
+    writer: &mut W,
+    mesh: &ManifoldMesh3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+    format: PlyFormat,
+) -> Result<Vec<[u8; 3]>> {
+    let vec_col = colors.unwrap_or_else(|| label_colors(mesh, None));
+
+    let ty_coord = PlyType::float_for::<T>();
+    let ty_label = PlyType::unsigned_for(vec_col.len() - 1);
+    let comments = [format!("{} {}", ply::UNLABELLED_COMMENT, vec_col.len() - 1)];
+    let elements = [
+        PlyElement::new(
+            "vertex",
+            mesh.vertices.len(),
+            vec![
+                PlyProperty::Scalar("x".to_string(), ty_coord),
+                PlyProperty::Scalar("y".to_string(), ty_coord),
+                PlyProperty::Scalar("z".to_string(), ty_coord),
+            ],
+        ),
+        PlyElement::new(
+            "face",
+            mesh.faces.len(),
+            vec![
+                PlyProperty::List("vertex_index".to_string(), PlyType::UChar, PlyType::Int),
+                PlyProperty::Scalar("label".to_string(), ty_label),
+                PlyProperty::Scalar("red".to_string(), PlyType::UChar),
+                PlyProperty::Scalar("green".to_string(), PlyType::UChar),
+                PlyProperty::Scalar("blue".to_string(), PlyType::UChar),
+            ],
+        ),
+    ];
+    ply::write_header(writer, format, &comments, &elements)?;
+
+    let mut corresp: HashMap<usize, usize> = HashMap::new();
+
+    for (cpt, v) in mesh.vertex_indices().into_iter().enumerate() {
+        let vert = mesh.get_vertex(v)?.vertex();
+        corresp.insert(v, cpt);
+        PlyRow::new()
+            .push(ty_coord, vert[0].to_f64())
+            .push(ty_coord, vert[1].to_f64())
+            .push(ty_coord, vert[2].to_f64())
+            .write(writer, format)?;
+    }
+
+    let mut face_inds: Vec<usize> = mesh.faces.keys().copied().collect();
+    face_inds.sort();
+    for fac_ind in face_inds {
+        let face = mesh.get_face(fac_ind)?.vertices_inds();
+        let face_inds: Vec<f64> = face.iter().map(|i| corresp[i] as f64).collect();
+        let lab = mesh.groups[&fac_ind].unwrap_or(vec_col.len() - 1);
+        PlyRow::new()
+            .push_list(PlyType::UChar, PlyType::Int, &face_inds)
+            .push(ty_label, lab as f64)
+            .push(PlyType::UChar, vec_col[lab][0] as f64)
+            .push(PlyType::UChar, vec_col[lab][1] as f64)
+            .push(PlyType::UChar, vec_col[lab][2] as f64)
+            .write(writer, format)?;
+    }
+    writer.flush()?;
+
+    Ok(vec_col)
+}
+
+/// Writes manifold mesh in ply format
+pub fn write_ply_manifold<T: Real, W: Write>(
+    writer: &mut W,
+    mesh: &ManifoldMesh3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+) -> Result<Vec<[u8; 3]>> {
+    write_ply_manifold_format(writer, mesh, colors, PlyFormat::Ascii)
+}
+
+/// Writes manifold mesh in binary (little endian) ply format
+pub fn write_ply_manifold_binary<T: Real, W: Write>(
+    writer: &mut W,
+    mesh: &ManifoldMesh3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+) -> Result<Vec<[u8; 3]>> {
+    write_ply_manifold_format(writer, mesh, colors, PlyFormat::BinaryLittleEndian)
+}
+
 /// Save manifold mesh as ply file
-pub fn save_ply_manifold(
+pub fn save_ply_manifold<T: Real>(
     filename: &str,
-    mesh: &ManifoldMesh3D,
+    mesh: &ManifoldMesh3D<T>,
     colors: Option<Vec<[u8; 3]>>,
 ) -> Result<Vec<[u8; 3]>> {
-    let mut file = File::create(filename)?;
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_ply_manifold(&mut file, mesh, colors)
+}
 
-    writeln!(file, "ply")?;
-    writeln!(file, "format ascii 1.0")?;
+/// Save manifold mesh as binary (little endian) ply file
+pub fn save_ply_manifold_binary<T: Real>(
+    filename: &str,
+    mesh: &ManifoldMesh3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+) -> Result<Vec<[u8; 3]>> {
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_ply_manifold_binary(&mut file, mesh, colors)
+}
 
-    writeln!(file, "element vertex {}", mesh.vertices.len())?;
-    writeln!(file, "property float x")?;
-    writeln!(file, "property float y")?;
-    writeln!(file, "property float z")?;
+/// Writes manifold mesh vertices in tetgen .node format
+///
+/// Points are numbered from 0 in vertex index order, as the vertices of the obj writer
+pub fn write_tetgen_node<T: Real, W: Write>(file: &mut W, mesh: &ManifoldMesh3D<T>) -> Result<()> {
+    writeln!(file, "{} 3 0 0", mesh.get_nb_vertices())?;
+    for (cpt, v) in mesh.vertex_indices().into_iter().enumerate() {
+        let vert = mesh.get_vertex(v)?.vertex();
+        writeln!(file, "{} {} {} {}", cpt, vert[0], vert[1], vert[2])?;
+    }
+    file.flush()?;
 
-    writeln!(file, "element face {}", mesh.faces.len())?;
-    writeln!(file, "property list uchar int vertex_index")?;
-    writeln!(file, "property uchar label")?;
-    writeln!(file, "property uchar red")?;
-    writeln!(file, "property uchar green")?;
-    writeln!(file, "property uchar blue")?;
+    Ok(())
+}
 
-    writeln!(file, "end_header")?;
+/// Writes tetrahedra in tetgen .ele format (indices starting from 0)
+pub fn write_tetgen_ele<W: Write>(file: &mut W, tetrahedra: &[[usize; 4]]) -> Result<()> {
+    writeln!(file, "{} 4 0", tetrahedra.len())?;
+    for (ind, tetra) in tetrahedra.iter().enumerate() {
+        writeln!(
+            file,
+            "{} {} {} {} {}",
+            ind, tetra[0], tetra[1], tetra[2], tetra[3]
+        )?;
+    }
+    file.flush()?;
 
-    let mut corresp: HashMap<usize, usize> = HashMap::new();
-    let mut cpt = 0;
+    Ok(())
+}
 
-    for v in mesh.vertex_indices() {
-        let vert = mesh.get_vertex(v)?.vertex();
-        corresp.insert(v, cpt);
-        cpt = cpt + 1;
-        writeln!(file, "{} {} {}", vert[0], vert[1], vert[2])?;
+/// Save manifold mesh vertices and their tetrahedralization as tetgen files basename.node and basename.ele
+///
+/// Tetrahedra are given as vertex indices, and renumbered as the points of the .node file
+pub fn save_tetgen<T: Real>(
+    basename: &str,
+    mesh: &ManifoldMesh3D<T>,
+    tetrahedra: &[[usize; 4]],
+) -> Result<()> {
+    let corresp: HashMap<usize, usize> = mesh
+        .vertex_indices()
+        .into_iter()
+        .enumerate()
+        .map(|(cpt, v)| (v, cpt))
+        .collect();
+    let mut tetrahedra_node = Vec::with_capacity(tetrahedra.len());
+    for tetra in tetrahedra.iter() {
+        let mut tetra_node = [0; 4];
+        for (node, ind_vertex) in tetra_node.iter_mut().zip(tetra.iter()) {
+            *node = *corresp.get(ind_vertex).ok_or(anyhow::Error::msg(
+                "save_tetgen(): tetrahedron vertex does not exist",
+            ))?;
+        }
+        tetrahedra_node.push(tetra_node);
     }
 
-    let vec_col = if let Some(col) = colors {
-        col
-    } else {
-        let lab_max = mesh.groups.iter().fold(0, |lm, (_, opt_lab)| {
-            if let &Some(lab) = opt_lab {
-                if lm > lab {
-                    lm
-                } else {
-                    lab
+    let mut file = io::BufWriter::new(File::create(format!("{}.node", basename))?);
+    write_tetgen_node(&mut file, mesh)?;
+    let mut file = io::BufWriter::new(File::create(format!("{}.ele", basename))?);
+    write_tetgen_ele(&mut file, &tetrahedra_node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh3d::mesh_operations;
+    use crate::mesh3d::test_meshes::jittered_bipyramid;
+
+    /// Path in the temporary directory, unique to the test process
+    fn temp_path(name: &str) -> String {
+        let filename = format!("compact_skel_3d_{}_{}", std::process::id(), name);
+        std::env::temp_dir()
+            .join(filename)
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn tetgen_round_trip_with_removed_vertices() {
+        let mut mesh = jittered_bipyramid();
+        let ind_he = mesh.is_edge_in(0, 2).unwrap().ind();
+        let vert = mesh.get_vertex(0).unwrap().vertex();
+        assert!(mesh_operations::collapse_halfedge(&mut mesh, &vert, ind_he).unwrap());
+        let vertex_inds = mesh.vertex_indices();
+        assert!(!vertex_inds.contains(&2));
+
+        // tetrahedra linking the faces to the bottom apex, given as vertex indices
+        let mut tetrahedra = Vec::new();
+        let mut face_inds: Vec<usize> = mesh.faces().keys().copied().collect();
+        face_inds.sort();
+        for ind_face in face_inds {
+            let [ind1, ind2, ind3] = mesh.get_face(ind_face).unwrap().vertices_inds();
+            if ![ind1, ind2, ind3].contains(&1) {
+                tetrahedra.push([1, ind1, ind2, ind3]);
+            }
+        }
+
+        let basename = temp_path("tetgen_round_trip");
+        let obj_filename = format!("{}.obj", basename);
+        save_obj_manifold(&obj_filename, &mesh, None).unwrap();
+        save_tetgen(&basename, &mesh, &tetrahedra).unwrap();
+
+        let tetrahedra_mesh = load_tetgen(&basename, &mesh).unwrap();
+        let mesh_obj: ManifoldMesh3D<f64> = load_obj_manifold(&obj_filename, None).unwrap();
+        let tetrahedra_obj = load_tetgen(&basename, &mesh_obj).unwrap();
+        for filename in [obj_filename, basename.clone() + ".node", basename + ".ele"] {
+            std::fs::remove_file(filename).unwrap();
+        }
+
+        assert_eq!(tetrahedra_mesh, tetrahedra);
+        // the obj vertices are renumbered as the tetgen points
+        let tetrahedra_compact: Vec<[usize; 4]> = tetrahedra
+            .iter()
+            .map(|tetra| tetra.map(|v| vertex_inds.binary_search(&v).unwrap()))
+            .collect();
+        assert_eq!(tetrahedra_obj, tetrahedra_compact);
+    }
+
+    fn assert_same_mesh(mesh: &ManifoldMesh3D<f64>, other: &ManifoldMesh3D<f64>) {
+        assert_eq!(other.get_nb_vertices(), mesh.get_nb_vertices());
+        for (ind_vertex, &ind_other) in mesh
+            .vertex_indices()
+            .iter()
+            .zip(other.vertex_indices().iter())
+        {
+            assert_eq!(
+                other.get_vertex(ind_other).unwrap().vertex(),
+                mesh.get_vertex(*ind_vertex).unwrap().vertex()
+            );
+        }
+        assert_eq!(other.get_nb_faces(), mesh.get_nb_faces());
+        for &ind_face in mesh.faces().keys() {
+            assert_eq!(
+                other.get_face(ind_face).unwrap().vertices_inds(),
+                mesh.get_face(ind_face).unwrap().vertices_inds()
+            );
+            assert_eq!(other.groups[&ind_face], mesh.groups[&ind_face]);
+        }
+    }
+
+    #[test]
+    fn ply_round_trip() {
+        let mut mesh = jittered_bipyramid();
+        mesh.set_face_in_group(0, 0);
+        mesh.set_face_in_group(1, 3);
+        mesh.set_face_in_group(2, 3);
+
+        // more than 256 colors need a ushort label
+        for (label, ty_label) in [(5, "uchar"), (298, "ushort")] {
+            mesh.set_face_in_group(3, label);
+            for format in [
+                PlyFormat::Ascii,
+                PlyFormat::BinaryLittleEndian,
+                PlyFormat::BinaryBigEndian,
+            ] {
+                let mut content = Vec::new();
+                let vec_col = write_ply_manifold_format(&mut content, &mesh, None, format).unwrap();
+                assert_eq!(vec_col.len(), label + 2);
+                let header = String::from_utf8_lossy(&content);
+                assert!(header.contains(&format!("property {} label", ty_label)));
+                let mesh_ply = read_ply_manifold(content.as_slice()).unwrap();
+                assert_same_mesh(&mesh, &mesh_ply);
+            }
+        }
+    }
+
+    #[test]
+    fn ply_given_colors() {
+        let mesh = jittered_bipyramid();
+        let colors = vec![[10, 20, 30]; 2];
+        let mut content = Vec::new();
+        let vec_col = write_ply_manifold(&mut content, &mesh, Some(colors.clone())).unwrap();
+        assert_eq!(vec_col, colors);
+        let content = String::from_utf8(content).unwrap();
+        // unlabelled faces take the last color
+        assert!(content.contains("\n3 0 2 3 1 10 20 30\n"));
+    }
+
+    /// Facets of a unit tetrahedron, the origin being written as -0.0 in some of them
+    fn tetrahedron_facets() -> Vec<[[f32; 3]; 3]> {
+        let origin = [0.0, 0.0, 0.0];
+        let origin_neg = [-0.0, 0.0, -0.0];
+        let [x, y, z] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        vec![
+            [origin, y, x],
+            [origin_neg, x, z],
+            [origin, z, y],
+            [x, y, z],
+        ]
+    }
+
+    fn stl_ascii(facets: &[[[f32; 3]; 3]]) -> Vec<u8> {
+        let mut content = String::from("solid tetrahedron\n");
+        for facet in facets.iter() {
+            content += "  facet normal 0 0 0\n    outer loop\n";
+            for vert in facet.iter() {
+                content += &format!(
+                    "      vertex {:.6} {:.6} {:.6}\n",
+                    vert[0], vert[1], vert[2]
+                );
+            }
+            content += "    endloop\n  endfacet\n";
+        }
+        content += "endsolid tetrahedron\n";
+        content.into_bytes()
+    }
+
+    fn stl_binary(facets: &[[[f32; 3]; 3]]) -> Vec<u8> {
+        // header starting as an ascii file
+        let mut content = b"solid binary".to_vec();
+        content.resize(80, 0);
+        content.extend((facets.len() as u32).to_le_bytes());
+        for facet in facets.iter() {
+            content.extend([0; 12]);
+            for vert in facet.iter() {
+                for coord in vert.iter() {
+                    content.extend(coord.to_le_bytes());
                 }
-            } else {
-                lm
             }
-        }) + 1;
-        let mut vec_col = Vec::new();
-        let mut rng = rand::thread_rng();
-        for _ in 0..(lab_max + 1) {
-            let rand_r = rng.gen_range(0..11) as f32;
-            let rand_g = rng.gen_range(0..11) as f32;
-            let rand_b = rng.gen_range(0..11) as f32;
-            let col_r = (255.0 * rand_r / 10.0) as u8;
-            let col_g = (255.0 * rand_g / 10.0) as u8;
-            let col_b = (255.0 * rand_b / 10.0) as u8;
-            vec_col.push([col_r, col_g, col_b]);
-        }
-        vec_col
-    };
+            content.extend([0; 2]);
+        }
+        content
+    }
 
-    for (&fac_ind, _) in mesh.faces.iter() {
-        let face = mesh.get_face(fac_ind)?.vertices_inds();
-        let label = mesh.groups[&fac_ind];
-        write!(file, "{} ", face.len())?;
-        for i in face {
-            write!(file, "{} ", corresp[&i])?;
-        }
-        if let Some(lab) = label {
-            writeln!(
-                file,
-                "{} {} {} {}",
-                lab, vec_col[lab][0], vec_col[lab][1], vec_col[lab][2]
-            )?;
-        } else {
-            let lab = vec_col.len() - 1;
-            writeln!(
-                file,
-                "{} {} {} {}",
-                lab, vec_col[lab][0], vec_col[lab][1], vec_col[lab][2]
-            )?;
+    #[test]
+    fn stl_ascii_and_binary() {
+        let facets = tetrahedron_facets();
+        for content in [stl_ascii(&facets), stl_binary(&facets)] {
+            let mesh: ManifoldMesh3D<f64> = read_stl_manifold(content.as_slice(), None).unwrap();
+            assert_eq!(mesh.get_nb_vertices(), 4);
+            assert_eq!(mesh.get_nb_faces(), 4);
+            mesh.check_mesh().unwrap();
+            assert_eq!(mesh.get_vertex(0).unwrap().vertex(), Vector3::zeros());
+
+            let soup: GenericMesh3D<f64> = read_stl_generic(content.as_slice(), None).unwrap();
+            assert_eq!(soup.get_nb_vertices(), 4);
+            assert_eq!(soup.get_nb_faces(), 4);
         }
     }
 
-    Ok(vec_col)
+    #[test]
+    fn stl_errors() {
+        let facets = tetrahedron_facets();
+        let mut content = stl_binary(&facets);
+        content.truncate(content.len() - 10);
+        content[0] = b'x';
+        assert!(read_stl_manifold::<f64, _>(content.as_slice(), None).is_err());
+
+        let content =
+            b"solid\nfacet normal 0 0 0\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nendloop\n";
+        let err = read_stl_manifold::<f64, _>(content.as_slice(), None)
+            .err()
+            .unwrap();
+        assert_eq!(err.line, Some(6));
+    }
+
+    #[test]
+    fn welding() {
+        let mut welder = VertexWelder::new(None);
+        assert_eq!(welder.weld(&Vector3::new(0.0, 1.0, 0.0)), 0);
+        assert_eq!(welder.weld(&Vector3::new(-0.0, 1.0, -0.0)), 0);
+        assert_eq!(welder.weld(&Vector3::new(1e-12, 1.0, 0.0)), 1);
+
+        // close vertices in neighbor cells
+        let mut welder = VertexWelder::new(Some(0.01));
+        assert_eq!(welder.weld(&Vector3::new(0.0999, 0.0, -0.0)), 0);
+        assert_eq!(welder.weld(&Vector3::new(0.1001, 0.0, 0.0)), 0);
+        assert_eq!(welder.weld(&Vector3::new(0.0, 0.0, 0.0)), 1);
+        assert_eq!(welder.weld(&Vector3::new(0.1, 0.009, 0.0)), 0);
+        assert_eq!(welder.weld(&Vector3::new(0.1, 0.011, 0.0)), 2);
+        assert_eq!(welder.vertices.len(), 3);
+    }
 }
@@ -1,9 +1,11 @@
 use anyhow::Result;
 use nalgebra::base::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use crate::geometry::Real;
 
 /// Mesh vertex
-pub type Vertex = Vector3<f32>;
+pub type Vertex<T = f32> = Vector3<T>;
 /// Mesh halfedge
 pub type HalfEdge = [usize; 2];
 /// Mesh face (array of halfedges)
@@ -11,8 +13,8 @@ pub type FaceHalfedges = [usize; 3];
 
 #[derive(Clone)]
 /// Manifold mesh
-pub struct ManifoldMesh3D {
-    pub(super) vertices: HashMap<usize, Vertex>,
+pub struct ManifoldMesh3D<T = f32> {
+    pub(super) vertices: HashMap<usize, Vertex<T>>,
     pub(super) halfedges: HashMap<usize, HalfEdge>,
     pub(super) faces: HashMap<usize, FaceHalfedges>,
     pub(super) groups: HashMap<usize, Option<usize>>,
@@ -29,28 +31,28 @@ pub struct ManifoldMesh3D {
 
 #[derive(Copy, Clone)]
 /// Vertex iterator
-pub struct IterVertex<'a> {
-    mesh: &'a ManifoldMesh3D,
+pub struct IterVertex<'a, T = f32> {
+    mesh: &'a ManifoldMesh3D<T>,
     ind_vertex: usize,
 }
 
 #[derive(Copy, Clone)]
 /// Halfedge iterator
-pub struct IterHalfEdge<'a> {
-    mesh: &'a ManifoldMesh3D,
+pub struct IterHalfEdge<'a, T = f32> {
+    mesh: &'a ManifoldMesh3D<T>,
     ind_halfedge: usize,
 }
 
 #[derive(Copy, Clone)]
 /// Face iterator
-pub struct IterFace<'a> {
-    mesh: &'a ManifoldMesh3D,
+pub struct IterFace<'a, T = f32> {
+    mesh: &'a ManifoldMesh3D<T>,
     ind_face: usize,
 }
 
-impl ManifoldMesh3D {
+impl<T: Real> ManifoldMesh3D<T> {
     /// Manifold mesh constructor
-    pub fn new() -> ManifoldMesh3D {
+    pub fn new() -> ManifoldMesh3D<T> {
         ManifoldMesh3D {
             vertices: HashMap::new(),
             halfedges: HashMap::new(),
@@ -69,14 +71,14 @@ impl ManifoldMesh3D {
     }
 
     /// Adds a vertex to th mesh
-    pub fn add_vertex(&mut self, point: &Vector3<f32>) -> usize {
+    pub fn add_vertex(&mut self, point: &Vector3<T>) -> usize {
         self.vertices.insert(self.last_ind_vert, *point);
         self.map_vert_hedg.insert(self.last_ind_vert, Vec::new());
         self.last_ind_vert = self.last_ind_vert + 1;
         self.last_ind_vert - 1
     }
 
-    fn get_vertex_uncheck(&self, ind_vertex: usize) -> IterVertex {
+    fn get_vertex_uncheck(&self, ind_vertex: usize) -> IterVertex<T> {
         IterVertex {
             mesh: self,
             ind_vertex,
@@ -84,7 +86,7 @@ impl ManifoldMesh3D {
     }
 
     /// Vertex getter
-    pub fn get_vertex(&self, ind_vertex: usize) -> Result<IterVertex> {
+    pub fn get_vertex(&self, ind_vertex: usize) -> Result<IterVertex<T>> {
         if !self.vertices.contains_key(&ind_vertex) {
             return Err(anyhow::Error::msg("get_vertex(): Index out of bounds"));
         }
@@ -105,7 +107,7 @@ impl ManifoldMesh3D {
     }
 
     /// Gets vertex map
-    pub fn vertices(&self) -> &HashMap<usize, Vertex> {
+    pub fn vertices(&self) -> &HashMap<usize, Vertex<T>> {
         &self.vertices
     }
 
@@ -132,7 +134,7 @@ impl ManifoldMesh3D {
         self.last_ind_hedge - 1
     }
 
-    fn get_halfedge_uncheck(&self, ind_halfedge: usize) -> IterHalfEdge {
+    fn get_halfedge_uncheck(&self, ind_halfedge: usize) -> IterHalfEdge<T> {
         IterHalfEdge {
             mesh: self,
             ind_halfedge,
@@ -140,7 +142,7 @@ impl ManifoldMesh3D {
     }
 
     /// Halfedge getter
-    pub fn get_halfedge(&self, ind_halfedge: usize) -> Result<IterHalfEdge> {
+    pub fn get_halfedge(&self, ind_halfedge: usize) -> Result<IterHalfEdge<T>> {
         if !self.halfedges.contains_key(&ind_halfedge) {
             return Err(anyhow::Error::msg("get_halfedge(): Index out of bounds"));
         }
@@ -281,7 +283,7 @@ impl ManifoldMesh3D {
         Ok(())
     }
 
-    fn get_face_uncheck(&self, ind_face: usize) -> IterFace {
+    fn get_face_uncheck(&self, ind_face: usize) -> IterFace<T> {
         IterFace {
             mesh: self,
             ind_face,
@@ -289,7 +291,7 @@ impl ManifoldMesh3D {
     }
 
     /// Face getter
-    pub fn get_face(&self, ind_face: usize) -> Result<IterFace> {
+    pub fn get_face(&self, ind_face: usize) -> Result<IterFace<T>> {
         if !self.faces.contains_key(&ind_face) {
             return Err(anyhow::Error::msg("get_face(): Index out of bounds"));
         }
@@ -309,7 +311,7 @@ impl ManifoldMesh3D {
     /// Checks if an edge is in the mesh
     ///
     /// Returns halfedge iterator if found
-    pub fn is_edge_in(&self, ind_vertex1: usize, ind_vertex2: usize) -> Option<IterHalfEdge> {
+    pub fn is_edge_in(&self, ind_vertex1: usize, ind_vertex2: usize) -> Option<IterHalfEdge<T>> {
         if !self.vertices.contains_key(&ind_vertex1) || !self.vertices.contains_key(&ind_vertex2) {
             return None;
         } else {
@@ -331,7 +333,7 @@ impl ManifoldMesh3D {
         ind_vertex1: usize,
         ind_vertex2: usize,
         ind_vertex3: usize,
-    ) -> Option<IterFace> {
+    ) -> Option<IterFace<T>> {
         if !self.vertices.contains_key(&ind_vertex3) {
             return None;
         } else {
@@ -359,6 +361,44 @@ impl ManifoldMesh3D {
         None
     }
 
+    /// Groups faces per connected component (faces joined through edges)
+    ///
+    /// Components are sorted by decreasing number of faces, faces by increasing index
+    pub fn connected_components(&self) -> Vec<Vec<usize>> {
+        let mut face_inds: Vec<usize> = self.faces.keys().copied().collect();
+        face_inds.sort();
+
+        let mut visited = HashSet::new();
+        let mut components = Vec::new();
+        for &ind_start in face_inds.iter() {
+            if !visited.insert(ind_start) {
+                continue;
+            }
+            let mut component = vec![ind_start];
+            let mut ind_cur = 0;
+            while ind_cur < component.len() {
+                let ind_face = component[ind_cur];
+                ind_cur += 1;
+                for ind_hedge in self.faces[&ind_face].iter() {
+                    if let Some(&ind_neigh) = self
+                        .map_hedg_opp
+                        .get(ind_hedge)
+                        .and_then(|ind_opp| self.map_hedg_face.get(ind_opp))
+                    {
+                        if visited.insert(ind_neigh) {
+                            component.push(ind_neigh);
+                        }
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        // stable sort, components of same size stay ordered by first face
+        components.sort_by_key(|comp| std::cmp::Reverse(comp.len()));
+        components
+    }
+
     fn check_face(&self, ind_face: usize) -> Result<()> {
         let face = self.get_face(ind_face)?;
         // check edges existence
@@ -489,9 +529,9 @@ impl ManifoldMesh3D {
     }
 }
 
-impl<'a> IterVertex<'a> {
+impl<'a, T: Real> IterVertex<'a, T> {
     /// Gets vertex coordinates
-    pub fn vertex(&self) -> Vertex {
+    pub fn vertex(&self) -> Vertex<T> {
         *self.mesh.vertices.get(&self.ind_vertex).unwrap()
     }
 
@@ -501,7 +541,7 @@ impl<'a> IterVertex<'a> {
     }
 
     /// Gets list of halfedges starting at this vertex
-    pub fn halfedges(&self) -> Vec<IterHalfEdge<'a>> {
+    pub fn halfedges(&self) -> Vec<IterHalfEdge<'a, T>> {
         let vec_he = self
             .mesh
             .map_vert_hedg
@@ -519,7 +559,7 @@ impl<'a> IterVertex<'a> {
     }
 }
 
-impl<'a> IterHalfEdge<'a> {
+impl<'a, T: Real> IterHalfEdge<'a, T> {
     /// Gets halfedge (array of vertex indices)
     pub fn halfedge(&self) -> HalfEdge {
         *self.mesh.halfedges.get(&self.ind_halfedge).unwrap()
@@ -531,7 +571,7 @@ impl<'a> IterHalfEdge<'a> {
     }
 
     /// First vertex iterator
-    pub fn first_vertex(&self) -> IterVertex<'a> {
+    pub fn first_vertex(&self) -> IterVertex<'a, T> {
         IterVertex {
             mesh: self.mesh,
             ind_vertex: self.halfedge()[0],
@@ -539,7 +579,7 @@ impl<'a> IterHalfEdge<'a> {
     }
 
     /// Last vertex iterator
-    pub fn last_vertex(&self) -> IterVertex<'a> {
+    pub fn last_vertex(&self) -> IterVertex<'a, T> {
         IterVertex {
             mesh: self.mesh,
             ind_vertex: self.halfedge()[1],
@@ -547,7 +587,7 @@ impl<'a> IterHalfEdge<'a> {
     }
 
     /// Next halfedge on same face
-    pub fn next_halfedge(&self) -> Option<IterHalfEdge<'a>> {
+    pub fn next_halfedge(&self) -> Option<IterHalfEdge<'a, T>> {
         if let Some(&ind_next) = self.mesh.map_hedg_next.get(&self.ind_halfedge) {
             Some(IterHalfEdge {
                 mesh: self.mesh,
@@ -559,7 +599,7 @@ impl<'a> IterHalfEdge<'a> {
     }
 
     /// Previous halfedge on same face
-    pub fn prev_halfedge(&self) -> Option<IterHalfEdge<'a>> {
+    pub fn prev_halfedge(&self) -> Option<IterHalfEdge<'a, T>> {
         if let Some(&ind_prev) = self.mesh.map_hedg_prev.get(&self.ind_halfedge) {
             Some(IterHalfEdge {
                 mesh: self.mesh,
@@ -571,7 +611,7 @@ impl<'a> IterHalfEdge<'a> {
     }
 
     /// Opposite halfedge: Same vertices in opposite order (on neighbor face)
-    pub fn opposite_halfedge(&self) -> Option<IterHalfEdge<'a>> {
+    pub fn opposite_halfedge(&self) -> Option<IterHalfEdge<'a, T>> {
         if let Some(&ind_opp) = self.mesh.map_hedg_opp.get(&self.ind_halfedge) {
             Some(IterHalfEdge {
                 mesh: self.mesh,
@@ -583,7 +623,7 @@ impl<'a> IterHalfEdge<'a> {
     }
 
     /// Face containing halfedge
-    pub fn face(&self) -> Option<IterFace<'a>> {
+    pub fn face(&self) -> Option<IterFace<'a, T>> {
         if let Some(&ind_face) = self.mesh.map_hedg_face.get(&self.ind_halfedge) {
             Some(IterFace {
                 mesh: self.mesh,
@@ -595,7 +635,7 @@ impl<'a> IterHalfEdge<'a> {
     }
 }
 
-impl<'a> IterFace<'a> {
+impl<'a, T: Real> IterFace<'a, T> {
     /// Gets face (array of halfedge indices)
     pub fn face_halfedges(&self) -> FaceHalfedges {
         *self.mesh.faces.get(&self.ind_face).unwrap()
@@ -607,7 +647,7 @@ impl<'a> IterFace<'a> {
     }
 
     /// Surrounding halfedges (array of halfedge iterators)
-    pub fn halfedges(&self) -> [IterHalfEdge<'a>; 3] {
+    pub fn halfedges(&self) -> [IterHalfEdge<'a, T>; 3] {
         let &face = self.mesh.faces.get(&self.ind_face).unwrap();
 
         [
@@ -627,7 +667,7 @@ impl<'a> IterFace<'a> {
     }
 
     /// Surrouding vertices (array of vertex iterators)
-    pub fn vertices(&self) -> [IterVertex<'a>; 3] {
+    pub fn vertices(&self) -> [IterVertex<'a, T>; 3] {
         let he = self.halfedges();
 
         [
@@ -643,3 +683,27 @@ impl<'a> IterFace<'a> {
         [ve[0].ind(), ve[1].ind(), ve[2].ind()]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh3d::test_meshes;
+
+    #[test]
+    fn connected_components_of_two_shells() {
+        let mut mesh = test_meshes::two_shells();
+        // the small shell first in face order
+        let nb_large = test_meshes::jittered_bipyramid().get_nb_faces();
+        for ind_face in 0..nb_large {
+            let [ind1, ind2, ind3] = mesh.get_face(ind_face).unwrap().vertices_inds();
+            mesh.remove_face(ind_face).unwrap();
+            mesh.add_face(ind1, ind2, ind3).unwrap();
+        }
+
+        let components = mesh.connected_components();
+        assert_eq!(components.len(), 2);
+        // sorted by decreasing size
+        let large: Vec<usize> = (mesh.get_nb_faces()..mesh.get_nb_faces() + nb_large).collect();
+        let small: Vec<usize> = (nb_large..mesh.get_nb_faces()).collect();
+        assert_eq!(components, vec![large, small]);
+    }
+}
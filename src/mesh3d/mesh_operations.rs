@@ -1,9 +1,11 @@
+use crate::geometry::Real;
 use crate::mesh3d::ManifoldMesh3D;
 use anyhow::Result;
 use nalgebra::base::*;
+use std::collections::HashSet;
 
 /// Checks if a halfedge can be flipped
-pub fn can_flip_halfedge(mesh: &ManifoldMesh3D, ind_halfedge: usize) -> Result<bool> {
+pub fn can_flip_halfedge<T: Real>(mesh: &ManifoldMesh3D<T>, ind_halfedge: usize) -> Result<bool> {
     let halfedge = mesh.get_halfedge(ind_halfedge)?;
 
     let opp_vert1 = halfedge
@@ -40,7 +42,7 @@ pub fn can_flip_halfedge(mesh: &ManifoldMesh3D, ind_halfedge: usize) -> Result<b
 ///   \ | /         \   /
 ///     2             2
 /// ```
-pub fn flip_halfedge(mesh: &mut ManifoldMesh3D, ind_halfedge: usize) -> Result<bool> {
+pub fn flip_halfedge<T: Real>(mesh: &mut ManifoldMesh3D<T>, ind_halfedge: usize) -> Result<bool> {
     if !mesh.halfedges.contains_key(&ind_halfedge) {
         return Err(anyhow::Error::msg("flip_halfedge(): Index out of bounds"));
     }
@@ -96,9 +98,9 @@ pub fn flip_halfedge(mesh: &mut ManifoldMesh3D, ind_halfedge: usize) -> Result<b
 ///   \ | /         \ | /
 ///     2             2
 /// ```
-pub fn split_halfedge(
-    mesh: &mut ManifoldMesh3D,
-    vert: &Vector3<f32>,
+pub fn split_halfedge<T: Real>(
+    mesh: &mut ManifoldMesh3D<T>,
+    vert: &Vector3<T>,
     ind_halfedge: usize,
 ) -> Result<()> {
     if !mesh.halfedges.contains_key(&ind_halfedge) {
@@ -154,7 +156,11 @@ pub fn split_halfedge(
 ///  /       \        / /   \ \
 /// 2 ------- 3      2 ------- 3
 /// ```
-pub fn split_face(mesh: &mut ManifoldMesh3D, vert: &Vector3<f32>, ind_face: usize) -> Result<()> {
+pub fn split_face<T: Real>(
+    mesh: &mut ManifoldMesh3D<T>,
+    vert: &Vector3<T>,
+    ind_face: usize,
+) -> Result<()> {
     if !mesh.faces.contains_key(&ind_face) {
         return Err(anyhow::Error::msg("split_face(): Index out of bounds"));
     }
@@ -174,3 +180,194 @@ pub fn split_face(mesh: &mut ManifoldMesh3D, vert: &Vector3<f32>, ind_face: usiz
 
     Ok(())
 }
+
+/// Vertices linked to a vertex, none if it lies on a boundary
+fn interior_neighbors<T: Real>(
+    mesh: &ManifoldMesh3D<T>,
+    ind_vertex: usize,
+) -> Result<Option<HashSet<usize>>> {
+    let mut neighbors = HashSet::new();
+    for he in mesh.get_vertex(ind_vertex)?.halfedges() {
+        if he.opposite_halfedge().is_none() {
+            return Ok(None);
+        }
+        neighbors.insert(he.last_vertex().ind());
+    }
+    Ok(Some(neighbors))
+}
+
+/// Checks if a halfedge can be collapsed
+///
+/// Both halfedge vertices should be inside the mesh, and the only vertices linked to both should be
+/// the opposite vertices of the two faces along the halfedge (link condition). These opposite
+/// vertices should be linked to more than three vertices, so that no face is duplicated.
+pub fn can_collapse_halfedge<T: Real>(
+    mesh: &ManifoldMesh3D<T>,
+    ind_halfedge: usize,
+) -> Result<bool> {
+    let halfedge = mesh.get_halfedge(ind_halfedge)?;
+
+    let opp_vert1 = halfedge
+        .next_halfedge()
+        .ok_or(anyhow::Error::msg(
+            "can_collapse_halfedge(): Halfedge should have next",
+        ))?
+        .last_vertex();
+    let opp_vert2 = if let Some(halfedge_opp) = halfedge.opposite_halfedge() {
+        halfedge_opp
+            .next_halfedge()
+            .ok_or(anyhow::Error::msg(
+                "can_collapse_halfedge(): Opposite halfedge should have next",
+            ))?
+            .last_vertex()
+    } else {
+        return Ok(false);
+    };
+
+    let neighbors1 = interior_neighbors(mesh, halfedge.first_vertex().ind())?;
+    let neighbors2 = interior_neighbors(mesh, halfedge.last_vertex().ind())?;
+    let (neighbors1, neighbors2) = match (neighbors1, neighbors2) {
+        (Some(neighbors1), Some(neighbors2)) => (neighbors1, neighbors2),
+        _ => return Ok(false),
+    };
+    let common: HashSet<usize> = neighbors1.intersection(&neighbors2).copied().collect();
+    if opp_vert1.ind() == opp_vert2.ind()
+        || common.len() != 2
+        || !common.contains(&opp_vert1.ind())
+        || !common.contains(&opp_vert2.ind())
+    {
+        return Ok(false);
+    }
+
+    Ok(opp_vert1.halfedges().len() > 3 && opp_vert2.halfedges().len() > 3)
+}
+
+/// Collapses an halfedge
+///
+/// Given halfedge (1->2), vertex 2 is merged into vertex 1, moved to given position:
+/// ```text
+///     1
+///   / | \
+///  4  |  3  -->  4 - 1 - 3
+///   \ | /
+///     2
+/// ```
+/// Vertex 2 is removed from the mesh (vertex indices are no longer contiguous)
+pub fn collapse_halfedge<T: Real>(
+    mesh: &mut ManifoldMesh3D<T>,
+    vert: &Vector3<T>,
+    ind_halfedge: usize,
+) -> Result<bool> {
+    if !mesh.halfedges.contains_key(&ind_halfedge) {
+        return Err(anyhow::Error::msg(
+            "collapse_halfedge(): Index out of bounds",
+        ));
+    }
+    let collapsible = can_collapse_halfedge(mesh, ind_halfedge)?;
+
+    if !collapsible {
+        return Ok(false);
+    }
+
+    let [ind_v1, ind_v2] = *mesh.halfedges.get(&ind_halfedge).unwrap();
+
+    // faces around vertex 2, the ones along the halfedge are not added back
+    let mut faces_v2 = Vec::new();
+    for he in mesh.get_vertex(ind_v2)?.halfedges() {
+        let face = he.face().ok_or(anyhow::Error::msg(
+            "collapse_halfedge(): Halfedge should be linked to a face",
+        ))?;
+        faces_v2.push((face.ind(), face.vertices_inds()));
+    }
+
+    let mut new_faces = Vec::new();
+    for (ind_face, vertices) in faces_v2 {
+        let group = mesh.groups.get(&ind_face).copied().flatten();
+        mesh.remove_face(ind_face)?;
+        if !vertices.contains(&ind_v1) {
+            new_faces.push((
+                vertices.map(|ind| if ind == ind_v2 { ind_v1 } else { ind }),
+                group,
+            ));
+        }
+    }
+
+    mesh.vertices.insert(ind_v1, *vert);
+    mesh.vertices.remove(&ind_v2);
+    mesh.map_vert_hedg.remove(&ind_v2);
+
+    for ([ind_va, ind_vb, ind_vc], group) in new_faces {
+        let ind_face = mesh.add_face(ind_va, ind_vb, ind_vc)?;
+        if let Some(group) = group {
+            mesh.set_face_in_group(ind_face, group);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Triangular bipyramid: equator vertices 0, 1 and 2, apexes 3 (top) and 4 (bottom)
+    fn bipyramid() -> ManifoldMesh3D<f64> {
+        let mut mesh = ManifoldMesh3D::new();
+        for pt in [
+            [1., 0., 0.],
+            [-0.5, 0.8, 0.],
+            [-0.5, -0.8, 0.],
+            [0., 0., 1.],
+            [0., 0., -1.],
+        ] {
+            mesh.add_vertex(&Vector3::from(pt));
+        }
+        for [ind1, ind2, ind3] in [
+            [3, 0, 1],
+            [3, 1, 2],
+            [3, 2, 0],
+            [4, 1, 0],
+            [4, 2, 1],
+            [4, 0, 2],
+        ] {
+            mesh.add_face(ind1, ind2, ind3).unwrap();
+        }
+        mesh
+    }
+
+    #[test]
+    fn collapse_checks_link_condition() {
+        let mut mesh = bipyramid();
+        // vertex 2 is linked to both equator vertices without being opposite to their edge
+        let ind_he = mesh.is_edge_in(0, 1).unwrap().ind();
+        assert!(!can_collapse_halfedge(&mesh, ind_he).unwrap());
+        assert!(!collapse_halfedge(&mut mesh, &Vector3::zeros(), ind_he).unwrap());
+        assert_eq!(mesh.get_nb_faces(), 6);
+    }
+
+    #[test]
+    fn collapse_merges_vertices() {
+        let mut mesh = bipyramid();
+        let ind_he = mesh.is_edge_in(3, 0).unwrap().ind();
+        let vert = Vector3::new(0.5, 0.0, 0.5);
+        assert!(collapse_halfedge(&mut mesh, &vert, ind_he).unwrap());
+        mesh.check_mesh().unwrap();
+        assert_eq!(mesh.vertex_indices(), vec![1, 2, 3, 4]);
+        assert_eq!(mesh.get_nb_faces(), 4);
+        assert_eq!(mesh.get_vertex(3).unwrap().vertex(), vert);
+        assert!(mesh.is_edge_in(3, 4).is_some());
+
+        // the remaining tetrahedron cannot be collapsed further
+        let ind_he = mesh.is_edge_in(3, 4).unwrap().ind();
+        assert!(!can_collapse_halfedge(&mesh, ind_he).unwrap());
+    }
+
+    #[test]
+    fn boundary_halfedge_is_not_collapsed() {
+        let mut mesh = bipyramid();
+        let ind_face = mesh.is_face_in(3, 0, 1).unwrap().ind();
+        mesh.remove_face(ind_face).unwrap();
+        let ind_he = mesh.is_edge_in(3, 2).unwrap().ind();
+        assert!(!can_collapse_halfedge(&mesh, ind_he).unwrap());
+    }
+}
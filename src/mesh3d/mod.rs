@@ -1,3 +1,7 @@
+/// Conversions between mesh types
+pub mod conversion;
+/// Quadric error decimation
+pub mod decimation;
 /// Non manifold mesh
 pub mod generic_mesh3d;
 /// Input/Ouput functions
@@ -6,5 +10,13 @@ pub mod io;
 pub mod manifold_mesh3d;
 /// Mesh operations
 pub mod mesh_operations;
+/// Repair of triangle soups into manifold meshes
+pub mod repair;
+/// Topological and geometric mesh statistics
+pub mod stats;
+/// Meshes shared by unit tests
+#[cfg(test)]
+pub(crate) mod test_meshes;
 pub use generic_mesh3d::GenericMesh3D;
 pub use manifold_mesh3d::ManifoldMesh3D;
+pub use stats::MeshStats;
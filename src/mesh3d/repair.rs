@@ -0,0 +1,433 @@
+use anyhow::Result;
+use nalgebra::base::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::geometry::geometry_operations::{self, PolygonTriangulation};
+use crate::geometry::Real;
+use crate::mesh3d::conversion;
+use crate::mesh3d::io::VertexWelder;
+use crate::mesh3d::{GenericMesh3D, ManifoldMesh3D};
+
+#[derive(Clone, Debug, Default)]
+/// Fixes applied by a mesh repair
+pub struct RepairReport {
+    /// Vertices merged into a close vertex
+    pub welded_vertices: usize,
+    /// Shortest edges of zero area faces collapsed (their end vertices are merged)
+    pub collapsed_edges: usize,
+    /// Faces removed because of repeated vertices
+    pub degenerate_faces: usize,
+    /// Faces removed because another face has the same vertices
+    pub duplicate_faces: usize,
+    /// Faces removed because they share an edge with two other faces
+    pub non_manifold_faces: usize,
+    /// Faces whose orientation was reversed
+    pub reoriented_faces: usize,
+    /// Vertex copies added to split pinched vertices (one per fan of faces beyond the first)
+    pub split_vertices: usize,
+    /// Faces that could not be added with a consistent orientation (non orientable surface)
+    pub rejected_faces: usize,
+    /// Vertices removed because no face uses them
+    pub unused_vertices: usize,
+    /// Boundary holes closed
+    pub filled_holes: usize,
+    /// Faces added to close holes
+    pub hole_faces: usize,
+    /// Boundary holes left open (too large, or not fillable)
+    pub open_holes: usize,
+    /// Whether the repaired mesh has no boundary edge
+    pub closed: bool,
+}
+
+/// Representative of a merged vertex
+fn find(merged: &mut [usize], ind_vertex: usize) -> usize {
+    let mut root = ind_vertex;
+    while merged[root] != root {
+        root = merged[root];
+    }
+    let mut cur = ind_vertex;
+    while merged[cur] != root {
+        let next = merged[cur];
+        merged[cur] = root;
+        cur = next;
+    }
+    root
+}
+
+fn is_degenerate(face: &[usize; 3]) -> bool {
+    face[0] == face[1] || face[1] == face[2] || face[2] == face[0]
+}
+
+/// Whether face traverses the edge from ind_vertex1 to ind_vertex2
+fn has_halfedge(face: &[usize; 3], ind_vertex1: usize, ind_vertex2: usize) -> bool {
+    (0..3).any(|i| face[i] == ind_vertex1 && face[(i + 1) % 3] == ind_vertex2)
+}
+
+fn sorted_edge(ind_vertex1: usize, ind_vertex2: usize) -> [usize; 2] {
+    [ind_vertex1.min(ind_vertex2), ind_vertex1.max(ind_vertex2)]
+}
+
+/// Collapses zero area faces (twice the area below area_min) until none is left
+fn collapse_flat_faces<T: Real>(
+    vertices: &[Vector3<T>],
+    faces: &[[usize; 3]],
+    merged: &mut [usize],
+    area_min: T,
+) -> usize {
+    let mut nb_collapsed = 0;
+    loop {
+        let mut collapsed = false;
+        for face in faces.iter() {
+            let face = face.map(|ind| find(merged, ind));
+            if is_degenerate(&face) {
+                continue;
+            }
+            let [pt1, pt2, pt3] = face.map(|ind| vertices[ind]);
+            if (pt2 - pt1).cross(&(pt3 - pt1)).norm() > area_min {
+                continue;
+            }
+            let ind_short = (0..3)
+                .min_by(|&i, &j| {
+                    let len_i = (vertices[face[(i + 1) % 3]] - vertices[face[i]]).norm();
+                    let len_j = (vertices[face[(j + 1) % 3]] - vertices[face[j]]).norm();
+                    len_i
+                        .partial_cmp(&len_j)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or(0);
+            merged[face[(ind_short + 1) % 3]] = face[ind_short];
+            nb_collapsed += 1;
+            collapsed = true;
+        }
+        if !collapsed {
+            return nb_collapsed;
+        }
+    }
+}
+
+/// Orients faces consistently by propagation through edges, component by component
+///
+/// Returns the faces of each component
+fn orient_faces(faces: &mut [[usize; 3]]) -> Vec<Vec<usize>> {
+    let mut edge_faces: HashMap<[usize; 2], Vec<usize>> = HashMap::new();
+    for (ind_face, face) in faces.iter().enumerate() {
+        for i in 0..3 {
+            edge_faces
+                .entry(sorted_edge(face[i], face[(i + 1) % 3]))
+                .or_default()
+                .push(ind_face);
+        }
+    }
+
+    let mut visited = vec![false; faces.len()];
+    let mut components = Vec::new();
+    for ind_start in 0..faces.len() {
+        if visited[ind_start] {
+            continue;
+        }
+        visited[ind_start] = true;
+        let mut component = vec![ind_start];
+        let mut ind_cur = 0;
+        while ind_cur < component.len() {
+            let face = faces[component[ind_cur]];
+            ind_cur += 1;
+            for i in 0..3 {
+                let (ind_vertex1, ind_vertex2) = (face[i], face[(i + 1) % 3]);
+                for &ind_neigh in edge_faces[&sorted_edge(ind_vertex1, ind_vertex2)].iter() {
+                    if visited[ind_neigh] {
+                        continue;
+                    }
+                    visited[ind_neigh] = true;
+                    // neighbor must traverse the shared edge the other way
+                    if has_halfedge(&faces[ind_neigh], ind_vertex1, ind_vertex2) {
+                        faces[ind_neigh].swap(1, 2);
+                    }
+                    component.push(ind_neigh);
+                }
+            }
+        }
+        components.push(component);
+    }
+    components
+}
+
+/// Closes boundary loops of the mesh, up to a given number of edges
+///
+/// Returns numbers of filled holes, added faces and open holes
+fn fill_holes<T: Real>(
+    mesh: &mut ManifoldMesh3D<T>,
+    opt_max_hole_edges: Option<usize>,
+) -> Result<(usize, usize, usize)> {
+    // a boundary halfedge (v1, v2) gives the hole edge (v2, v1), sorted so that holes do not
+    // depend on hash order
+    let mut boundary: Vec<[usize; 2]> = mesh
+        .halfedges()
+        .values()
+        .filter(|he| mesh.is_edge_in(he[1], he[0]).is_none())
+        .copied()
+        .collect();
+    boundary.sort();
+    let mut hole_next: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for he in boundary.iter().rev() {
+        hole_next.entry(he[1]).or_default().push(he[0]);
+    }
+
+    let mut holes = Vec::new();
+    let mut nb_open = 0;
+    while let Some((&ind_start, _)) = hole_next.first_key_value() {
+        let mut hole = vec![ind_start];
+        let mut ind_cur = ind_start;
+        let closed = loop {
+            let opt_next = hole_next.get_mut(&ind_cur).and_then(|nexts| nexts.pop());
+            if hole_next
+                .get(&ind_cur)
+                .is_some_and(|nexts| nexts.is_empty())
+            {
+                hole_next.remove(&ind_cur);
+            }
+            match opt_next {
+                Some(ind_next) if ind_next == ind_start => break true,
+                Some(ind_next) => {
+                    hole.push(ind_next);
+                    ind_cur = ind_next;
+                }
+                None => break false,
+            }
+        };
+        if closed {
+            holes.push(hole);
+        } else {
+            nb_open += 1;
+        }
+    }
+
+    let mut nb_filled = 0;
+    let mut nb_faces = 0;
+    for hole in holes {
+        if opt_max_hole_edges.is_some_and(|max_edges| hole.len() > max_edges) {
+            nb_open += 1;
+            continue;
+        }
+        let mut pts = Vec::with_capacity(hole.len());
+        for &ind_vertex in hole.iter() {
+            pts.push(mesh.get_vertex(ind_vertex)?.vertex());
+        }
+        let mut added = Vec::new();
+        for tri in geometry_operations::triangulate_polygon(&pts, PolygonTriangulation::EarClipping)
+        {
+            match mesh.add_face(hole[tri[0]], hole[tri[1]], hole[tri[2]]) {
+                Ok(ind_face) => added.push(ind_face),
+                Err(_) => break,
+            }
+        }
+        if added.len() == hole.len() - 2 {
+            nb_filled += 1;
+            nb_faces += added.len();
+        } else {
+            // triangulation conflicting with existing edges
+            for ind_face in added {
+                mesh.remove_face(ind_face)?;
+            }
+            nb_open += 1;
+        }
+    }
+
+    Ok((nb_filled, nb_faces, nb_open))
+}
+
+/// Repairs a triangle soup into a manifold mesh
+///
+/// Vertices closer than the tolerance are welded (exact matching if None), and faces with an area
+/// below the squared tolerance are considered flat. Degenerate, duplicated and non manifold faces
+/// are removed, orientations are made consistent (outward for closed components), pinched vertices
+/// are split (one copy per fan of faces) and boundary holes are closed, up to the given number of
+/// edges (all holes if None)
+///
+/// Faces that cannot be oriented consistently are rejected, and holes may be left open: the report
+/// tells whether the repaired mesh is closed
+pub fn repair<T: Real>(
+    mesh: &GenericMesh3D<T>,
+    opt_tolerance: Option<T>,
+    opt_max_hole_edges: Option<usize>,
+) -> Result<(ManifoldMesh3D<T>, RepairReport)> {
+    let mut report = RepairReport::default();
+
+    let mut welder = VertexWelder::new(opt_tolerance);
+    let mut welded = Vec::with_capacity(mesh.get_nb_vertices());
+    for ind_vertex in 0..mesh.get_nb_vertices() {
+        welded.push(welder.weld(&mesh.get_vertex(ind_vertex)?));
+    }
+    let mut vertices = welder.vertices;
+    report.welded_vertices = mesh.get_nb_vertices() - vertices.len();
+
+    let mut input_faces = Vec::with_capacity(mesh.get_nb_faces());
+    for ind_face in 0..mesh.get_nb_faces() {
+        input_faces.push(mesh.get_face(ind_face)?.map(|ind| welded[ind]));
+    }
+
+    let tolerance = opt_tolerance.unwrap_or(T::zero()).max(T::zero());
+    let mut merged: Vec<usize> = (0..vertices.len()).collect();
+    report.collapsed_edges =
+        collapse_flat_faces(&vertices, &input_faces, &mut merged, tolerance * tolerance);
+
+    let mut faces = Vec::new();
+    let mut face_set = HashSet::new();
+    let mut edge_counts: HashMap<[usize; 2], usize> = HashMap::new();
+    for face in input_faces.iter() {
+        let face = face.map(|ind| find(&mut merged, ind));
+        if is_degenerate(&face) {
+            report.degenerate_faces += 1;
+            continue;
+        }
+        let mut key = face;
+        key.sort();
+        if !face_set.insert(key) {
+            report.duplicate_faces += 1;
+            continue;
+        }
+        let edges = [0, 1, 2].map(|i| sorted_edge(face[i], face[(i + 1) % 3]));
+        if edges
+            .iter()
+            .any(|edge| edge_counts.get(edge).is_some_and(|&nb| nb >= 2))
+        {
+            report.non_manifold_faces += 1;
+            continue;
+        }
+        for edge in edges {
+            *edge_counts.entry(edge).or_insert(0) += 1;
+        }
+        faces.push(face);
+    }
+
+    let input_orientation = faces.clone();
+    let components = orient_faces(&mut faces);
+    for component in components.iter() {
+        let volume = component.iter().fold(T::zero(), |vol, &ind_face| {
+            let [pt1, pt2, pt3] = faces[ind_face].map(|ind| vertices[ind]);
+            vol + pt1.dot(&pt2.cross(&pt3))
+        });
+        if volume < T::zero() {
+            for &ind_face in component.iter() {
+                faces[ind_face].swap(1, 2);
+            }
+        }
+    }
+    report.reoriented_faces = faces
+        .iter()
+        .zip(input_orientation.iter())
+        .filter(|(face, face_in)| !has_halfedge(face_in, face[0], face[1]))
+        .count();
+
+    // vertices on edges left inconsistent (non orientable surface) are not split, their faces are
+    // rejected below
+    let edge_faces = conversion::edge_faces(&faces);
+    let mut inconsistent_vertices = HashSet::new();
+    for (edge, faces_around) in edge_faces.iter() {
+        if faces_around.len() == 2 && !conversion::is_glued(faces_around) {
+            inconsistent_vertices.extend(edge.iter().copied());
+        }
+    }
+    let fans = conversion::vertex_fans(vertices.len(), &faces, &edge_faces);
+    for (ind_vertex, vertex_fans) in fans.iter().enumerate() {
+        if inconsistent_vertices.contains(&ind_vertex) {
+            continue;
+        }
+        for fan in vertex_fans.iter().skip(1) {
+            let ind_copy = vertices.len();
+            vertices.push(vertices[ind_vertex]);
+            merged.push(ind_copy);
+            report.split_vertices += 1;
+            for &ind_face in fan.iter() {
+                for ind in faces[ind_face].iter_mut() {
+                    if *ind == ind_vertex {
+                        *ind = ind_copy;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut used = vec![false; vertices.len()];
+    for face in faces.iter() {
+        for &ind in face.iter() {
+            used[ind] = true;
+        }
+    }
+    let mut repaired = ManifoldMesh3D::new();
+    let mut new_inds = vec![0; vertices.len()];
+    for (ind_vertex, vertex) in vertices.iter().enumerate() {
+        if used[ind_vertex] {
+            new_inds[ind_vertex] = repaired.add_vertex(vertex);
+        } else if merged[ind_vertex] == ind_vertex {
+            report.unused_vertices += 1;
+        }
+    }
+    for face in faces.iter() {
+        let [ind1, ind2, ind3] = face.map(|ind| new_inds[ind]);
+        if repaired.add_face(ind1, ind2, ind3).is_err() {
+            report.rejected_faces += 1;
+        }
+    }
+
+    let (nb_filled, nb_faces, nb_open) = fill_holes(&mut repaired, opt_max_hole_edges)?;
+    report.filled_holes = nb_filled;
+    report.hole_faces = nb_faces;
+    report.open_holes = nb_open;
+    report.closed = repaired
+        .halfedges()
+        .keys()
+        .all(|ind_hedge| repaired.map_hedg_opp.contains_key(ind_hedge));
+
+    Ok((repaired, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two tetrahedra sharing their first vertex (pinched vertex), optionally without a face
+    fn bowtie(open: bool) -> GenericMesh3D<f64> {
+        let mut mesh = GenericMesh3D::new();
+        for pt in [
+            [0., 0., 0.],
+            [1., 0., 0.],
+            [0., 1., 0.],
+            [0., 0., 1.],
+            [-1., 0., 0.],
+            [0., -1., 0.],
+            [0., 0., -1.],
+        ] {
+            mesh.add_vertex(&Vector3::from(pt));
+        }
+        let mut faces = vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]];
+        faces.extend([[0, 5, 4], [0, 4, 6], [0, 6, 5], [4, 5, 6]]);
+        if open {
+            faces.pop();
+        }
+        for [ind1, ind2, ind3] in faces {
+            mesh.add_face(ind1, ind2, ind3).unwrap();
+        }
+        mesh
+    }
+
+    #[test]
+    fn pinched_vertex_is_split() {
+        let (repaired, report) = repair(&bowtie(false), None, None).unwrap();
+        assert_eq!(report.split_vertices, 1);
+        assert!(report.closed);
+        assert_eq!(repaired.get_nb_vertices(), 8);
+        assert_eq!(repaired.connected_components().len(), 2);
+        repaired.check_mesh().unwrap();
+    }
+
+    #[test]
+    fn holes_are_reported() {
+        let (_, report) = repair(&bowtie(true), None, None).unwrap();
+        assert_eq!((report.filled_holes, report.open_holes), (1, 0));
+        assert!(report.closed);
+
+        let (_, report) = repair(&bowtie(true), None, Some(2)).unwrap();
+        assert_eq!((report.filled_holes, report.open_holes), (0, 1));
+        assert!(!report.closed);
+    }
+}
@@ -0,0 +1,291 @@
+use nalgebra::base::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::geometry::Real;
+use crate::mesh3d::ManifoldMesh3D;
+
+#[derive(Clone, Debug)]
+/// Topological and geometric statistics of a manifold mesh
+pub struct MeshStats<T = f32> {
+    /// Number of vertices
+    pub nb_vertices: usize,
+    /// Number of edges (boundary edges included)
+    pub nb_edges: usize,
+    /// Number of faces
+    pub nb_faces: usize,
+    /// Number of connected components (faces joined through edges)
+    pub nb_components: usize,
+    /// Number of boundary loops (0 for a closed mesh)
+    pub boundary_loops: usize,
+    /// Euler characteristic (vertices - edges + faces)
+    pub euler_characteristic: isize,
+    /// Total genus of the components (vertices without face excepted)
+    pub genus: usize,
+    /// Signed volume enclosed by the faces (positive for outward oriented closed meshes)
+    pub signed_volume: T,
+    /// Surface area
+    pub area: T,
+    /// Bounding box (min and max corners), none for a mesh without vertex
+    pub bounding_box: Option<(Vector3<T>, Vector3<T>)>,
+    /// Bounding box diagonal length
+    pub diagonal: T,
+    /// Minimum edge length
+    pub min_edge_length: T,
+    /// Maximum edge length
+    pub max_edge_length: T,
+    /// Minimum triangle quality, in [0, 1] (1 for an equilateral triangle, 0 for a flat one)
+    pub min_quality: T,
+    /// Maximum triangle quality, in [0, 1]
+    pub max_quality: T,
+}
+
+/// Bounding box of a mesh, none if it has no vertex
+pub fn bounding_box<T: Real>(mesh: &ManifoldMesh3D<T>) -> Option<(Vector3<T>, Vector3<T>)> {
+    mesh.vertices().values().fold(None, |bb_val, vert| {
+        if let Some((mut bb_min, mut bb_max)) = bb_val {
+            for i in 0..3 {
+                bb_min[i] = bb_min[i].min(vert[i]);
+                bb_max[i] = bb_max[i].max(vert[i]);
+            }
+            Some((bb_min, bb_max))
+        } else {
+            Some((*vert, *vert))
+        }
+    })
+}
+
+/// Triangle quality, area over sum of squared edge lengths, normalized to 1 for equilateral triangles
+fn triangle_quality<T: Real>(pts: [Vector3<T>; 3]) -> T {
+    let area_double = (pts[1] - pts[0]).cross(&(pts[2] - pts[0])).norm();
+    let sq_lengths = (pts[1] - pts[0]).norm_squared()
+        + (pts[2] - pts[1]).norm_squared()
+        + (pts[0] - pts[2]).norm_squared();
+    if sq_lengths == T::zero() {
+        return T::zero();
+    }
+    // 4 * sqrt(3) * area / sq_lengths
+    nalgebra::convert::<f64, T>(2.0 * 3.0_f64.sqrt()) * area_double / sq_lengths
+}
+
+/// Number of loops formed by boundary halfedges (halfedges without opposite)
+fn count_boundary_loops<T: Real>(mesh: &ManifoldMesh3D<T>) -> usize {
+    let mut boundary: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut hedge_inds: Vec<usize> = mesh
+        .halfedges()
+        .keys()
+        .copied()
+        .filter(|ind_hedge| !mesh.map_hedg_opp.contains_key(ind_hedge))
+        .collect();
+    hedge_inds.sort();
+    for &ind_hedge in hedge_inds.iter() {
+        boundary
+            .entry(mesh.halfedges()[&ind_hedge][0])
+            .or_default()
+            .push(ind_hedge);
+    }
+
+    let mut visited = HashSet::new();
+    let mut nb_loops = 0;
+    for &ind_start in hedge_inds.iter() {
+        if visited.contains(&ind_start) {
+            continue;
+        }
+        nb_loops += 1;
+        let mut ind_cur = ind_start;
+        // boundary vertices have as many incoming as outgoing boundary halfedges
+        while visited.insert(ind_cur) {
+            let ind_vertex = mesh.halfedges()[&ind_cur][1];
+            let opt_next = boundary
+                .get(&ind_vertex)
+                .and_then(|hedges| hedges.iter().find(|ind_hedge| !visited.contains(ind_hedge)));
+            match opt_next {
+                Some(&ind_next) => ind_cur = ind_next,
+                None => break,
+            }
+        }
+    }
+    nb_loops
+}
+
+impl<T: Real> MeshStats<T> {
+    /// Computes statistics of a mesh
+    ///
+    /// Lengths and qualities are zero for a mesh without face
+    pub fn compute(mesh: &ManifoldMesh3D<T>) -> MeshStats<T> {
+        // sorted indices, so that sums do not depend on hash order
+        let mut face_inds: Vec<usize> = mesh.faces().keys().copied().collect();
+        face_inds.sort();
+
+        let mut signed_volume = T::zero();
+        let mut area = T::zero();
+        let mut quality_bounds: Option<(T, T)> = None;
+        let mut used_vertices = HashSet::new();
+        for &ind_face in face_inds.iter() {
+            let face = mesh.faces()[&ind_face];
+            let vertices = face.map(|ind_hedge| mesh.halfedges()[&ind_hedge][0]);
+            used_vertices.extend(vertices.iter().copied());
+            let pts = vertices.map(|ind_vertex| mesh.vertices()[&ind_vertex]);
+
+            signed_volume = signed_volume + pts[0].dot(&pts[1].cross(&pts[2]));
+            area = area + (pts[1] - pts[0]).cross(&(pts[2] - pts[0])).norm();
+            let quality = triangle_quality(pts);
+            quality_bounds = match quality_bounds {
+                Some((quality_min, quality_max)) => {
+                    Some((quality_min.min(quality), quality_max.max(quality)))
+                }
+                None => Some((quality, quality)),
+            };
+        }
+        let signed_volume = signed_volume / nalgebra::convert::<f64, T>(6.0);
+        let area = area * nalgebra::convert::<f64, T>(0.5);
+        let (min_quality, max_quality) = quality_bounds.unwrap_or((T::zero(), T::zero()));
+
+        let mut nb_edges = 0;
+        let mut length_bounds: Option<(T, T)> = None;
+        for (ind_hedge, hedge) in mesh.halfedges().iter() {
+            // each edge counted once, from its halfedge of lower index or its boundary halfedge
+            if let Some(ind_opp) = mesh.map_hedg_opp.get(ind_hedge) {
+                if ind_opp < ind_hedge {
+                    continue;
+                }
+            }
+            nb_edges += 1;
+            let length = (mesh.vertices()[&hedge[1]] - mesh.vertices()[&hedge[0]]).norm();
+            length_bounds = match length_bounds {
+                Some((length_min, length_max)) => {
+                    Some((length_min.min(length), length_max.max(length)))
+                }
+                None => Some((length, length)),
+            };
+        }
+        let (min_edge_length, max_edge_length) = length_bounds.unwrap_or((T::zero(), T::zero()));
+
+        let nb_vertices = mesh.get_nb_vertices();
+        let nb_faces = mesh.get_nb_faces();
+        let nb_components = mesh.connected_components().len();
+        let boundary_loops = count_boundary_loops(mesh);
+        let euler_characteristic = nb_vertices as isize - nb_edges as isize + nb_faces as isize;
+        // chi = 2 * components - 2 * genus - boundaries, for vertices belonging to faces
+        let euler_used = used_vertices.len() as isize - nb_edges as isize + nb_faces as isize;
+        let genus_double = 2 * nb_components as isize - boundary_loops as isize - euler_used;
+        let genus = (genus_double / 2).max(0) as usize;
+
+        let bounding_box = bounding_box(mesh);
+        let diagonal = bounding_box
+            .map(|(bb_min, bb_max)| (bb_max - bb_min).norm())
+            .unwrap_or(T::zero());
+
+        MeshStats {
+            nb_vertices,
+            nb_edges,
+            nb_faces,
+            nb_components,
+            boundary_loops,
+            euler_characteristic,
+            genus,
+            signed_volume,
+            area,
+            bounding_box,
+            diagonal,
+            min_edge_length,
+            max_edge_length,
+            min_quality,
+            max_quality,
+        }
+    }
+
+    /// Whether the mesh has no boundary
+    pub fn is_closed(&self) -> bool {
+        self.boundary_loops == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh3d::test_meshes;
+
+    #[test]
+    fn closed_torus() {
+        let mesh = test_meshes::torus(16, 8);
+        let stats = MeshStats::compute(&mesh);
+        assert_eq!(
+            (stats.nb_vertices, stats.nb_edges, stats.nb_faces),
+            (128, 384, 256)
+        );
+        assert_eq!(stats.euler_characteristic, 0);
+        assert_eq!((stats.nb_components, stats.genus), (1, 1));
+        assert_eq!(stats.boundary_loops, 0);
+        assert!(stats.is_closed());
+        assert!(stats.signed_volume > 0.0);
+    }
+
+    #[test]
+    fn open_meshes() {
+        // two holes far apart
+        let mut mesh = test_meshes::torus(16, 8);
+        mesh.remove_face(0).unwrap();
+        mesh.remove_face(128).unwrap();
+        let stats = MeshStats::compute(&mesh);
+        assert_eq!(stats.boundary_loops, 2);
+        assert_eq!(stats.genus, 1);
+        assert!(!stats.is_closed());
+
+        // bipyramid without its bottom faces
+        let mut mesh = test_meshes::jittered_bipyramid();
+        let mut face_inds: Vec<usize> = mesh.faces().keys().copied().collect();
+        face_inds.sort();
+        for ind_face in face_inds {
+            if mesh
+                .get_face(ind_face)
+                .unwrap()
+                .vertices_inds()
+                .contains(&1)
+            {
+                mesh.remove_face(ind_face).unwrap();
+            }
+        }
+        let stats = MeshStats::compute(&mesh);
+        assert_eq!(stats.nb_faces, 9);
+        assert_eq!(stats.boundary_loops, 1);
+        assert_eq!((stats.nb_components, stats.genus), (1, 0));
+    }
+
+    #[test]
+    fn unit_tetrahedron() {
+        let mesh = test_meshes::tetrahedron();
+        let stats = MeshStats::compute(&mesh);
+        assert!((stats.signed_volume - 1.0 / 6.0).abs() < 1e-12);
+        assert!((stats.area - (1.5 + 0.75_f64.sqrt())).abs() < 1e-12);
+        assert_eq!((stats.genus, stats.boundary_loops), (0, 0));
+        assert_eq!(stats.min_edge_length, 1.0);
+        assert!((stats.max_edge_length - 2.0_f64.sqrt()).abs() < 1e-12);
+        assert!((stats.diagonal - 3.0_f64.sqrt()).abs() < 1e-12);
+
+        // inward oriented faces
+        let tetrahedron = test_meshes::tetrahedron();
+        let mut mesh = ManifoldMesh3D::new();
+        for ind_vertex in tetrahedron.vertex_indices() {
+            mesh.add_vertex(&tetrahedron.vertices()[&ind_vertex]);
+        }
+        for [ind1, ind2, ind3] in [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]] {
+            mesh.add_face(ind1, ind2, ind3).unwrap();
+        }
+        let stats = MeshStats::compute(&mesh);
+        assert!((stats.signed_volume + 1.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn equilateral_triangle() {
+        let mut mesh = ManifoldMesh3D::new();
+        for i in 0..3 {
+            let ang = 2.0 * std::f64::consts::PI * (i as f64) / 3.0;
+            mesh.add_vertex(&Vector3::new(ang.cos(), ang.sin(), 0.5));
+        }
+        mesh.add_face(0, 1, 2).unwrap();
+        let stats = MeshStats::compute(&mesh);
+        assert!((stats.min_quality - 1.0).abs() < 1e-12);
+        assert!((stats.max_quality - 1.0).abs() < 1e-12);
+        assert_eq!(stats.boundary_loops, 1);
+    }
+}
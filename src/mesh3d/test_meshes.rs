@@ -0,0 +1,80 @@
+use nalgebra::Vector3;
+
+use super::ManifoldMesh3D;
+
+/// Adds a bipyramid with a jittered equator of the given size, so that no five vertices are
+/// cospherical
+fn add_jittered_bipyramid(mesh: &mut ManifoldMesh3D<f64>, nb_surr: usize, offset: Vector3<f64>) {
+    let up_vert = mesh.add_vertex(&(Vector3::new(0.03, -0.02, 1.1) + offset));
+    let down_vert = mesh.add_vertex(&(Vector3::new(-0.01, 0.04, -0.9) + offset));
+    let mut surr_vert = Vec::new();
+    for i in 0..nb_surr {
+        let ang = 2.0 * std::f64::consts::PI * (i as f64) / (nb_surr as f64);
+        let rad = 1.0 + 0.1 * (3.0 * i as f64).sin();
+        let z = 0.05 * (5.0 * i as f64).cos();
+        let vert = Vector3::new(rad * ang.cos(), rad * ang.sin(), z) + offset;
+        surr_vert.push(mesh.add_vertex(&vert));
+    }
+    for i in 0..nb_surr {
+        let j = (i + 1) % nb_surr;
+        mesh.add_face(up_vert, surr_vert[i], surr_vert[j]).unwrap();
+        mesh.add_face(down_vert, surr_vert[j], surr_vert[i])
+            .unwrap();
+    }
+}
+
+/// Bipyramid with a jittered equator, so that no five vertices are cospherical
+///
+/// Apexes are vertices 0 (top) and 1 (bottom), the equator is made of the 9 other vertices
+pub fn jittered_bipyramid() -> ManifoldMesh3D<f64> {
+    let mut mesh = ManifoldMesh3D::new();
+    add_jittered_bipyramid(&mut mesh, 9, Vector3::zeros());
+    mesh
+}
+
+/// Two disjoint shells: the jittered bipyramid (18 faces, centered near the origin) and a
+/// bipyramid with a 6 vertices equator (12 faces, centered near x = 4)
+pub fn two_shells() -> ManifoldMesh3D<f64> {
+    let mut mesh = jittered_bipyramid();
+    add_jittered_bipyramid(&mut mesh, 6, Vector3::new(4.0, 0.0, 0.0));
+    mesh
+}
+
+/// Torus, vertex (i, j) at index i * nb_section + j
+pub fn torus(nb_around: usize, nb_section: usize) -> ManifoldMesh3D<f64> {
+    let mut mesh = ManifoldMesh3D::new();
+    for i in 0..nb_around {
+        let ang_around = 2.0 * std::f64::consts::PI * (i as f64) / (nb_around as f64);
+        for j in 0..nb_section {
+            let ang_section = 2.0 * std::f64::consts::PI * (j as f64) / (nb_section as f64);
+            let rad = 2.0 + 0.7 * ang_section.cos();
+            mesh.add_vertex(&Vector3::new(
+                rad * ang_around.cos(),
+                rad * ang_around.sin(),
+                0.7 * ang_section.sin(),
+            ));
+        }
+    }
+    let ind = |i: usize, j: usize| (i % nb_around) * nb_section + j % nb_section;
+    for i in 0..nb_around {
+        for j in 0..nb_section {
+            mesh.add_face(ind(i, j), ind(i + 1, j), ind(i + 1, j + 1))
+                .unwrap();
+            mesh.add_face(ind(i, j), ind(i + 1, j + 1), ind(i, j + 1))
+                .unwrap();
+        }
+    }
+    mesh
+}
+
+/// Unit tetrahedron with outward oriented faces, right angle corner at vertex 0
+pub fn tetrahedron() -> ManifoldMesh3D<f64> {
+    let mut mesh = ManifoldMesh3D::new();
+    for pt in [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]] {
+        mesh.add_vertex(&Vector3::from(pt));
+    }
+    for [ind1, ind2, ind3] in [[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]] {
+        mesh.add_face(ind1, ind2, ind3).unwrap();
+    }
+    mesh
+}
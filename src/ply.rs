@@ -0,0 +1,561 @@
+use anyhow::Result;
+use std::io::{BufRead, Read, Write};
+
+use crate::geometry::Real;
+use crate::mesh3d::io::{parse_next, LoadError};
+
+/// Header comment giving the label written for unlabelled faces
+pub(crate) const UNLABELLED_COMMENT: &str = "unlabelled_label";
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum PlyType {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum PlyProperty {
+    Scalar(String, PlyType),
+    List(String, PlyType, PlyType),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum PlyValue {
+    Scalar(f64),
+    List(Vec<f64>),
+}
+
+/// Element description and content
+pub(crate) struct PlyElement {
+    pub name: String,
+    pub count: usize,
+    pub properties: Vec<PlyProperty>,
+    pub rows: Vec<Vec<PlyValue>>,
+    /// Line of the first row, for ascii content
+    pub first_line: Option<usize>,
+}
+
+/// Content of a ply file
+pub(crate) struct PlyData {
+    pub format: PlyFormat,
+    pub comments: Vec<String>,
+    pub elements: Vec<PlyElement>,
+}
+
+impl PlyFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+            PlyFormat::BinaryBigEndian => "binary_big_endian",
+        }
+    }
+}
+
+impl PlyType {
+    fn from_name(name: &str) -> std::result::Result<PlyType, LoadError> {
+        match name {
+            "char" | "int8" => Ok(PlyType::Char),
+            "uchar" | "uint8" => Ok(PlyType::UChar),
+            "short" | "int16" => Ok(PlyType::Short),
+            "ushort" | "uint16" => Ok(PlyType::UShort),
+            "int" | "int32" => Ok(PlyType::Int),
+            "uint" | "uint32" => Ok(PlyType::UInt),
+            "float" | "float32" => Ok(PlyType::Float),
+            "double" | "float64" => Ok(PlyType::Double),
+            _ => Err(LoadError::parse(&format!("Unknown ply type {}", name))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            PlyType::Char => "char",
+            PlyType::UChar => "uchar",
+            PlyType::Short => "short",
+            PlyType::UShort => "ushort",
+            PlyType::Int => "int",
+            PlyType::UInt => "uint",
+            PlyType::Float => "float",
+            PlyType::Double => "double",
+        }
+    }
+
+    /// Floating type matching the precision of a scalar type
+    pub fn float_for<T: Real>() -> PlyType {
+        if std::mem::size_of::<T>() > std::mem::size_of::<f32>() {
+            PlyType::Double
+        } else {
+            PlyType::Float
+        }
+    }
+
+    /// Smallest unsigned type able to store the given value
+    pub fn unsigned_for(val_max: usize) -> PlyType {
+        if val_max <= u8::MAX as usize {
+            PlyType::UChar
+        } else if val_max <= u16::MAX as usize {
+            PlyType::UShort
+        } else {
+            PlyType::UInt
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            PlyType::Char | PlyType::UChar => 1,
+            PlyType::Short | PlyType::UShort => 2,
+            PlyType::Int | PlyType::UInt | PlyType::Float => 4,
+            PlyType::Double => 8,
+        }
+    }
+
+    fn decode(&self, bytes: &[u8], format: PlyFormat) -> f64 {
+        let mut buf = [0u8; 8];
+        let size = self.size();
+        buf[..size].copy_from_slice(&bytes[..size]);
+        if format == PlyFormat::BinaryBigEndian {
+            buf[..size].reverse();
+        }
+        match self {
+            PlyType::Char => buf[0] as i8 as f64,
+            PlyType::UChar => buf[0] as f64,
+            PlyType::Short => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+            PlyType::UShort => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+            PlyType::Int => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            PlyType::UInt => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            PlyType::Float => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            PlyType::Double => f64::from_le_bytes(buf),
+        }
+    }
+}
+
+impl PlyElement {
+    /// Element description, without content
+    pub fn new(name: &str, count: usize, properties: Vec<PlyProperty>) -> PlyElement {
+        PlyElement {
+            name: name.to_string(),
+            count,
+            properties,
+            rows: Vec::new(),
+            first_line: None,
+        }
+    }
+
+    /// Gets line of a row, for ascii content
+    pub fn row_line(&self, ind_row: usize) -> Option<usize> {
+        self.first_line.map(|line| line + ind_row)
+    }
+
+    /// Gets position of a property in element rows
+    pub fn property_index(&self, names: &[&str]) -> Option<usize> {
+        self.properties.iter().position(|prop| {
+            let prop_name = match prop {
+                PlyProperty::Scalar(name, _) => name,
+                PlyProperty::List(name, _, _) => name,
+            };
+            names.contains(&prop_name.as_str())
+        })
+    }
+}
+
+impl PlyData {
+    /// Gets element from its name
+    pub fn element(&self, name: &str) -> Option<&PlyElement> {
+        self.elements.iter().find(|elem| elem.name == name)
+    }
+
+    /// Gets value following a given keyword in header comments
+    pub fn comment_value(&self, keyword: &str) -> Option<usize> {
+        self.comments.iter().find_map(|comment| {
+            let mut comment_split = comment.split_whitespace();
+            if comment_split.next() == Some(keyword) {
+                comment_split.next()?.parse::<usize>().ok()
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl PlyValue {
+    /// Gets scalar value
+    pub fn scalar(&self) -> std::result::Result<f64, LoadError> {
+        match self {
+            PlyValue::Scalar(val) => Ok(*val),
+            PlyValue::List(_) => Err(LoadError::parse("Expected scalar ply property")),
+        }
+    }
+
+    /// Gets list value
+    pub fn list(&self) -> std::result::Result<&Vec<f64>, LoadError> {
+        match self {
+            PlyValue::List(vals) => Ok(vals),
+            PlyValue::Scalar(_) => Err(LoadError::parse("Expected list ply property")),
+        }
+    }
+}
+
+/// Reads ply header, returning also the number of header lines
+fn read_header<R: BufRead>(reader: &mut R) -> std::result::Result<(PlyData, usize), LoadError> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if line.trim() != "ply" {
+        return Err(LoadError::parse("Not a ply file").at_line(1));
+    }
+
+    let mut nb_lines = 1;
+    let mut opt_format = None;
+    let mut comments = Vec::new();
+    let mut elements: Vec<PlyElement> = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(LoadError::parse("Unexpected end of ply header").at_line(nb_lines));
+        }
+        nb_lines += 1;
+        let located = |err: LoadError| err.at_line(nb_lines);
+        let mut line_split = line.split_whitespace();
+        match line_split.next() {
+            Some("format") => {
+                opt_format = Some(match line_split.next() {
+                    Some("ascii") => PlyFormat::Ascii,
+                    Some("binary_little_endian") => PlyFormat::BinaryLittleEndian,
+                    Some("binary_big_endian") => PlyFormat::BinaryBigEndian,
+                    _ => return Err(located(LoadError::parse("Unknown ply format"))),
+                });
+            }
+            Some("comment") => {
+                comments.push(line_split.collect::<Vec<&str>>().join(" "));
+            }
+            Some("element") => {
+                let name: String = parse_next(&mut line_split, "element name").map_err(located)?;
+                let count = parse_next(&mut line_split, "element count").map_err(located)?;
+                elements.push(PlyElement::new(&name, count, Vec::new()));
+            }
+            Some("property") => {
+                let elem = elements
+                    .last_mut()
+                    .ok_or(located(LoadError::parse("Property defined before element")))?;
+                let words: Vec<&str> = line_split.collect();
+                let prop = match words[..] {
+                    ["list", count_ty, item_ty, name] => PlyProperty::List(
+                        name.to_string(),
+                        PlyType::from_name(count_ty).map_err(located)?,
+                        PlyType::from_name(item_ty).map_err(located)?,
+                    ),
+                    [ty, name] => PlyProperty::Scalar(
+                        name.to_string(),
+                        PlyType::from_name(ty).map_err(located)?,
+                    ),
+                    _ => return Err(located(LoadError::parse("Malformed ply property"))),
+                };
+                elem.properties.push(prop);
+            }
+            Some("end_header") => break,
+            _ => (),
+        }
+    }
+
+    let format = opt_format.ok_or(LoadError::parse("Missing ply format").at_line(nb_lines))?;
+    Ok((
+        PlyData {
+            format,
+            comments,
+            elements,
+        },
+        nb_lines,
+    ))
+}
+
+fn read_body_ascii<R: BufRead>(
+    reader: &mut R,
+    ply: &mut PlyData,
+    nb_header_lines: usize,
+) -> std::result::Result<(), LoadError> {
+    let mut lines = reader.lines();
+    let mut nb_lines = nb_header_lines;
+    for elem in ply.elements.iter_mut() {
+        elem.first_line = Some(nb_lines + 1);
+        for _ in 0..elem.count {
+            nb_lines += 1;
+            let located = |err: LoadError| err.at_line(nb_lines);
+            let line = lines
+                .next()
+                .ok_or(located(LoadError::parse("Unexpected end of ply file")))?
+                .map_err(|err| located(LoadError::from(err)))?;
+            let mut line_split = line.split_whitespace();
+            let mut next_value =
+                || -> std::result::Result<f64, LoadError> { parse_next(&mut line_split, "value") };
+            let mut row = Vec::with_capacity(elem.properties.len());
+            for prop in elem.properties.iter() {
+                match prop {
+                    PlyProperty::Scalar(_, _) => {
+                        row.push(PlyValue::Scalar(next_value().map_err(located)?))
+                    }
+                    PlyProperty::List(_, _, _) => {
+                        let nb = next_value().map_err(located)? as usize;
+                        let vals = (0..nb)
+                            .map(|_| next_value())
+                            .collect::<std::result::Result<Vec<f64>, LoadError>>()
+                            .map_err(located)?;
+                        row.push(PlyValue::List(vals));
+                    }
+                }
+            }
+            elem.rows.push(row);
+        }
+    }
+    Ok(())
+}
+
+fn read_body_binary<R: Read>(
+    reader: &mut R,
+    ply: &mut PlyData,
+) -> std::result::Result<(), LoadError> {
+    let format = ply.format;
+    let mut buf = [0u8; 8];
+    let mut next_value = |reader: &mut R, ty: PlyType| -> std::result::Result<f64, LoadError> {
+        reader.read_exact(&mut buf[..ty.size()])?;
+        Ok(ty.decode(&buf, format))
+    };
+    for elem in ply.elements.iter_mut() {
+        for _ in 0..elem.count {
+            let mut row = Vec::with_capacity(elem.properties.len());
+            for prop in elem.properties.iter() {
+                match *prop {
+                    PlyProperty::Scalar(_, ty) => {
+                        row.push(PlyValue::Scalar(next_value(reader, ty)?))
+                    }
+                    PlyProperty::List(_, count_ty, item_ty) => {
+                        let nb = next_value(reader, count_ty)? as usize;
+                        let vals = (0..nb)
+                            .map(|_| next_value(reader, item_ty))
+                            .collect::<std::result::Result<Vec<f64>, LoadError>>()?;
+                        row.push(PlyValue::List(vals));
+                    }
+                }
+            }
+            elem.rows.push(row);
+        }
+    }
+    Ok(())
+}
+
+/// Reads a whole ply file (ascii or binary)
+pub(crate) fn read_ply<R: BufRead>(reader: &mut R) -> std::result::Result<PlyData, LoadError> {
+    let (mut ply, nb_header_lines) = read_header(reader)?;
+    if ply.format == PlyFormat::Ascii {
+        read_body_ascii(reader, &mut ply, nb_header_lines)?;
+    } else {
+        read_body_binary(reader, &mut ply)?;
+    }
+    Ok(ply)
+}
+
+/// Row of values to be written in a ply file
+pub(crate) struct PlyRow {
+    values: Vec<(PlyType, f64)>,
+}
+
+impl PlyRow {
+    pub fn new() -> PlyRow {
+        PlyRow { values: Vec::new() }
+    }
+
+    /// Adds a scalar value
+    pub fn push(&mut self, ty: PlyType, val: f64) -> &mut PlyRow {
+        self.values.push((ty, val));
+        self
+    }
+
+    /// Adds a list of values, preceded by its length
+    pub fn push_list(&mut self, count_ty: PlyType, item_ty: PlyType, vals: &[f64]) -> &mut PlyRow {
+        self.values.push((count_ty, vals.len() as f64));
+        for &val in vals {
+            self.values.push((item_ty, val));
+        }
+        self
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W, format: PlyFormat) -> Result<()> {
+        if format == PlyFormat::Ascii {
+            let words: Vec<String> = self
+                .values
+                .iter()
+                .map(|&(ty, val)| match ty {
+                    PlyType::Float => format!("{}", val as f32),
+                    PlyType::Double => format!("{}", val),
+                    _ => format!("{}", val as i64),
+                })
+                .collect();
+            writeln!(writer, "{}", words.join(" "))?;
+        } else {
+            for &(ty, val) in self.values.iter() {
+                let mut bytes = match ty {
+                    PlyType::Char => (val as i8).to_le_bytes().to_vec(),
+                    PlyType::UChar => (val as u8).to_le_bytes().to_vec(),
+                    PlyType::Short => (val as i16).to_le_bytes().to_vec(),
+                    PlyType::UShort => (val as u16).to_le_bytes().to_vec(),
+                    PlyType::Int => (val as i32).to_le_bytes().to_vec(),
+                    PlyType::UInt => (val as u32).to_le_bytes().to_vec(),
+                    PlyType::Float => (val as f32).to_le_bytes().to_vec(),
+                    PlyType::Double => val.to_le_bytes().to_vec(),
+                };
+                if format == PlyFormat::BinaryBigEndian {
+                    bytes.reverse();
+                }
+                writer.write_all(&bytes)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes ply header (elements content is ignored)
+pub(crate) fn write_header<W: Write>(
+    writer: &mut W,
+    format: PlyFormat,
+    comments: &[String],
+    elements: &[PlyElement],
+) -> Result<()> {
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format {} 1.0", format.name())?;
+    for comment in comments.iter() {
+        writeln!(writer, "comment {}", comment)?;
+    }
+    for elem in elements.iter() {
+        writeln!(writer, "element {} {}", elem.name, elem.count)?;
+        for prop in elem.properties.iter() {
+            match prop {
+                PlyProperty::Scalar(name, ty) => {
+                    writeln!(writer, "property {} {}", ty.name(), name)?
+                }
+                PlyProperty::List(name, count_ty, item_ty) => writeln!(
+                    writer,
+                    "property list {} {} {}",
+                    count_ty.name(),
+                    item_ty.name(),
+                    name
+                )?,
+            }
+        }
+    }
+    writeln!(writer, "end_header")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMATS: [PlyFormat; 3] = [
+        PlyFormat::Ascii,
+        PlyFormat::BinaryLittleEndian,
+        PlyFormat::BinaryBigEndian,
+    ];
+
+    #[test]
+    fn type_selection() {
+        assert_eq!(PlyType::float_for::<f32>(), PlyType::Float);
+        assert_eq!(PlyType::float_for::<f64>(), PlyType::Double);
+        assert_eq!(PlyType::unsigned_for(255), PlyType::UChar);
+        assert_eq!(PlyType::unsigned_for(256), PlyType::UShort);
+        assert_eq!(PlyType::unsigned_for(65535), PlyType::UShort);
+        assert_eq!(PlyType::unsigned_for(65536), PlyType::UInt);
+    }
+
+    #[test]
+    fn round_trip() {
+        let scalar_types = [
+            (PlyType::Char, -100.0),
+            (PlyType::UChar, 200.0),
+            (PlyType::Short, -30000.0),
+            (PlyType::UShort, 60000.0),
+            (PlyType::Int, -2000000000.0),
+            (PlyType::UInt, 4000000000.0),
+            (PlyType::Float, 0.1f32 as f64),
+            (PlyType::Double, 0.1),
+        ];
+        let list = [0.0, 70000.0, 3.0];
+        let mut properties: Vec<PlyProperty> = scalar_types
+            .iter()
+            .enumerate()
+            .map(|(i, &(ty, _))| PlyProperty::Scalar(format!("prop{}", i), ty))
+            .collect();
+        properties.push(PlyProperty::List(
+            "list".to_string(),
+            PlyType::UChar,
+            PlyType::UInt,
+        ));
+        let comments = [format!("{} 12", UNLABELLED_COMMENT)];
+
+        for format in FORMATS {
+            let elements = [
+                PlyElement::new("data", 2, properties.clone()),
+                PlyElement::new("empty", 0, Vec::new()),
+            ];
+            let mut content = Vec::new();
+            write_header(&mut content, format, &comments, &elements).unwrap();
+            for _ in 0..2 {
+                let mut row = PlyRow::new();
+                for &(ty, val) in scalar_types.iter() {
+                    row.push(ty, val);
+                }
+                row.push_list(PlyType::UChar, PlyType::UInt, &list)
+                    .write(&mut content, format)
+                    .unwrap();
+            }
+
+            let ply = read_ply(&mut content.as_slice()).unwrap();
+            assert_eq!(ply.format, format);
+            assert_eq!(ply.comment_value(UNLABELLED_COMMENT), Some(12));
+            assert_eq!(ply.element("empty").unwrap().rows.len(), 0);
+            let elem = ply.element("data").unwrap();
+            assert_eq!(elem.property_index(&["list"]), Some(scalar_types.len()));
+            assert_eq!(elem.rows.len(), 2);
+            for row in elem.rows.iter() {
+                for (value, &(ty, val)) in row.iter().zip(scalar_types.iter()) {
+                    let value = value.scalar().unwrap();
+                    if ty == PlyType::Float {
+                        // ascii floats are read back as doubles
+                        assert_eq!(value as f32, val as f32);
+                    } else {
+                        assert_eq!(value, val);
+                    }
+                }
+                assert_eq!(row[scalar_types.len()].list().unwrap(), &list.to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_content() {
+        let elements = [PlyElement::new(
+            "vertex",
+            2,
+            vec![PlyProperty::Scalar("x".to_string(), PlyType::Float)],
+        )];
+        for format in FORMATS {
+            let mut content = Vec::new();
+            write_header(&mut content, format, &[], &elements).unwrap();
+            PlyRow::new()
+                .push(PlyType::Float, 1.0)
+                .write(&mut content, format)
+                .unwrap();
+            assert!(read_ply(&mut content.as_slice()).is_err());
+        }
+    }
+}
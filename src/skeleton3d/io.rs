@@ -1,60 +1,83 @@
 use anyhow::Result;
-use rand::Rng;
-use std::collections::HashMap;
+use nalgebra::base::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, BufRead, Write};
 
+use crate::geometry::Real;
+use crate::mesh3d::io::{self as mesh_io, parse_next, LoadError, LoadErrorKind};
+use crate::ply::{self, PlyElement, PlyFormat, PlyProperty, PlyRow, PlyType};
+use crate::skeleton3d::skeleton3d::Sphere;
 use crate::skeleton3d::Skeleton3D;
 
-fn write_alveola(
-    file: &mut File,
-    skel_ind_to_ind: &HashMap<&usize, i32>,
-    alv: &Vec<usize>,
-) -> Result<()> {
+/// Map keys in increasing order, for reproducible outputs
+fn sorted_keys<V>(map: &HashMap<usize, V>) -> Vec<&usize> {
+    let mut keys: Vec<&usize> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+/// Computes a random color per alveola label, the last one being used for unlabelled alveolae
+///
+/// Giving a seed makes the colors reproducible
+pub fn label_colors<T: Real>(skeleton: &Skeleton3D<T>, opt_seed: Option<u64>) -> Vec<[u8; 3]> {
+    let lab_max = skeleton.labels.iter().fold(0, |lm, (_, opt_lab)| {
+        if let &Some(lab) = opt_lab {
+            if lm > lab {
+                lm
+            } else {
+                lab
+            }
+        } else {
+            lm
+        }
+    }) + 1;
+    mesh_io::random_colors(lab_max + 1, opt_seed)
+}
+
+/// Triangulates an alveola, zigzagging between both ends of its node list
+fn alveola_triangles(alv: &[usize]) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::new();
     for i in 1..(alv.len() >> 1) {
-        writeln!(
-            file,
-            "f {}// {}// {}//",
-            skel_ind_to_ind[&alv[alv.len() - i]],
-            skel_ind_to_ind[&alv[i - 1]],
-            skel_ind_to_ind[&alv[i]],
-        )?;
-        writeln!(
-            file,
-            "f {}// {}// {}//",
-            skel_ind_to_ind[&alv[alv.len() - i - 1]],
-            skel_ind_to_ind[&alv[alv.len() - i]],
-            skel_ind_to_ind[&alv[i]],
-        )?;
+        triangles.push([alv[alv.len() - i], alv[i - 1], alv[i]]);
+        triangles.push([alv[alv.len() - i - 1], alv[alv.len() - i], alv[i]]);
     }
     if alv.len() % 2 == 1 {
         let ind = alv.len() >> 1;
+        triangles.push([alv[ind - 1], alv[ind], alv[ind + 1]]);
+    }
+    triangles
+}
+
+fn write_alveola<W: Write>(
+    file: &mut W,
+    skel_ind_to_ind: &HashMap<&usize, i32>,
+    alv: &Vec<usize>,
+) -> Result<()> {
+    for tri in alveola_triangles(alv) {
         writeln!(
             file,
             "f {}// {}// {}//",
-            skel_ind_to_ind[&alv[ind - 1]],
-            skel_ind_to_ind[&alv[ind]],
-            skel_ind_to_ind[&alv[ind + 1]],
+            skel_ind_to_ind[&tri[0]], skel_ind_to_ind[&tri[1]], skel_ind_to_ind[&tri[2]],
         )?;
     }
     Ok(())
 }
 
-/// Save skeleton as .obj file
-pub fn save_obj(
-    filename: &str,
-    skeleton: &Skeleton3D,
+/// Writes skeleton in .obj format
+pub fn write_obj<T: Real, W: Write>(
+    file: &mut W,
+    skeleton: &Skeleton3D<T>,
     opt_material_file: Option<&str>,
 ) -> Result<()> {
-    let mut file = File::create(filename)?;
-
     if let Some(material_file) = opt_material_file {
         writeln!(file, "mtllib {}", material_file)?;
     }
 
     let mut skel_ind_to_ind = HashMap::new();
     let mut ind = 1;
-    for (skel_ind, sph) in skeleton.nodes.iter() {
+    for skel_ind in sorted_keys(&skeleton.nodes) {
+        let sph = &skeleton.nodes[skel_ind];
         let vert = sph.center;
         writeln!(file, "v {} {} {}", vert[0], vert[1], vert[2])?;
         skel_ind_to_ind.insert(skel_ind, ind);
@@ -73,7 +96,7 @@ pub fn save_obj(
         }
     }) + 1;
 
-    let alv_ind_none: Vec<usize> = skeleton
+    let mut alv_ind_none: Vec<usize> = skeleton
         .labels
         .iter()
         .filter_map(
@@ -86,15 +109,16 @@ pub fn save_obj(
             },
         )
         .collect();
+    alv_ind_none.sort();
     if alv_ind_none.len() != 0 {
         writeln!(file, "g sheet_no_label")?;
         for ind in alv_ind_none.iter() {
-            write_alveola(&mut file, &skel_ind_to_ind, &skeleton.alveolae[ind])?;
+            write_alveola(file, &skel_ind_to_ind, &skeleton.alveolae[ind])?;
         }
     }
 
     for lab_curr in 0..lab_max {
-        let alv_ind: Vec<usize> = skeleton
+        let mut alv_ind: Vec<usize> = skeleton
             .labels
             .iter()
             .filter_map(|(&ind, &opt_lab)| {
@@ -109,25 +133,43 @@ pub fn save_obj(
                 }
             })
             .collect();
+        alv_ind.sort();
         if alv_ind.len() != 0 {
             writeln!(file, "g sheet{}", lab_curr)?;
             if opt_material_file.is_some() {
                 writeln!(file, "usemtl mtl_sheet{}", lab_curr)?;
             }
             for ind in alv_ind.iter() {
-                write_alveola(&mut file, &skel_ind_to_ind, &skeleton.alveolae[ind])?;
+                write_alveola(file, &skel_ind_to_ind, &skeleton.alveolae[ind])?;
             }
         }
     }
+    file.flush()?;
 
     Ok(())
 }
 
-/// Save material file
-pub fn save_mtl(filename: &str, skeleton: &Skeleton3D) -> Result<()> {
-    let mut file = File::create(filename)?;
+/// Save skeleton as .obj file
+pub fn save_obj<T: Real>(
+    filename: &str,
+    skeleton: &Skeleton3D<T>,
+    opt_material_file: Option<&str>,
+) -> Result<()> {
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_obj(&mut file, skeleton, opt_material_file)
+}
+
+/// Writes materials in .mtl format
+///
+/// Colors are indexed by label, random colors are computed if not given
+pub fn write_mtl<T: Real, W: Write>(
+    file: &mut W,
+    skeleton: &Skeleton3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+) -> Result<Vec<[u8; 3]>> {
+    let vec_col = colors.unwrap_or_else(|| label_colors(skeleton, None));
 
-    let mut siz_sheet = HashMap::new();
+    let mut siz_sheet = BTreeMap::new();
     for (_, &opt_lab) in skeleton.labels.iter() {
         if let Some(lab) = opt_lab {
             siz_sheet
@@ -137,66 +179,99 @@ pub fn save_mtl(filename: &str, skeleton: &Skeleton3D) -> Result<()> {
         }
     }
 
-    let mut rng = rand::thread_rng();
     for (&lab, _) in siz_sheet.iter() {
-        let rand_r = rng.gen_range(0..11) as f32;
-        let rand_g = rng.gen_range(0..11) as f32;
-        let rand_b = rng.gen_range(0..11) as f32;
-        let col_r = rand_r / 10.0;
-        let col_g = rand_g / 10.0;
-        let col_b = rand_b / 10.0;
+        let col = vec_col
+            .get(lab)
+            .ok_or(anyhow::Error::msg("write_mtl(): missing label color"))?;
+        let col_r = col[0] as f32 / 255.0;
+        let col_g = col[1] as f32 / 255.0;
+        let col_b = col[2] as f32 / 255.0;
         writeln!(file, "newmtl mtl_sheet{}", lab)?;
         writeln!(file, "Kd {} {} {}", col_r, col_g, col_b)?;
     }
+    file.flush()?;
 
-    Ok(())
+    Ok(vec_col)
 }
 
-/// Save radii as .rad file
-pub fn save_rad(filename: &str, skeleton: &Skeleton3D) -> Result<()> {
-    let mut file = File::create(filename)?;
+/// Save material file
+///
+/// Colors are indexed by label, random colors are computed if not given
+pub fn save_mtl<T: Real>(
+    filename: &str,
+    skeleton: &Skeleton3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+) -> Result<Vec<[u8; 3]>> {
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_mtl(&mut file, skeleton, colors)
+}
 
-    for (_, sph) in skeleton.nodes.iter() {
-        let rad = sph.radius;
+/// Writes radii in .rad format
+pub fn write_rad<T: Real, W: Write>(file: &mut W, skeleton: &Skeleton3D<T>) -> Result<()> {
+    for skel_ind in sorted_keys(&skeleton.nodes) {
+        let rad = skeleton.nodes[skel_ind].radius;
         writeln!(file, "{}", rad)?;
     }
+    file.flush()?;
 
     Ok(())
 }
 
-/// Save skeleton as .ply file
-pub fn save_ply(
-    filename: &str,
-    skeleton: &Skeleton3D,
+/// Save radii as .rad file
+pub fn save_rad<T: Real>(filename: &str, skeleton: &Skeleton3D<T>) -> Result<()> {
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_rad(&mut file, skeleton)
+}
+
+fn write_ply_format<T: Real, W: Write>(
+    writer: &mut W,
+    skeleton: &Skeleton3D<T>,
     colors: Option<Vec<[u8; 3]>>,
+    format: PlyFormat,
 ) -> Result<Vec<[u8; 3]>> {
-    let mut file = File::create(filename)?;
-
-    writeln!(file, "ply")?;
-    writeln!(file, "format ascii 1.0")?;
-
-    writeln!(file, "element vertex {}", skeleton.nodes.len())?;
-    writeln!(file, "property float x")?;
-    writeln!(file, "property float y")?;
-    writeln!(file, "property float z")?;
-    writeln!(file, "property float radius")?;
-    writeln!(file, "property uchar red")?;
-    writeln!(file, "property uchar green")?;
-    writeln!(file, "property uchar blue")?;
+    let vec_col = colors.unwrap_or_else(|| label_colors(skeleton, None));
 
-    writeln!(file, "element face {}", skeleton.alveolae.len())?;
-    writeln!(file, "property list uchar int vertex_index")?;
-    writeln!(file, "property uchar label")?;
-    writeln!(file, "property uchar red")?;
-    writeln!(file, "property uchar green")?;
-    writeln!(file, "property uchar blue")?;
-
-    writeln!(file, "end_header")?;
+    let ty_coord = PlyType::float_for::<T>();
+    let ty_label = PlyType::unsigned_for(vec_col.len() - 1);
+    let ty_count = PlyType::unsigned_for(
+        skeleton
+            .alveolae
+            .iter()
+            .fold(0, |nb_max, (_, alv_nods)| nb_max.max(alv_nods.len())),
+    );
+    let comments = [format!("{} {}", ply::UNLABELLED_COMMENT, vec_col.len() - 1)];
+    let elements = [
+        PlyElement::new(
+            "vertex",
+            skeleton.nodes.len(),
+            vec![
+                PlyProperty::Scalar("x".to_string(), ty_coord),
+                PlyProperty::Scalar("y".to_string(), ty_coord),
+                PlyProperty::Scalar("z".to_string(), ty_coord),
+                PlyProperty::Scalar("radius".to_string(), ty_coord),
+                PlyProperty::Scalar("red".to_string(), PlyType::UChar),
+                PlyProperty::Scalar("green".to_string(), PlyType::UChar),
+                PlyProperty::Scalar("blue".to_string(), PlyType::UChar),
+            ],
+        ),
+        PlyElement::new(
+            "face",
+            skeleton.alveolae.len(),
+            vec![
+                PlyProperty::List("vertex_index".to_string(), ty_count, PlyType::Int),
+                PlyProperty::Scalar("label".to_string(), ty_label),
+                PlyProperty::Scalar("red".to_string(), PlyType::UChar),
+                PlyProperty::Scalar("green".to_string(), PlyType::UChar),
+                PlyProperty::Scalar("blue".to_string(), PlyType::UChar),
+            ],
+        ),
+    ];
+    ply::write_header(writer, format, &comments, &elements)?;
 
     let mut min_rad = -1.0;
     let mut max_rad = -1.0;
     for (_, sph) in skeleton.nodes.iter() {
-        let rad = sph.radius;
+        let rad = sph.radius.to_f64();
         if min_rad < 0.0 || min_rad < rad {
             min_rad = rad;
         }
@@ -206,76 +281,518 @@ pub fn save_ply(
     }
 
     let mut skel_ind_to_ind = HashMap::new();
-    let mut ind = 0;
-    for (skel_ind, sph) in skeleton.nodes.iter() {
+    for (ind, skel_ind) in sorted_keys(&skeleton.nodes).into_iter().enumerate() {
+        let sph = &skeleton.nodes[skel_ind];
         let vert = sph.center;
-        let rad = sph.radius;
+        let rad = sph.radius.to_f64();
 
-        let p = (rad - min_rad) / (max_rad - min_rad);
-        writeln!(
-            file,
-            "{} {} {} {} {} {} {}",
-            vert[0],
-            vert[1],
-            vert[2],
-            rad,
-            (p * 255.0) as u8,
-            0,
-            ((1.0 - p) * 255.0) as u8
-        )?;
+        // uniform radii give a single color
+        let p = if max_rad != min_rad {
+            (rad - min_rad) / (max_rad - min_rad)
+        } else {
+            0.0
+        };
+        PlyRow::new()
+            .push(ty_coord, vert[0].to_f64())
+            .push(ty_coord, vert[1].to_f64())
+            .push(ty_coord, vert[2].to_f64())
+            .push(ty_coord, rad)
+            .push(PlyType::UChar, (p * 255.0) as u8 as f64)
+            .push(PlyType::UChar, 0.0)
+            .push(PlyType::UChar, ((1.0 - p) * 255.0) as u8 as f64)
+            .write(writer, format)?;
         skel_ind_to_ind.insert(skel_ind, ind);
-        ind = ind + 1;
     }
 
-    let vec_col = if let Some(col) = colors {
-        col
+    for alv_ind in sorted_keys(&skeleton.alveolae) {
+        let alv_nods = &skeleton.alveolae[alv_ind];
+        let alv_inds: Vec<f64> = alv_nods.iter().map(|i| skel_ind_to_ind[i] as f64).collect();
+        let lab = skeleton.labels[alv_ind].unwrap_or(vec_col.len() - 1);
+        PlyRow::new()
+            .push_list(ty_count, PlyType::Int, &alv_inds)
+            .push(ty_label, lab as f64)
+            .push(PlyType::UChar, vec_col[lab][0] as f64)
+            .push(PlyType::UChar, vec_col[lab][1] as f64)
+            .push(PlyType::UChar, vec_col[lab][2] as f64)
+            .write(writer, format)?;
+    }
+    writer.flush()?;
+
+    Ok(vec_col)
+}
+
+/// Writes skeleton in .ply format
+pub fn write_ply<T: Real, W: Write>(
+    writer: &mut W,
+    skeleton: &Skeleton3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+) -> Result<Vec<[u8; 3]>> {
+    write_ply_format(writer, skeleton, colors, PlyFormat::Ascii)
+}
+
+/// Writes skeleton in binary (little endian) .ply format
+pub fn write_ply_binary<T: Real, W: Write>(
+    writer: &mut W,
+    skeleton: &Skeleton3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+) -> Result<Vec<[u8; 3]>> {
+    write_ply_format(writer, skeleton, colors, PlyFormat::BinaryLittleEndian)
+}
+
+/// Save skeleton as .ply file
+pub fn save_ply<T: Real>(
+    filename: &str,
+    skeleton: &Skeleton3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+) -> Result<Vec<[u8; 3]>> {
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_ply(&mut file, skeleton, colors)
+}
+
+/// Save skeleton as binary (little endian) .ply file
+pub fn save_ply_binary<T: Real>(
+    filename: &str,
+    skeleton: &Skeleton3D<T>,
+    colors: Option<Vec<[u8; 3]>>,
+) -> Result<Vec<[u8; 3]>> {
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_ply_binary(&mut file, skeleton, colors)
+}
+
+/// Adds an edge to the skeleton, unless already added
+fn add_unique_edge<T: Real>(
+    skeleton: &mut Skeleton3D<T>,
+    edge_inds: &mut HashMap<[usize; 2], usize>,
+    ind_nod1: usize,
+    ind_nod2: usize,
+) {
+    let edge = if ind_nod1 < ind_nod2 {
+        [ind_nod1, ind_nod2]
     } else {
-        let lab_max = skeleton.labels.iter().fold(0, |lm, (_, opt_lab)| {
-            if let &Some(lab) = opt_lab {
-                if lm > lab {
-                    lm
-                } else {
-                    lab
+        [ind_nod2, ind_nod1]
+    };
+    if !edge_inds.contains_key(&edge) {
+        let ind_edge = edge_inds.len();
+        edge_inds.insert(edge, ind_edge);
+        skeleton.add_edge(ind_edge, edge);
+    }
+}
+
+/// Reads skeleton from .ply content, as written by write_ply
+pub fn read_ply<T: Real, R: BufRead>(mut reader: R) -> Result<Skeleton3D<T>> {
+    let ply_data = ply::read_ply(&mut reader)?;
+
+    let mut skeleton = Skeleton3D::new();
+
+    let vertex_elem = ply_data
+        .element("vertex")
+        .ok_or(anyhow::Error::msg("Expected vertex element"))?;
+    let mut ind_props = [0; 4];
+    for (i, name) in ["x", "y", "z", "radius"].iter().enumerate() {
+        ind_props[i] = vertex_elem
+            .property_index(&[name])
+            .ok_or(anyhow::Error::msg(format!(
+                "Expected vertex property {}",
+                name
+            )))?;
+    }
+    for (ind_node, row) in vertex_elem.rows.iter().enumerate() {
+        let center = Vector3::new(
+            nalgebra::convert(row[ind_props[0]].scalar()?),
+            nalgebra::convert(row[ind_props[1]].scalar()?),
+            nalgebra::convert(row[ind_props[2]].scalar()?),
+        );
+        let radius = nalgebra::convert(row[ind_props[3]].scalar()?);
+        skeleton.nodes.insert(ind_node, Sphere { center, radius });
+    }
+
+    if let Some(face_elem) = ply_data.element("face") {
+        let ind_vertices = face_elem
+            .property_index(&["vertex_index", "vertex_indices"])
+            .ok_or(anyhow::Error::msg("Expected face vertex indices"))?;
+        let opt_ind_label = face_elem.property_index(&["label"]);
+        let opt_unlabelled = ply_data.comment_value(ply::UNLABELLED_COMMENT);
+        let mut edge_inds: HashMap<[usize; 2], usize> = HashMap::new();
+        for (ind_alveola, row) in face_elem.rows.iter().enumerate() {
+            let alv_nods: Vec<usize> = row[ind_vertices]
+                .list()?
+                .iter()
+                .map(|&ind| ind as usize)
+                .collect();
+            if let Some(&ind) = alv_nods
+                .iter()
+                .find(|&ind| !skeleton.nodes.contains_key(ind))
+            {
+                return Err(anyhow::Error::msg(format!(
+                    "Alveola {} refers to missing node {}",
+                    ind_alveola, ind
+                )));
+            }
+
+            // alveola boundary segments are skeleton edges
+            for i in 0..alv_nods.len() {
+                let ind_nod1 = alv_nods[i];
+                let ind_nod2 = alv_nods[(i + 1) % alv_nods.len()];
+                add_unique_edge(&mut skeleton, &mut edge_inds, ind_nod1, ind_nod2);
+            }
+
+            skeleton.add_alveola(ind_alveola, alv_nods);
+            if let Some(ind_label) = opt_ind_label {
+                let label = row[ind_label].scalar()? as usize;
+                if opt_unlabelled != Some(label) {
+                    skeleton.set_label(ind_alveola, label);
                 }
-            } else {
-                lm
             }
-        }) + 1;
-        let mut vec_col = Vec::new();
-        let mut rng = rand::thread_rng();
-        for _ in 0..(lab_max + 1) {
-            let rand_r = rng.gen_range(0..11) as f32;
-            let rand_g = rng.gen_range(0..11) as f32;
-            let rand_b = rng.gen_range(0..11) as f32;
-            let col_r = (255.0 * rand_r / 10.0) as u8;
-            let col_g = (255.0 * rand_g / 10.0) as u8;
-            let col_b = (255.0 * rand_b / 10.0) as u8;
-            vec_col.push([col_r, col_g, col_b]);
         }
-        vec_col
-    };
+    }
+
+    Ok(skeleton)
+}
+
+/// Adds file path to loading errors
+fn located_in_file(err: anyhow::Error, filename: &str) -> anyhow::Error {
+    match err.downcast::<LoadError>() {
+        Ok(err) => anyhow::Error::new(err.in_file(filename)),
+        Err(err) => err,
+    }
+}
+
+/// Loads skeleton from .ply file, as written by save_ply
+pub fn load_ply<T: Real>(filename: &str) -> Result<Skeleton3D<T>> {
+    let file = File::open(filename)?;
+    read_ply(io::BufReader::new(file)).map_err(|err| located_in_file(err, filename))
+}
+
+/// Writes skeleton in .ma (medial axis) format
+///
+/// Alveolae are triangulated, triangulation diagonals being written as edges
+pub fn write_ma<T: Real, W: Write>(file: &mut W, skeleton: &Skeleton3D<T>) -> Result<()> {
+    let mut node_inds: Vec<usize> = skeleton.nodes.keys().copied().collect();
+    node_inds.sort();
+    let skel_ind_to_ind: HashMap<usize, usize> = node_inds
+        .iter()
+        .enumerate()
+        .map(|(ind, &skel_ind)| (skel_ind, ind))
+        .collect();
 
-    for (alv_ind, alv_nods) in skeleton.alveolae.iter() {
-        let label = skeleton.labels[alv_ind];
-        write!(file, "{} ", alv_nods.len())?;
-        for i in alv_nods {
-            write!(file, "{} ", skel_ind_to_ind[i])?;
+    let mut alv_inds: Vec<usize> = skeleton.alveolae.keys().copied().collect();
+    alv_inds.sort();
+    let mut triangles = Vec::new();
+    for ind_alv in alv_inds {
+        for tri in alveola_triangles(&skeleton.alveolae[&ind_alv]) {
+            triangles.push([
+                skel_ind_to_ind[&tri[0]],
+                skel_ind_to_ind[&tri[1]],
+                skel_ind_to_ind[&tri[2]],
+            ]);
         }
-        if let Some(lab) = label {
-            writeln!(
-                file,
-                "{} {} {} {}",
-                lab, vec_col[lab][0], vec_col[lab][1], vec_col[lab][2]
-            )?;
+    }
+
+    let mut edge_inds: Vec<usize> = skeleton.edges.keys().copied().collect();
+    edge_inds.sort();
+    // edges kept in insertion order, the set only deduplicates them
+    let mut edges: Vec<[usize; 2]> = Vec::new();
+    let mut edge_set: HashSet<[usize; 2]> = HashSet::new();
+    let mut add_edge = |ind_nod1: usize, ind_nod2: usize| {
+        let edge = if ind_nod1 < ind_nod2 {
+            [ind_nod1, ind_nod2]
         } else {
-            let lab = vec_col.len() - 1;
-            writeln!(
-                file,
-                "{} {} {} {}",
-                lab, vec_col[lab][0], vec_col[lab][1], vec_col[lab][2]
-            )?;
+            [ind_nod2, ind_nod1]
+        };
+        if edge_set.insert(edge) {
+            edges.push(edge);
+        }
+    };
+    for ind_edge in edge_inds {
+        let edge = skeleton.edges[&ind_edge];
+        add_edge(skel_ind_to_ind[&edge[0]], skel_ind_to_ind[&edge[1]]);
+    }
+    for tri in triangles.iter() {
+        for i in 0..3 {
+            add_edge(tri[i], tri[(i + 1) % 3]);
         }
     }
 
-    Ok(vec_col)
+    writeln!(
+        file,
+        "{} {} {}",
+        node_inds.len(),
+        edges.len(),
+        triangles.len()
+    )?;
+    for skel_ind in node_inds.iter() {
+        let sph = skeleton.nodes[skel_ind];
+        writeln!(
+            file,
+            "v {} {} {} {}",
+            sph.center[0], sph.center[1], sph.center[2], sph.radius
+        )?;
+    }
+    for edge in edges.iter() {
+        writeln!(file, "e {} {}", edge[0], edge[1])?;
+    }
+    for tri in triangles.iter() {
+        writeln!(file, "f {} {} {}", tri[0], tri[1], tri[2])?;
+    }
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Save skeleton as .ma (medial axis) file
+pub fn save_ma<T: Real>(filename: &str, skeleton: &Skeleton3D<T>) -> Result<()> {
+    let mut file = io::BufWriter::new(File::create(filename)?);
+    write_ma(&mut file, skeleton)
+}
+
+/// Reads node indices of a .ma element
+fn read_ma_nodes<'a, T: Real, const N: usize>(
+    words: &mut impl Iterator<Item = &'a str>,
+    skeleton: &Skeleton3D<T>,
+) -> std::result::Result<[usize; N], LoadError> {
+    let mut nodes = [0; N];
+    for node in nodes.iter_mut() {
+        *node = parse_next(words, "node index")?;
+        if !skeleton.nodes.contains_key(node) {
+            return Err(LoadError::new(LoadErrorKind::InvalidVertexIndex(
+                *node as isize,
+            )));
+        }
+    }
+    Ok(nodes)
+}
+
+/// Reads skeleton from .ma (medial axis) content
+///
+/// Each face becomes an alveola, face sides missing from the edge list are added as edges
+pub fn read_ma<T: Real, R: BufRead>(reader: R) -> Result<Skeleton3D<T>> {
+    let mut skeleton = Skeleton3D::new();
+
+    let mut opt_nb_elements: Option<[usize; 3]> = None;
+    let mut nb_read = [0; 3];
+    let mut edge_inds: HashMap<[usize; 2], usize> = HashMap::new();
+    let mut nb_lines = 0;
+    for (ind_line, line_) in reader.lines().enumerate() {
+        nb_lines = ind_line + 1;
+        let located = |err: LoadError| err.at_line(ind_line + 1);
+        let line = line_.map_err(|err| located(LoadError::from(err)))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut line_split = line.split_whitespace();
+        if opt_nb_elements.is_none() {
+            let mut nb_elements = [0; 3];
+            for (nb, what) in
+                nb_elements
+                    .iter_mut()
+                    .zip(["vertex number", "edge number", "face number"])
+            {
+                *nb = parse_next(&mut line_split, what).map_err(located)?;
+            }
+            opt_nb_elements = Some(nb_elements);
+            continue;
+        }
+        match line_split.next() {
+            Some("v") => {
+                let mut vals = [T::zero(); 4];
+                for val in vals.iter_mut() {
+                    *val = parse_next(&mut line_split, "vertex value").map_err(located)?;
+                }
+                let center = Vector3::new(vals[0], vals[1], vals[2]);
+                let radius = vals[3];
+                skeleton.nodes.insert(nb_read[0], Sphere { center, radius });
+                nb_read[0] += 1;
+            }
+            Some("e") => {
+                let edge: [usize; 2] =
+                    read_ma_nodes(&mut line_split, &skeleton).map_err(located)?;
+                add_unique_edge(&mut skeleton, &mut edge_inds, edge[0], edge[1]);
+                nb_read[1] += 1;
+            }
+            Some("f") => {
+                let face: [usize; 3] =
+                    read_ma_nodes(&mut line_split, &skeleton).map_err(located)?;
+                for i in 0..3 {
+                    add_unique_edge(&mut skeleton, &mut edge_inds, face[i], face[(i + 1) % 3]);
+                }
+                skeleton.add_alveola(nb_read[2], face.to_vec());
+                nb_read[2] += 1;
+            }
+            _ => {
+                return Err(
+                    located(LoadError::parse(&format!("Unknown ma element '{}'", line))).into(),
+                )
+            }
+        }
+    }
+
+    let nb_elements =
+        opt_nb_elements.ok_or(LoadError::parse("Expected element numbers").at_line(nb_lines))?;
+    if nb_read != nb_elements {
+        return Err(LoadError::parse(&format!(
+            "Expected {} vertices, {} edges and {} faces, read {}, {} and {}",
+            nb_elements[0], nb_elements[1], nb_elements[2], nb_read[0], nb_read[1], nb_read[2]
+        ))
+        .at_line(nb_lines)
+        .into());
+    }
+
+    Ok(skeleton)
+}
+
+/// Loads skeleton from .ma (medial axis) file
+pub fn load_ma<T: Real>(filename: &str) -> Result<Skeleton3D<T>> {
+    let file = File::open(filename)?;
+    read_ma(io::BufReader::new(file)).map_err(|err| located_in_file(err, filename))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Skeleton with a quad and a triangle alveola sharing an edge, and a fan of the given size
+    fn small_skeleton(fan_size: usize) -> Skeleton3D<f64> {
+        let mut skeleton = Skeleton3D::new();
+        let points = [
+            [0.0, 0.0, 0.0, 0.5],
+            [1.0, 0.0, 0.0, 0.25],
+            [1.0, 1.0, 0.0, 0.125],
+            [0.0, 1.0, 0.1, 0.3],
+            [2.0, 0.5, 0.2, 0.1],
+        ];
+        for (ind_node, pt) in points.iter().enumerate() {
+            let center = Vector3::new(pt[0], pt[1], pt[2]);
+            let radius = pt[3];
+            skeleton.nodes.insert(ind_node, Sphere { center, radius });
+        }
+        let mut fan = vec![0];
+        for i in 0..fan_size {
+            let ang = std::f64::consts::PI * (i as f64) / (fan_size as f64);
+            let center = Vector3::new(-ang.sin(), ang.cos(), 0.0);
+            let radius = 0.01 * (i + 1) as f64;
+            skeleton
+                .nodes
+                .insert(points.len() + i, Sphere { center, radius });
+            fan.push(points.len() + i);
+        }
+        skeleton.add_alveola(0, vec![0, 1, 2, 3]);
+        skeleton.set_label(0, 1);
+        skeleton.add_alveola(1, vec![1, 4, 2]);
+        skeleton.add_alveola(2, fan);
+        skeleton.set_label(2, 0);
+        skeleton
+    }
+
+    fn assert_same_skeleton(skeleton: &Skeleton3D<f64>, other: &Skeleton3D<f64>) {
+        assert_eq!(other.nodes.len(), skeleton.nodes.len());
+        for (ind_node, sph) in skeleton.nodes.iter() {
+            assert_eq!(other.nodes[ind_node].center, sph.center);
+            assert_eq!(other.nodes[ind_node].radius, sph.radius);
+        }
+        assert_eq!(other.alveolae, skeleton.alveolae);
+        assert_eq!(other.labels, skeleton.labels);
+    }
+
+    #[test]
+    fn ply_round_trip() {
+        // more than 255 nodes in an alveola need a ushort list count
+        for (fan_size, ty_count) in [(10, "uchar"), (300, "ushort")] {
+            let skeleton = small_skeleton(fan_size);
+            for format in [
+                PlyFormat::Ascii,
+                PlyFormat::BinaryLittleEndian,
+                PlyFormat::BinaryBigEndian,
+            ] {
+                let mut content = Vec::new();
+                let vec_col = write_ply_format(&mut content, &skeleton, None, format).unwrap();
+                assert_eq!(vec_col.len(), 3);
+                let header = String::from_utf8_lossy(&content);
+                let list_prop = format!("property list {} int vertex_index", ty_count);
+                assert!(header.contains(&list_prop));
+                let skeleton_ply = read_ply(content.as_slice()).unwrap();
+                assert_same_skeleton(&skeleton, &skeleton_ply);
+            }
+        }
+    }
+
+    #[test]
+    fn ply_uniform_radii() {
+        let mut skeleton = small_skeleton(3);
+        for sph in skeleton.nodes.values_mut() {
+            sph.radius = 0.5;
+        }
+        let mut content = Vec::new();
+        write_ply(&mut content, &skeleton, None).unwrap();
+        let ply_data = ply::read_ply(&mut content.as_slice()).unwrap();
+        let vertex_elem = ply_data.element("vertex").unwrap();
+        let ind_red = vertex_elem.property_index(&["red"]).unwrap();
+        for row in vertex_elem.rows.iter() {
+            let color: Vec<f64> = row[ind_red..ind_red + 3]
+                .iter()
+                .map(|val| val.scalar().unwrap())
+                .collect();
+            assert_eq!(color, vec![0.0, 0.0, 255.0]);
+        }
+    }
+
+    fn sorted_edges(skeleton: &Skeleton3D<f64>) -> Vec<[usize; 2]> {
+        let mut edges: Vec<[usize; 2]> = skeleton
+            .edges
+            .values()
+            .map(|&[ind1, ind2]| [ind1.min(ind2), ind1.max(ind2)])
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    #[test]
+    fn ma_round_trip() {
+        let mut skeleton = small_skeleton(3);
+        // an edge outside of any alveola
+        skeleton.add_edge(0, [4, 3]);
+        skeleton.add_edge(1, [0, 1]);
+
+        let mut content = Vec::new();
+        write_ma(&mut content, &skeleton).unwrap();
+        let skeleton_ma: Skeleton3D<f64> = read_ma(content.as_slice()).unwrap();
+
+        assert_eq!(skeleton_ma.nodes.len(), skeleton.nodes.len());
+        for (ind_node, sph) in skeleton.nodes.iter() {
+            assert_eq!(skeleton_ma.nodes[ind_node].center, sph.center);
+            assert_eq!(skeleton_ma.nodes[ind_node].radius, sph.radius);
+        }
+        // alveolae are triangulated, their sides and diagonals become edges
+        let mut triangles = Vec::new();
+        let mut edges = vec![[3, 4], [0, 1]];
+        for ind_alveola in 0..skeleton.alveolae.len() {
+            for tri in alveola_triangles(&skeleton.alveolae[&ind_alveola]) {
+                triangles.push(tri.to_vec());
+                for i in 0..3 {
+                    let [ind1, ind2] = [tri[i], tri[(i + 1) % 3]];
+                    edges.push([ind1.min(ind2), ind1.max(ind2)]);
+                }
+            }
+        }
+        edges.sort();
+        edges.dedup();
+        let triangles_ma: Vec<Vec<usize>> = (0..skeleton_ma.alveolae.len())
+            .map(|ind_alveola| skeleton_ma.alveolae[&ind_alveola].clone())
+            .collect();
+        assert_eq!(triangles_ma, triangles);
+        assert_eq!(sorted_edges(&skeleton_ma), edges);
+        assert_eq!(
+            String::from_utf8(content).unwrap().lines().next(),
+            Some(format!("8 {} {}", edges.len(), triangles.len()).as_str())
+        );
+    }
+
+    #[test]
+    fn ma_unknown_element() {
+        let content = "1 0 0\n# comment\nv 0 0 0 1\nx 1 2\n";
+        let err = read_ma::<f64, _>(content.as_bytes()).err().unwrap();
+        let load_err = err.downcast_ref::<LoadError>().unwrap();
+        assert_eq!(load_err.line, Some(4));
+        assert!(err.to_string().contains("Unknown ma element 'x 1 2'"));
+    }
 }
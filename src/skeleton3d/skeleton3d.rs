@@ -3,29 +3,30 @@ use nalgebra::base::*;
 use std::collections::HashMap;
 
 use crate::geometry::geometry_operations;
+use crate::geometry::Real;
 
 #[derive(Copy, Clone)]
 /// Sphere
-pub struct Sphere {
+pub struct Sphere<T = f32> {
     /// Sphere center
-    pub center: Vector3<f32>,
+    pub center: Vector3<T>,
     /// sphere radius
-    pub radius: f32,
+    pub radius: T,
 }
 
 #[derive(Clone)]
 /// 3D Skeleton structure
-pub struct Skeleton3D {
-    pub(super) nodes: HashMap<usize, Sphere>,
+pub struct Skeleton3D<T = f32> {
+    pub(super) nodes: HashMap<usize, Sphere<T>>,
     pub(super) edges: HashMap<usize, [usize; 2]>, // connects two nodes
     pub(super) alveolae: HashMap<usize, Vec<usize>>, // ordered list of nodes
 
     pub(super) labels: HashMap<usize, Option<usize>>, // alveolae labels
 }
 
-impl Skeleton3D {
+impl<T: Real> Skeleton3D<T> {
     /// Skeleton 3D constructor
-    pub fn new() -> Skeleton3D {
+    pub fn new() -> Skeleton3D<T> {
         Skeleton3D {
             nodes: HashMap::new(),
             edges: HashMap::new(),
@@ -35,7 +36,7 @@ impl Skeleton3D {
     }
 
     /// Adds a node to the skeleton
-    pub fn add_node(&mut self, ind_node: usize, boundary_points: [Vector3<f32>; 4]) -> Result<()> {
+    pub fn add_node(&mut self, ind_node: usize, boundary_points: [Vector3<T>; 4]) -> Result<()> {
         if !self.nodes.contains_key(&ind_node) {
             let (center, radius) = geometry_operations::center_and_radius(boundary_points, None)
                 .ok_or(anyhow::Error::msg("Flat tetrahedron"))?;
@@ -46,7 +47,7 @@ impl Skeleton3D {
     }
 
     /// Get nodes hashmap
-    pub fn get_nodes(&self) -> &HashMap<usize, Sphere> {
+    pub fn get_nodes(&self) -> &HashMap<usize, Sphere<T>> {
         &self.nodes
     }
 